@@ -0,0 +1,71 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compares parsing the bundled registry XML with reading a snapshot from
+//! `RegistryBuilder::cache_dir`.
+//!
+//! Run with `cargo bench -p gl_generator --features serde`.
+
+extern crate gl_generator;
+
+use gl_generator::{Api, RegistryBuilder};
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, Instant};
+
+const RUNS: u32 = 10;
+
+fn time<F: FnMut()>(name: &str, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    let elapsed = start.elapsed() / RUNS;
+    println!("{:<36} {:>10.2} ms", name, elapsed.as_secs_f64() * 1000.0);
+    elapsed
+}
+
+fn bench(name: &str, builder: RegistryBuilder) {
+    let dir = env::temp_dir().join(format!("gl_generator-bench-{}", process::id()));
+    let cached = builder.clone().cache_dir(&dir);
+
+    println!("{}:", name);
+    let parsed = time("  parsing the XML", || {
+        builder.build().unwrap();
+    });
+    time("  parsing and storing a snapshot", || {
+        let _ = fs::remove_dir_all(&dir);
+        cached.build().unwrap();
+    });
+    let loaded = time("  reading the snapshot", || {
+        cached.build().unwrap();
+    });
+    println!(
+        "  saved {:.2} ms ({:.1}x faster)",
+        (parsed.as_secs_f64() - loaded.as_secs_f64()) * 1000.0,
+        parsed.as_secs_f64() / loaded.as_secs_f64()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn main() {
+    bench("GL 4.5 core", RegistryBuilder::new(Api::Gl, (4, 5)));
+    bench(
+        "GL 4.5 core with all extensions",
+        RegistryBuilder::new(Api::Gl, (4, 5)).all_extensions(),
+    );
+    bench("GLES 3.2", RegistryBuilder::new(Api::Gles2, (3, 2)));
+}
@@ -0,0 +1,498 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A command line interface to `gl_generator`, for generating bindings ahead of time instead of
+//! in a build script.
+
+extern crate gl_generator;
+extern crate khronos_api;
+extern crate xml;
+
+use gl_generator::{
+    Api, CommandStyle, DebugStructGenerator, ErrorCheckingGenerator, ErrorReport, Fallbacks,
+    GeneratorOptions, GlobalGenerator, Profile, Registry, RegistryBuilder, StaticGenerator,
+    StaticStructGenerator, StructGenerator, TracingGenerator,
+};
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::process;
+
+use query::Query;
+
+mod query;
+
+const USAGE: &str = "\
+Usage: gl_generator --api <api> --version <major.minor> [options]
+       gl_generator <query> [query options]
+
+Writes bindings for a version of an API to standard output, or to the file given with --out,
+or answers a query about the registry.
+
+Options:
+    --api <api>              gl, glcore, gles1, gles2, glsc2, glx, wgl or egl
+    --version <version>      the newest version to include, for example 4.5
+    --profile <profile>      core (the default) or compatibility
+    --fallbacks <fallbacks>  all (the default) or none
+    --ext <name>             include an extension, or every extension matching a pattern
+                             such as GL_ARB_*
+    --exclude-ext <name>     leave out an extension, even if it matches a pattern
+    --generator <generator>  global (the default), struct, static, static-struct,
+                             debug-struct, tracing, error-checking or error-checking-struct
+    --report <report>        how the error-checking generators report errors: result (the
+                             default) or hook
+    --typed-groups           generate newtypes for enum groups
+    --typed-handles          generate newtypes for object names
+    --slices                 generate wrappers that take slices
+    --enum-names             generate functions that look up the names of enums
+    --replay                 generate a module that replays traces through the bindings
+    --xml <path>             build the registry from this XML instead of the bundled XML,
+                             with any further files as supplemental registries
+    --out <path>             write the bindings to this file
+    -h, --help               print this message
+
+Queries:
+    list-versions            list the versions of the API
+    list-extensions          list the extensions that support the API
+    list-commands            list the commands, with the versions and extensions that need them
+    show-command <name>      show the prototype, aliases and origin of a command
+    find-enum <value|name>   show the enums with a value, such as 0x8B31, or a name
+
+Query options:
+    --api <api>              the API to query, gl by default
+    --version <version>      the newest version to include, the latest by default
+    --profile <profile>      compatibility (the default) or core
+    --ext <name>             for list-commands, only include these extensions instead of
+                             all of them
+    --xml <path>             query this XML instead of the bundled XML
+
+--ext, --exclude-ext and --xml may be given more than once.
+";
+
+/// An error that stops the program.
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    /// The arguments were wrong, so the usage is worth repeating.
+    Usage(String),
+    /// Anything else.
+    Failed(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Usage(ref msg) | Error::Failed(ref msg) => write!(fmt, "{}", msg),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum GeneratorKind {
+    Global,
+    Struct,
+    Static,
+    StaticStruct,
+    DebugStruct,
+    Tracing,
+    ErrorChecking,
+    ErrorCheckingStruct,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    api: Api,
+    version: (u8, u8),
+    profile: Profile,
+    fallbacks: Fallbacks,
+    extensions: Vec<String>,
+    excluded_extensions: Vec<String>,
+    generator: GeneratorKind,
+    report: ErrorReport,
+    options: GeneratorOptions,
+    xml: Vec<String>,
+    out: Option<String>,
+}
+
+fn parse_api(src: &str) -> Result<Api, Error> {
+    match src {
+        "gl" => Ok(Api::Gl),
+        "glcore" => Ok(Api::GlCore),
+        "gles1" => Ok(Api::Gles1),
+        "gles2" => Ok(Api::Gles2),
+        "glsc2" => Ok(Api::Glsc2),
+        "glx" => Ok(Api::Glx),
+        "wgl" => Ok(Api::Wgl),
+        "egl" => Ok(Api::Egl),
+        _ => Err(Error::Usage(format!("unknown API `{}`", src))),
+    }
+}
+
+fn parse_version(src: &str) -> Result<(u8, u8), Error> {
+    let mut parts = src.split('.').map(|part| part.parse::<u8>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(minor)), None) => Ok((major, minor)),
+        _ => Err(Error::Usage(format!(
+            "`{}` is not a version of the form <major>.<minor>",
+            src
+        ))),
+    }
+}
+
+fn parse_profile(src: &str) -> Result<Profile, Error> {
+    match src {
+        "core" => Ok(Profile::Core),
+        "compatibility" => Ok(Profile::Compatibility),
+        _ => Err(Error::Usage(format!("unknown profile `{}`", src))),
+    }
+}
+
+fn parse_fallbacks(src: &str) -> Result<Fallbacks, Error> {
+    match src {
+        "all" => Ok(Fallbacks::All),
+        "none" => Ok(Fallbacks::None),
+        _ => Err(Error::Usage(format!("unknown fallbacks `{}`", src))),
+    }
+}
+
+fn parse_generator(src: &str) -> Result<GeneratorKind, Error> {
+    match src {
+        "global" => Ok(GeneratorKind::Global),
+        "struct" => Ok(GeneratorKind::Struct),
+        "static" => Ok(GeneratorKind::Static),
+        "static-struct" => Ok(GeneratorKind::StaticStruct),
+        "debug-struct" => Ok(GeneratorKind::DebugStruct),
+        "tracing" => Ok(GeneratorKind::Tracing),
+        "error-checking" => Ok(GeneratorKind::ErrorChecking),
+        "error-checking-struct" => Ok(GeneratorKind::ErrorCheckingStruct),
+        _ => Err(Error::Usage(format!("unknown generator `{}`", src))),
+    }
+}
+
+fn parse_report(src: &str) -> Result<ErrorReport, Error> {
+    match src {
+        "result" => Ok(ErrorReport::Result),
+        "hook" => Ok(ErrorReport::Hook),
+        _ => Err(Error::Usage(format!("unknown report `{}`", src))),
+    }
+}
+
+/// Splits an option of the form `--name=value` into its name and value.
+fn split_option(arg: &str) -> (&str, Option<&str>) {
+    match arg.find('=') {
+        Some(i) if arg.starts_with("--") => (&arg[..i], Some(&arg[i + 1..])),
+        _ => (arg, None),
+    }
+}
+
+/// The value of the option `name`, which is either given after an `=`, or is the next argument.
+fn option_value<I>(name: &str, inline_value: Option<&str>, args: &mut I) -> Result<String, Error>
+where
+    I: Iterator<Item = String>,
+{
+    match inline_value.map(str::to_string).or_else(|| args.next()) {
+        Some(value) => Ok(value),
+        None => Err(Error::Usage(format!("`{}` needs a value", name))),
+    }
+}
+
+/// Parses the arguments, not including the name of the program. Returns `None` if the usage
+/// was asked for.
+fn parse_args<I>(args: I) -> Result<Option<Args>, Error>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut api = None;
+    let mut version = None;
+    let mut report = None;
+    let mut parsed = Args {
+        api: Api::Gl,
+        version: (0, 0),
+        profile: Profile::Core,
+        fallbacks: Fallbacks::All,
+        extensions: Vec::new(),
+        excluded_extensions: Vec::new(),
+        generator: GeneratorKind::Global,
+        report: ErrorReport::Result,
+        options: GeneratorOptions::default(),
+        xml: Vec::new(),
+        out: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_option(&arg);
+        let mut value = || option_value(name, inline_value, &mut args);
+
+        match name {
+            "-h" | "--help" => return Ok(None),
+            "--api" => api = Some(parse_api(&value()?)?),
+            "--version" => version = Some(parse_version(&value()?)?),
+            "--profile" => parsed.profile = parse_profile(&value()?)?,
+            "--fallbacks" => parsed.fallbacks = parse_fallbacks(&value()?)?,
+            "--ext" => parsed.extensions.push(value()?),
+            "--exclude-ext" => parsed.excluded_extensions.push(value()?),
+            "--generator" => parsed.generator = parse_generator(&value()?)?,
+            "--report" => report = Some(parse_report(&value()?)?),
+            "--typed-groups" => parsed.options.typed_groups = true,
+            "--typed-handles" => parsed.options.typed_handles = true,
+            "--slices" => parsed.options.slices = true,
+            "--enum-names" => parsed.options.enum_names = true,
+            "--replay" => parsed.options.replay = true,
+            "--xml" => parsed.xml.push(value()?),
+            "--out" => parsed.out = Some(value()?),
+            _ => return Err(Error::Usage(format!("unexpected argument `{}`", arg))),
+        }
+    }
+
+    parsed.api = api.ok_or_else(|| Error::Usage("`--api` is required".to_string()))?;
+    parsed.version = version.ok_or_else(|| Error::Usage("`--version` is required".to_string()))?;
+    if let Some(report) = report {
+        match parsed.generator {
+            GeneratorKind::ErrorChecking | GeneratorKind::ErrorCheckingStruct => {
+                parsed.report = report
+            },
+            _ => {
+                return Err(Error::Usage(
+                    "`--report` needs an error-checking generator".to_string(),
+                ))
+            },
+        }
+    }
+    Ok(Some(parsed))
+}
+
+fn build_registry(args: &Args) -> Result<Registry, Error> {
+    let builder = RegistryBuilder::new(args.api, args.version)
+        .profile(args.profile)
+        .fallbacks(args.fallbacks)
+        .extensions(&args.extensions)
+        .exclude_extensions(&args.excluded_extensions);
+    let registry = match args.xml.len() {
+        0 => builder.build(),
+        _ => Registry::from_paths(builder.filter(), &args.xml),
+    };
+    registry.map_err(|err| Error::Failed(err.to_string()))
+}
+
+fn write_bindings<W: Write>(registry: &Registry, args: &Args, dest: &mut W) -> io::Result<()> {
+    let options = &args.options;
+    match args.generator {
+        GeneratorKind::Global => {
+            registry.write_bindings_with_options(GlobalGenerator, options, dest)
+        },
+        GeneratorKind::Struct => {
+            registry.write_bindings_with_options(StructGenerator, options, dest)
+        },
+        GeneratorKind::Static => {
+            registry.write_bindings_with_options(StaticGenerator, options, dest)
+        },
+        GeneratorKind::StaticStruct => {
+            registry.write_bindings_with_options(StaticStructGenerator, options, dest)
+        },
+        GeneratorKind::DebugStruct => {
+            registry.write_bindings_with_options(DebugStructGenerator, options, dest)
+        },
+        GeneratorKind::Tracing => {
+            registry.write_bindings_with_options(TracingGenerator, options, dest)
+        },
+        GeneratorKind::ErrorChecking | GeneratorKind::ErrorCheckingStruct => {
+            let style = match args.generator {
+                GeneratorKind::ErrorCheckingStruct => CommandStyle::Methods,
+                _ => CommandStyle::Functions,
+            };
+            let generator = ErrorCheckingGenerator {
+                style,
+                report: args.report,
+            };
+            registry.write_bindings_with_options(generator, options, dest)
+        },
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let mut args = env::args().skip(1);
+    let first = args.next();
+    if let Some(query) = first.as_ref().and_then(|arg| Query::from_name(arg)) {
+        return query::run(query, args);
+    }
+
+    let args = match parse_args(first.into_iter().chain(args))? {
+        Some(args) => args,
+        None => {
+            print!("{}", USAGE);
+            return Ok(());
+        },
+    };
+
+    let registry = build_registry(&args)?;
+    let result = match args.out {
+        Some(ref path) => File::create(path)
+            .map(io::BufWriter::new)
+            .and_then(|mut file| {
+                write_bindings(&registry, &args, &mut file)?;
+                file.flush()
+            })
+            .map_err(|err| format!("failed to write `{}`: {}", path, err)),
+        None => {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            match write_bindings(&registry, &args, &mut stdout) {
+                // Piping the bindings into `head` is not a failure
+                Err(ref err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                result => result.map_err(|err| format!("failed to write the bindings: {}", err)),
+            }
+        },
+    };
+    result.map_err(Error::Failed)
+}
+
+fn main() {
+    match run() {
+        Ok(()) => {},
+        Err(err @ Error::Usage(_)) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            process::exit(2);
+        },
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Args, Error, GeneratorKind};
+    use gl_generator::{Api, ErrorReport, Fallbacks, Profile};
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, Error> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_defaults() {
+        let args = parse(&["--api", "gles2", "--version", "3.0"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.api, Api::Gles2);
+        assert_eq!(args.version, (3, 0));
+        assert_eq!(args.profile, Profile::Core);
+        assert_eq!(args.fallbacks, Fallbacks::All);
+        assert_eq!(args.generator, GeneratorKind::Global);
+        assert_eq!(args.report, ErrorReport::Result);
+        assert!(args.extensions.is_empty() && args.xml.is_empty());
+        assert_eq!(args.out, None);
+    }
+
+    #[test]
+    fn test_options() {
+        let args = parse(&[
+            "--api=gl",
+            "--version=4.5",
+            "--profile",
+            "compatibility",
+            "--fallbacks=none",
+            "--ext",
+            "GL_ARB_*",
+            "--ext=GL_KHR_debug",
+            "--exclude-ext",
+            "GL_ARB_shading_language_include",
+            "--generator",
+            "static-struct",
+            "--typed-groups",
+            "--slices",
+            "--enum-names",
+            "--replay",
+            "--out",
+            "src/bindings.rs",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(args.api, Api::Gl);
+        assert_eq!(args.version, (4, 5));
+        assert_eq!(args.profile, Profile::Compatibility);
+        assert_eq!(args.fallbacks, Fallbacks::None);
+        assert_eq!(args.extensions, ["GL_ARB_*", "GL_KHR_debug"]);
+        assert_eq!(
+            args.excluded_extensions,
+            ["GL_ARB_shading_language_include"]
+        );
+        assert_eq!(args.generator, GeneratorKind::StaticStruct);
+        assert!(args.options.typed_groups && args.options.slices && args.options.enum_names);
+        assert!(args.options.replay);
+        assert!(!args.options.typed_handles);
+        assert_eq!(args.out, Some("src/bindings.rs".to_string()));
+    }
+
+    #[test]
+    fn test_error_checking() {
+        let args = parse(&["--api=gl", "--version=4.5", "--generator=error-checking"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.generator, GeneratorKind::ErrorChecking);
+        assert_eq!(args.report, ErrorReport::Result);
+
+        let args = parse(&[
+            "--api=gl",
+            "--version=4.5",
+            "--report",
+            "hook",
+            "--generator",
+            "error-checking-struct",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(args.generator, GeneratorKind::ErrorCheckingStruct);
+        assert_eq!(args.report, ErrorReport::Hook);
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(parse(&["--api", "gl", "--help"]), Ok(None));
+    }
+
+    #[test]
+    fn test_errors() {
+        let usage = |msg: &str| Err(Error::Usage(msg.to_string()));
+        assert_eq!(parse(&["--version", "4.5"]), usage("`--api` is required"));
+        assert_eq!(parse(&["--api", "gl"]), usage("`--version` is required"));
+        assert_eq!(parse(&["--api", "opengl"]), usage("unknown API `opengl`"));
+        assert_eq!(
+            parse(&["--api", "gl", "--version", "4"]),
+            usage("`4` is not a version of the form <major>.<minor>")
+        );
+        assert_eq!(
+            parse(&["--api", "gl", "--out"]),
+            usage("`--out` needs a value")
+        );
+        assert_eq!(
+            parse(&["--api", "gl", "-v"]),
+            usage("unexpected argument `-v`")
+        );
+        assert_eq!(
+            parse(&[
+                "--api=gl",
+                "--version=4.5",
+                "--generator=error-checking",
+                "--report=panic"
+            ]),
+            usage("unknown report `panic`")
+        );
+        assert_eq!(
+            parse(&["--api=gl", "--version=4.5", "--report=hook"]),
+            usage("`--report` needs an error-checking generator")
+        );
+    }
+}
@@ -0,0 +1,701 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queries about the contents of a registry: which versions and extensions there are, and
+//! where commands and enums come from.
+
+use gl_generator::{Api, CType, Cmd, Enum, Profile, Registry, RegistryBuilder};
+use khronos_api;
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use xml::reader::{EventReader, XmlEvent};
+
+use {option_value, parse_api, parse_profile, parse_version, split_option, Error, USAGE};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Query {
+    ListVersions,
+    ListExtensions,
+    ListCommands,
+    ShowCommand,
+    FindEnum,
+}
+
+impl Query {
+    /// The query run by the subcommand `name`, if there is one.
+    pub fn from_name(name: &str) -> Option<Query> {
+        match name {
+            "list-versions" => Some(Query::ListVersions),
+            "list-extensions" => Some(Query::ListExtensions),
+            "list-commands" => Some(Query::ListCommands),
+            "show-command" => Some(Query::ShowCommand),
+            "find-enum" => Some(Query::FindEnum),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Query::ListVersions => "list-versions",
+            Query::ListExtensions => "list-extensions",
+            Query::ListCommands => "list-commands",
+            Query::ShowCommand => "show-command",
+            Query::FindEnum => "find-enum",
+        }
+    }
+
+    /// Whether the query is about a single command or enum, named after the subcommand.
+    fn takes_argument(self) -> bool {
+        self == Query::ShowCommand || self == Query::FindEnum
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct QueryArgs {
+    api: Api,
+    /// The newest version to include, or `None` for the latest one in the registry.
+    version: Option<(u8, u8)>,
+    profile: Profile,
+    extensions: Vec<String>,
+    xml: Vec<String>,
+    /// The command name or enum value or name that the query is about.
+    argument: Option<String>,
+}
+
+/// Parses the arguments that follow the name of the query. Returns `None` if the usage was
+/// asked for.
+fn parse_args<I>(query: Query, args: I) -> Result<Option<QueryArgs>, Error>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut parsed = QueryArgs {
+        api: Api::Gl,
+        version: None,
+        profile: Profile::Compatibility,
+        extensions: Vec::new(),
+        xml: Vec::new(),
+        argument: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = split_option(&arg);
+        let mut value = || option_value(name, inline_value, &mut args);
+
+        match name {
+            "-h" | "--help" => return Ok(None),
+            "--api" => parsed.api = parse_api(&value()?)?,
+            "--version" => parsed.version = Some(parse_version(&value()?)?),
+            "--profile" => parsed.profile = parse_profile(&value()?)?,
+            "--ext" if query == Query::ListCommands => parsed.extensions.push(value()?),
+            "--xml" => parsed.xml.push(value()?),
+            // Enum values such as `-1` look like options, so only `--` marks one
+            _ if query.takes_argument() && parsed.argument.is_none() && !name.starts_with("--") => {
+                parsed.argument = Some(arg.clone())
+            },
+            _ => return Err(Error::Usage(format!("unexpected argument `{}`", arg))),
+        }
+    }
+
+    if query.takes_argument() && parsed.argument.is_none() {
+        return Err(Error::Usage(format!(
+            "`{}` needs a name or value to look for",
+            query.name()
+        )));
+    }
+    Ok(Some(parsed))
+}
+
+/// The XML that the query is about: the first file given with `--xml`, or the registry
+/// bundled with `khronos_api`.
+fn main_source(args: &QueryArgs) -> Result<Cow<'static, [u8]>, Error> {
+    match args.xml.first() {
+        Some(path) => fs::read(path)
+            .map(Cow::Owned)
+            .map_err(|err| Error::Failed(format!("failed to read `{}`: {}", path, err))),
+        None => Ok(Cow::Borrowed(match args.api {
+            Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => khronos_api::GL_XML,
+            Api::Glx => khronos_api::GLX_XML,
+            Api::Wgl => khronos_api::WGL_XML,
+            Api::Egl => khronos_api::EGL_XML,
+        })),
+    }
+}
+
+/// The versions of `api` that `src` has a `<feature>` for, from oldest to newest.
+fn versions(src: &[u8], api: Api) -> Result<Vec<(u8, u8)>, Error> {
+    let mut versions = Vec::new();
+    for event in EventReader::new(src) {
+        let event = event.map_err(|err| Error::Failed(format!("malformed XML: {}", err)))?;
+        if let XmlEvent::StartElement {
+            ref name,
+            ref attributes,
+            ..
+        } = event
+        {
+            if name.local_name != "feature" {
+                continue;
+            }
+            let attribute = |key: &str| {
+                attributes
+                    .iter()
+                    .find(|attr| attr.name.local_name == key)
+                    .map(|attr| &attr.value[..])
+            };
+            // The core profile of OpenGL has the same versions as OpenGL
+            let matches = match attribute("api") {
+                Some("gl") => api == Api::Gl || api == Api::GlCore,
+                Some(feature_api) => feature_api == api.to_string(),
+                None => false,
+            };
+            if let (true, Some(number)) = (matches, attribute("number")) {
+                versions.push(
+                    parse_version(number).map_err(|_| {
+                        Error::Failed(format!("`{}` is not a version number", number))
+                    })?,
+                );
+            }
+        }
+    }
+    versions.sort();
+    versions.dedup();
+    Ok(versions)
+}
+
+/// Builds the registry that the query is about, with all of the extensions unless some were
+/// asked for.
+fn build_registry(args: &QueryArgs, src: &[u8]) -> Result<Registry, Error> {
+    let version = match args.version {
+        Some(version) => version,
+        None => *versions(src, args.api)?.last().ok_or_else(|| {
+            Error::Failed(format!("the registry has no versions of `{}`", args.api))
+        })?,
+    };
+
+    let builder = RegistryBuilder::new(args.api, version).profile(args.profile);
+    let builder = match args.extensions.len() {
+        0 => builder.all_extensions(),
+        _ => builder.extensions(&args.extensions),
+    };
+    let registry = match args.xml.len() {
+        0 => builder.build(),
+        _ => Registry::from_paths(builder.filter(), &args.xml),
+    };
+    registry.map_err(|err| Error::Failed(err.to_string()))
+}
+
+fn cmd_prefix(api: Api) -> &'static str {
+    match api {
+        Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => "gl",
+        Api::Glx => "glX",
+        Api::Wgl => "wgl",
+        Api::Egl => "egl",
+    }
+}
+
+fn enum_prefix(api: Api) -> &'static str {
+    match api {
+        Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => "GL_",
+        Api::Glx => "GLX_",
+        Api::Wgl => "WGL_",
+        Api::Egl => "EGL_",
+    }
+}
+
+fn version_str(version: (u8, u8)) -> String {
+    format!("{}.{}", version.0, version.1)
+}
+
+/// Where a command or enum comes from: the version that introduced it, and the extensions
+/// that provide it.
+fn origin(introduced_in: Option<(u8, u8)>, extensions: &[String]) -> String {
+    introduced_in
+        .map(version_str)
+        .into_iter()
+        .chain(extensions.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes a C declaration of `declarator` with the type `ty`, in the style of the registry, for
+/// example `const GLchar *const*string`.
+fn c_decl(ty: &CType, declarator: &str) -> String {
+    match *ty {
+        CType::Named(ref name) => format!("{} {}", name, declarator).trim_end().to_string(),
+        CType::Struct(ref name) => format!("struct {} {}", name, declarator)
+            .trim_end()
+            .to_string(),
+        CType::Const(ref ty) => match **ty {
+            CType::Named(_) | CType::Struct(_) => format!("const {}", c_decl(ty, declarator)),
+            // A `const` pointer, where the qualifier goes after the `*`
+            ref ty => match declarator.chars().next() {
+                Some('*') | Some('[') | None => c_decl(ty, &format!("const{}", declarator)),
+                _ => c_decl(ty, &format!("const {}", declarator)),
+            },
+        },
+        CType::Pointer(ref ty) => c_decl(ty, &format!("*{}", declarator)),
+        CType::Array(ref ty, len) => c_decl(ty, &format!("{}[{}]", declarator, len)),
+    }
+}
+
+fn prototype(api: Api, cmd: &Cmd) -> String {
+    let params = cmd
+        .params
+        .iter()
+        .map(|param| c_decl(&param.c_ty, &param.ident))
+        .collect::<Vec<_>>();
+    let params = match params.len() {
+        0 => "void".to_string(),
+        _ => params.join(", "),
+    };
+    let ident = format!("{}{}", cmd_prefix(api), cmd.proto.ident);
+    format!("{}({});", c_decl(&cmd.proto.c_ty, &ident), params)
+}
+
+fn profile_str(profile: Profile) -> &'static str {
+    match profile {
+        Profile::Core => "core",
+        Profile::Compatibility => "compatibility",
+    }
+}
+
+/// Writes a line of a description, with the labels lined up.
+fn field(out: &mut String, label: &str, value: &str) {
+    if !value.is_empty() {
+        out.push_str(&format!("    {:<15}{}\n", format!("{}:", label), value));
+    }
+}
+
+fn list_extensions(registry: &Registry) -> String {
+    registry
+        .extensions
+        .iter()
+        .map(|ext| format!("{}\n", ext))
+        .collect()
+}
+
+fn list_commands(registry: &Registry) -> String {
+    let prefix = cmd_prefix(registry.api);
+    let width = registry
+        .cmds
+        .iter()
+        .map(|cmd| cmd.proto.ident.len())
+        .max()
+        .unwrap_or(0)
+        + prefix.len();
+    registry
+        .cmds
+        .iter()
+        .map(|cmd| {
+            let ident = format!("{}{}", prefix, cmd.proto.ident);
+            let origin = origin(cmd.introduced_in, &cmd.required_by_extensions);
+            let line = format!("{:<width$}  {}", ident, origin, width = width);
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// Describes the command named `name`, which may leave out the API's prefix.
+fn show_command(registry: &Registry, name: &str) -> Result<String, Error> {
+    let api = registry.api;
+    let ident = name.strip_prefix(cmd_prefix(api)).unwrap_or(name);
+    let cmd = registry
+        .cmds
+        .iter()
+        .find(|cmd| cmd.proto.ident == ident || cmd.proto.ident == name)
+        .ok_or_else(|| Error::Failed(format!("there is no command named `{}`", name)))?;
+
+    let prefixed = |ident: &str| format!("{}{}", cmd_prefix(api), ident);
+    let aliases: Vec<_> = registry
+        .aliases
+        .get(&cmd.proto.ident)
+        .map(|aliases| aliases.iter().map(|alias| prefixed(alias)).collect())
+        .unwrap_or_default();
+
+    let mut out = format!("{}\n", prototype(api, cmd));
+    field(
+        &mut out,
+        "introduced in",
+        &match cmd.introduced_in {
+            Some(version) => version_str(version),
+            None => "only in extensions".to_string(),
+        },
+    );
+    field(
+        &mut out,
+        "extensions",
+        &cmd.required_by_extensions.join(", "),
+    );
+    field(
+        &mut out,
+        "alias of",
+        &cmd.alias
+            .as_ref()
+            .map(|alias| prefixed(alias))
+            .unwrap_or_default(),
+    );
+    field(&mut out, "aliases", &aliases.join(", "));
+    field(
+        &mut out,
+        "removed from",
+        &cmd.removed_in_profile
+            .map(|profile| format!("the {} profile", profile_str(profile)))
+            .unwrap_or_default(),
+    );
+    if let Some(ref glx) = cmd.glx {
+        let opcode = match glx.name {
+            Some(ref name) => format!("{} ({})", glx.opcode, name),
+            None => glx.opcode.clone(),
+        };
+        field(&mut out, "GLX opcode", &opcode);
+    }
+    Ok(out)
+}
+
+/// Parses an enum value as it is written in the registry, for example `0x8B31`, `-1` or
+/// `0xFFFFFFFFFFFFFFFFull`.
+fn parse_value(src: &str) -> Option<i128> {
+    let src = src.trim().trim_end_matches(&['u', 'U', 'l', 'L'][..]);
+    if src.starts_with("0x") || src.starts_with("0X") {
+        i128::from_str_radix(&src[2..], 16).ok()
+    } else {
+        src.parse().ok()
+    }
+}
+
+/// Describes the enums that have the value `query`, or if it is not a number, the name.
+fn find_enum(registry: &Registry, query: &str) -> Result<String, Error> {
+    let prefix = enum_prefix(registry.api);
+    let ident = query.strip_prefix(prefix).unwrap_or(query);
+    let value = parse_value(query);
+    let matches = |enm: &&Enum| match value {
+        Some(value) => parse_value(&enm.value) == Some(value),
+        None => enm.ident == ident,
+    };
+
+    let mut out = String::new();
+    for enm in registry.enums.iter().filter(matches) {
+        let groups = registry
+            .groups
+            .values()
+            .filter(|group| group.enums.contains(&enm.ident))
+            .map(|group| &group.ident[..])
+            .collect::<Vec<_>>();
+
+        out.push_str(&format!(
+            "{}{} = {} ({})\n",
+            prefix, enm.ident, enm.value, enm.ty
+        ));
+        field(
+            &mut out,
+            "introduced in",
+            &match enm.introduced_in {
+                Some(version) => version_str(version),
+                None => "only in extensions".to_string(),
+            },
+        );
+        field(
+            &mut out,
+            "extensions",
+            &enm.required_by_extensions.join(", "),
+        );
+        field(
+            &mut out,
+            "alias of",
+            &enm.alias
+                .as_ref()
+                .map(|alias| format!("{}{}", prefix, alias))
+                .unwrap_or_default(),
+        );
+        field(&mut out, "groups", &groups.join(", "));
+    }
+
+    match out.len() {
+        0 => Err(Error::Failed(format!(
+            "there is no enum with the value or name `{}`",
+            query
+        ))),
+        _ => Ok(out),
+    }
+}
+
+fn answer(query: Query, args: &QueryArgs) -> Result<String, Error> {
+    let src = main_source(args)?;
+    if query == Query::ListVersions {
+        return Ok(versions(&src, args.api)?
+            .into_iter()
+            .map(|version| format!("{}\n", version_str(version)))
+            .collect());
+    }
+
+    let registry = build_registry(args, &src)?;
+    let argument = args.argument.as_ref().map_or("", |arg| &arg[..]);
+    match query {
+        Query::ListVersions => unreachable!(),
+        Query::ListExtensions => Ok(list_extensions(&registry)),
+        Query::ListCommands => Ok(list_commands(&registry)),
+        Query::ShowCommand => show_command(&registry, argument),
+        Query::FindEnum => find_enum(&registry, argument),
+    }
+}
+
+/// Runs `query` with the arguments that follow its name, and prints the answer.
+pub fn run<I>(query: Query, args: I) -> Result<(), Error>
+where
+    I: IntoIterator<Item = String>,
+{
+    let args = match parse_args(query, args)? {
+        Some(args) => args,
+        None => {
+            print!("{}", USAGE);
+            return Ok(());
+        },
+    };
+
+    let answer = answer(query, &args)?;
+    let stdout = io::stdout();
+    match stdout.lock().write_all(answer.as_bytes()) {
+        Err(ref err) if err.kind() != io::ErrorKind::BrokenPipe => Err(Error::Failed(format!(
+            "failed to write the answer: {}",
+            err
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        c_decl, find_enum, list_commands, parse_args, parse_value, show_command, versions, Query,
+    };
+    use gl_generator::{Api, CType, Fallbacks, Filter, Profile, Registry};
+    use Error;
+
+    const SRC: &str = r#"
+        <registry>
+            <groups>
+                <group name="ShaderType">
+                    <enum name="GL_VERTEX_SHADER"/>
+                </group>
+            </groups>
+            <enums namespace="GL">
+                <enum value="0x8B31" name="GL_VERTEX_SHADER"/>
+                <enum value="0x8B31" name="GL_VERTEX_SHADER_ARB"/>
+                <enum value="0xFFFFFFFF" name="GL_INVALID_INDEX" type="u"/>
+            </enums>
+            <commands namespace="GL">
+                <command>
+                    <proto>void <name>glShaderSource</name></proto>
+                    <param><ptype>GLuint</ptype> <name>shader</name></param>
+                    <param><ptype>GLsizei</ptype> <name>count</name></param>
+                    <param>const <ptype>GLchar</ptype> *const*<name>string</name></param>
+                    <param>const <ptype>GLint</ptype> *<name>length</name></param>
+                </command>
+                <command>
+                    <proto>void <name>glShaderSourceARB</name></proto>
+                    <param><ptype>GLuint</ptype> <name>shader</name></param>
+                    <param><ptype>GLsizei</ptype> <name>count</name></param>
+                    <param>const <ptype>GLchar</ptype> *const*<name>string</name></param>
+                    <param>const <ptype>GLint</ptype> *<name>length</name></param>
+                    <alias name="glShaderSource"/>
+                </command>
+                <command>
+                    <proto><ptype>GLuint</ptype> <name>glCreateProgram</name></proto>
+                    <glx type="single" opcode="199"/>
+                </command>
+            </commands>
+            <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                <require>
+                    <command name="glCreateProgram"/>
+                </require>
+            </feature>
+            <feature api="gles2" name="GL_ES_VERSION_2_0" number="2.0"/>
+            <feature api="gl" name="GL_VERSION_2_0" number="2.0">
+                <require>
+                    <enum name="GL_VERTEX_SHADER"/>
+                    <enum name="GL_INVALID_INDEX"/>
+                    <command name="glShaderSource"/>
+                </require>
+            </feature>
+            <extensions>
+                <extension name="GL_ARB_shader_objects" supported="gl">
+                    <require>
+                        <enum name="GL_VERTEX_SHADER_ARB"/>
+                        <command name="glShaderSourceARB"/>
+                    </require>
+                </extension>
+            </extensions>
+        </registry>
+    "#;
+
+    fn registry() -> Registry {
+        let filter = Filter::new(
+            Api::Gl,
+            (2, 0),
+            Profile::Compatibility,
+            Fallbacks::All,
+            ["GL_ARB_shader_objects"],
+        );
+        Registry::from_sources(&filter, vec![SRC.as_bytes()]).unwrap()
+    }
+
+    fn parse(query: Query, args: &[&str]) -> Result<Option<super::QueryArgs>, Error> {
+        parse_args(query, args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(Query::FindEnum, &["--api=gles2", "-1"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.api, Api::Gles2);
+        assert_eq!(args.version, None);
+        assert_eq!(args.argument, Some("-1".to_string()));
+
+        let usage = |msg: &str| Err(Error::Usage(msg.to_string()));
+        assert_eq!(
+            parse(Query::ShowCommand, &["--api", "gl"]),
+            usage("`show-command` needs a name or value to look for")
+        );
+        assert_eq!(
+            parse(Query::ListVersions, &["glClear"]),
+            usage("unexpected argument `glClear`")
+        );
+        assert_eq!(
+            parse(Query::ListExtensions, &["--ext", "GL_ARB_*"]),
+            usage("unexpected argument `--ext`")
+        );
+    }
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(versions(SRC.as_bytes(), Api::Gl), Ok(vec![(1, 0), (2, 0)]));
+        assert_eq!(
+            versions(SRC.as_bytes(), Api::GlCore),
+            Ok(vec![(1, 0), (2, 0)])
+        );
+        assert_eq!(versions(SRC.as_bytes(), Api::Gles2), Ok(vec![(2, 0)]));
+        assert_eq!(versions(SRC.as_bytes(), Api::Egl), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_c_decl() {
+        let named = |name: &str| Box::new(CType::Named(name.to_string()));
+        let string = CType::Pointer(Box::new(CType::Const(Box::new(CType::Pointer(Box::new(
+            CType::Const(named("GLchar")),
+        ))))));
+        assert_eq!(c_decl(&string, "string"), "const GLchar *const*string");
+        assert_eq!(c_decl(&CType::Pointer(named("void")), "data"), "void *data");
+        assert_eq!(
+            c_decl(
+                &CType::Pointer(Box::new(CType::Pointer(named("void")))),
+                "data"
+            ),
+            "void **data"
+        );
+        assert_eq!(
+            c_decl(&CType::Array(named("GLfloat"), 16), "m"),
+            "GLfloat m[16]"
+        );
+        assert_eq!(
+            c_decl(
+                &CType::Pointer(Box::new(CType::Struct("_cl_event".to_string()))),
+                "event"
+            ),
+            "struct _cl_event *event"
+        );
+        assert_eq!(c_decl(&CType::Named("GLenum".to_string()), ""), "GLenum");
+    }
+
+    #[test]
+    fn test_list_commands() {
+        assert_eq!(
+            list_commands(&registry()),
+            "glCreateProgram    1.0\n\
+             glShaderSource     2.0\n\
+             glShaderSourceARB  GL_ARB_shader_objects\n"
+        );
+    }
+
+    #[test]
+    fn test_show_command() {
+        let registry = registry();
+        let expected = "\
+void glShaderSource(GLuint shader, GLsizei count, const GLchar *const*string, const GLint *length);
+    introduced in: 2.0
+    aliases:       glShaderSourceARB
+";
+        assert_eq!(
+            show_command(&registry, "glShaderSource"),
+            Ok(expected.to_string())
+        );
+        assert_eq!(
+            show_command(&registry, "ShaderSource"),
+            Ok(expected.to_string())
+        );
+
+        let expected = "\
+void glShaderSourceARB(GLuint shader, GLsizei count, const GLchar *const*string, const GLint *length);
+    introduced in: only in extensions
+    extensions:    GL_ARB_shader_objects
+    alias of:      glShaderSource
+";
+        assert_eq!(
+            show_command(&registry, "glShaderSourceARB"),
+            Ok(expected.to_string())
+        );
+        assert!(show_command(&registry, "CreateProgram")
+            .unwrap()
+            .ends_with("    GLX opcode:    199\n"));
+        assert_eq!(
+            show_command(&registry, "glClear"),
+            Err(Error::Failed(
+                "there is no command named `glClear`".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("0x8B31"), Some(0x8B31));
+        assert_eq!(parse_value("0xFFFFFFFFu"), Some(0xFFFFFFFF));
+        assert_eq!(
+            parse_value("0xFFFFFFFFFFFFFFFFull"),
+            Some(0xFFFFFFFFFFFFFFFF)
+        );
+        assert_eq!(parse_value("-1"), Some(-1));
+        assert_eq!(parse_value("VERTEX_SHADER"), None);
+    }
+
+    #[test]
+    fn test_find_enum() {
+        let registry = registry();
+        let by_value = find_enum(&registry, "0x8b31").unwrap();
+        assert!(by_value.starts_with("GL_VERTEX_SHADER = 0x8B31 (GLenum)\n"));
+        assert!(by_value.contains("    groups:        ShaderType\n"));
+        assert!(by_value.contains("GL_VERTEX_SHADER_ARB = 0x8B31 (GLenum)\n"));
+        assert!(by_value.contains("    extensions:    GL_ARB_shader_objects\n"));
+
+        assert_eq!(
+            find_enum(&registry, "4294967295"),
+            find_enum(&registry, "GL_INVALID_INDEX")
+        );
+        assert_eq!(
+            find_enum(&registry, "INVALID_INDEX").unwrap(),
+            "GL_INVALID_INDEX = 0xFFFFFFFF (GLuint)\n    introduced in: 2.0\n"
+        );
+        assert!(find_enum(&registry, "0x1234").is_err());
+    }
+}
@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use registry::Registry;
+use registry::{Binding, Registry};
 use std::io;
 
+use super::enum_names;
+
 #[allow(missing_copy_implementations)]
 pub struct DebugStructGenerator;
 
@@ -28,10 +30,15 @@ impl super::Generator for DebugStructGenerator {
         write_enums(registry, dest)?;
         write_fnptr_struct_def(dest)?;
         write_panicking_fns(registry, dest)?;
+        write_logger(registry, dest)?;
         write_struct(registry, dest)?;
         write_impl(registry, dest)?;
         Ok(())
     }
+
+    fn command_style(&self) -> super::CommandStyle {
+        super::CommandStyle::Methods
+    }
 }
 
 /// Creates a `__gl_imports` module which contains all the external symbols that we need for the
@@ -44,9 +51,11 @@ where
         dest,
         r#"
         mod __gl_imports {{
+            pub use std::fmt;
             pub use std::mem;
             pub use std::marker::Send;
             pub use std::os::raw;
+            pub use std::sync::Arc;
         }}
     "#
     )
@@ -67,7 +76,7 @@ where
     "#
     )?;
 
-    super::gen_types(registry.api, dest)?;
+    super::gen_types(registry, dest)?;
 
     writeln!(dest, "}}")
 }
@@ -144,6 +153,98 @@ where
     )
 }
 
+/// Creates the `CallLogger` trait that the calls are passed to, along with `LogCallLogger`, the
+/// logger that is used by default.
+///
+/// Enum arguments that belong to a group are wrapped in an `EnumArg`, whose `Debug` output is
+/// the name of the enum.
+fn write_logger<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+        /// Receives the commands that are called through a `{api}`, and the errors that they cause.
+        #[allow(dead_code)]
+        pub trait CallLogger {{
+            /// Called before each command, with the name of the command, such as
+            /// `"glBindTexture"`, and its arguments. Arguments from enum groups are formatted by
+            /// name, such as `TEXTURE_2D`.
+            fn log_call(&self, command: &'static str, args: &[&dyn __gl_imports::fmt::Debug]);
+
+            /// Called after a command that caused an error, with the value that `GetError`
+            /// returned.
+            fn log_error(&self, command: &'static str, error: u32);
+        }}
+
+        /// The default `CallLogger`, which writes calls with the `log` crate at the `debug` level,
+        /// and errors at the `error` level.
+        #[derive(Copy, Clone, Debug, Default)]
+        pub struct LogCallLogger;
+
+        impl CallLogger for LogCallLogger {{
+            fn log_call(&self, command: &'static str, args: &[&dyn __gl_imports::fmt::Debug]) {{
+                ::log::debug!(
+                    "{{}}({{}})",
+                    command,
+                    args.iter()
+                        .map(|arg| format!("{{:?}}", arg))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }}
+
+            fn log_error(&self, command: &'static str, error: u32) {{
+                ::log::error!("{{:?}} after {{}}", EnumArg(error, "ErrorCode"), command);
+            }}
+        }}
+
+        /// A `GLenum` argument from a group, which is formatted by name.
+        #[allow(dead_code)]
+        #[derive(Copy, Clone)]
+        struct EnumArg(u32, &'static str);
+
+        impl __gl_imports::fmt::Debug for EnumArg {{
+            fn fmt(&self, f: &mut __gl_imports::fmt::Formatter) -> __gl_imports::fmt::Result {{
+                match __gl_enum_names::enum_name_in(self.1, self.0) {{
+                    Some(name) => f.write_str(name),
+                    None => write!(f, "0x{{:04X}}", self.0),
+                }}
+            }}
+        }}
+    "#,
+        api = super::gen_struct_name(registry.api),
+    )?;
+
+    let mut groups = enum_names::used_groups(registry);
+    if registry.groups.contains_key("ErrorCode") {
+        groups.insert("ErrorCode");
+    }
+    writeln!(dest, "mod __gl_enum_names {{")?;
+    enum_names::write_enum_name_in_fn(registry, &groups, "u32", dest)?;
+    writeln!(dest, "}}")
+}
+
+/// The expression that passes an argument to the `CallLogger`.
+fn gen_log_arg(registry: &Registry, binding: &Binding) -> String {
+    let group = binding
+        .group
+        .as_ref()
+        .and_then(|group| registry.groups.get(group))
+        .filter(|group| group.enums_type.as_ref().map(String::as_ref) != Some("bitmask"));
+    match group {
+        _ if binding.ty.contains("GLDEBUGPROC") => "&\"<callback>\"".to_string(),
+        Some(group) if binding.ty == "types::GLenum" => {
+            format!("&EnumArg({}, \"{}\")", binding.ident, group.ident)
+        },
+        Some(group) if binding.ty == format!("groups::{}", group.ident) => {
+            format!("&EnumArg({}.0, \"{}\")", binding.ident, group.ident)
+        },
+        _ => format!("&{}", binding.ident),
+    }
+}
+
 /// Creates a structure which stores all the `FnPtr` of the bindings.
 ///
 /// The name of the struct corresponds to the namespace.
@@ -164,8 +265,19 @@ where
         if let Some(v) = registry.aliases.get(&cmd.proto.ident) {
             writeln!(dest, "/// Fallbacks: {}", v.join(", "))?;
         }
+        if let Some(v) = super::gen_since_version(registry, cmd) {
+            writeln!(
+                dest,
+                "/// Available since version {}, so it may not be loaded.",
+                v
+            )?;
+        }
         writeln!(dest, "pub {name}: FnPtr,", name = cmd.proto.ident)?;
     }
+    writeln!(
+        dest,
+        "logger: __gl_imports::Arc<dyn CallLogger + Send + Sync>,"
+    )?;
     writeln!(dest, "_priv: ()")?;
 
     writeln!(dest, "}}")
@@ -222,62 +334,61 @@ where
             },
         )?
     }
+    writeln!(dest, "logger: __gl_imports::Arc::new(LogCallLogger),")?;
     writeln!(dest, "_priv: ()")?;
 
     writeln!(
         dest,
         "}}
+        }}
+
+        /// Passes the commands that are called through these bindings, and their errors, to
+        /// `logger` instead of the `LogCallLogger`.
+        #[allow(dead_code)]
+        pub fn set_logger<L>(&mut self, logger: L) where L: CallLogger + Send + Sync + 'static {{
+            self.logger = __gl_imports::Arc::new(logger);
         }}"
     )?;
 
+    let has_get_error = registry
+        .cmds
+        .iter()
+        .any(|cmd| cmd.proto.ident == "GetError");
+
     for cmd in &registry.cmds {
-        let idents = super::gen_parameters(cmd, true, false);
-        let typed_params = super::gen_parameters(cmd, false, true);
-        let println = format!(
-            "println!(\"[OpenGL] {}({})\" {});",
-            cmd.proto.ident,
-            (0..idents.len())
-                .map(|_| "{:?}".to_string())
-                .collect::<Vec<_>>()
-                .join(", "),
-            idents
-                .iter()
-                .zip(typed_params.iter())
-                .map(|(name, ty)| if ty.contains("GLDEBUGPROC") {
-                    format!(", \"<callback>\"")
-                } else {
-                    format!(", {}", name)
-                })
-                .collect::<Vec<_>>()
-                .concat()
-        );
+        let symbol = super::gen_symbol_name(registry.api, &cmd.proto.ident);
+        let log_error = if has_get_error && cmd.proto.ident != "GetError" {
+            format!(
+                "match __gl_imports::mem::transmute::<_, extern \"system\" fn() -> u32>\
+                    (self.GetError.f)() {{ 0 => (), r => self.logger.log_error(\"{}\", r) }}",
+                symbol
+            )
+        } else {
+            String::new()
+        };
 
         writeln!(dest,
                       "#[allow(non_snake_case, unused_variables, dead_code)]
             #[inline] pub unsafe fn {name}(&self, {params}) -> {return_suffix} {{ \
-                {println}
+                self.logger.log_call(\"{symbol}\", &[{log_args}]);
                 let r = __gl_imports::mem::transmute::<_, extern \"system\" fn({typed_params}) -> {return_suffix}>\
                     (self.{name}.f)({idents});
-                {print_err}
+                {log_error}
                 r
             }}",
                       name = cmd.proto.ident,
                       params = super::gen_parameters(cmd, true, true).join(", "),
-                      typed_params = typed_params.join(", "),
+                      typed_params = super::gen_parameters(cmd, false, true).join(", "),
                       return_suffix = cmd.proto.ty,
-                      idents = idents.join(", "),
-                      println = println,
-                      print_err = if cmd.proto.ident != "GetError" &&
-                                     registry
-                                         .cmds
-                                         .iter()
-                                         .find(|cmd| cmd.proto.ident == "GetError")
-                                         .is_some() {
-                          format!(r#"match __gl_imports::mem::transmute::<_, extern "system" fn() -> u32>
-                    (self.GetError.f)() {{ 0 => (), r => println!("[OpenGL] ^ GL error triggered: {{}}", r) }}"#)
-                      } else {
-                          format!("")
-                      })?
+                      idents = super::gen_parameters(cmd, true, false).join(", "),
+                      symbol = symbol,
+                      log_args = cmd
+                          .params
+                          .iter()
+                          .map(|param| gen_log_arg(registry, param))
+                          .collect::<Vec<_>>()
+                          .join(", "),
+                      log_error = log_error)?
     }
 
     writeln!(
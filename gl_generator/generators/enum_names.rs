@@ -0,0 +1,207 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tables that turn enum values back into their names.
+
+use registry::{Api, Enum, Registry};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+/// The type of the enums that are named by the lookup tables of an API.
+fn enum_type(api: Api) -> &'static str {
+    match api {
+        Api::Egl => "EGLenum",
+        _ => "GLenum",
+    }
+}
+
+/// Parses the value of an enum, such as `0x0DE1` or `0xFFFFFFFFu`.
+fn parse_value(value: &str) -> Option<u64> {
+    let value = value.trim_end_matches(&['u', 'U', 'l', 'L'][..]);
+    if value.starts_with("0x") || value.starts_with("0X") {
+        u64::from_str_radix(&value[2..], 16).ok()
+    } else {
+        value.parse().ok()
+    }
+}
+
+/// Orders the enums that share a value, so that the name of the first one is used: enums from
+/// the earliest version come first, enums that are only required by extensions come last, and
+/// aliases come after the enums that they alias.
+fn precedence(enm: &Enum) -> (bool, Option<(u8, u8)>, bool) {
+    (
+        enm.introduced_in.is_none(),
+        enm.introduced_in,
+        enm.alias.is_some(),
+    )
+}
+
+/// The names of the enums of type `ty`, by value. When several enums share a value, the one
+/// with the lowest `precedence` is used, or the first one if they are equal.
+fn names_by_value<'a, I>(enums: I, ty: &str) -> BTreeMap<u64, &'a Enum>
+where
+    I: IntoIterator<Item = &'a Enum>,
+{
+    let mut names: BTreeMap<u64, &Enum> = BTreeMap::new();
+    for enm in enums.into_iter().filter(|enm| enm.ty == ty) {
+        let value = match parse_value(&enm.value) {
+            Some(value) => value,
+            None => continue,
+        };
+        let replace = match names.get(&value) {
+            Some(&other) => precedence(enm) < precedence(other),
+            None => true,
+        };
+        if replace {
+            names.insert(value, enm);
+        }
+    }
+    names
+}
+
+/// Writes the arms of a `match value`, from the values in `names` to their names.
+fn write_arms<W>(names: &BTreeMap<u64, &Enum>, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    for (value, enm) in names {
+        writeln!(dest, "0x{:04X} => Some(\"{}\"),", value, enm.ident)?;
+    }
+    writeln!(dest, "_ => None,")
+}
+
+/// Returns the groups of enums that the commands in the registry take or return, whether or
+/// not they have been given newtypes by `GeneratorOptions::typed_groups`.
+pub fn used_groups(registry: &Registry) -> BTreeSet<&str> {
+    let mut used = BTreeSet::new();
+    for cmd in &registry.cmds {
+        for binding in Some(&cmd.proto).into_iter().chain(&cmd.params) {
+            match binding
+                .group
+                .as_ref()
+                .and_then(|group| registry.groups.get(group))
+            {
+                Some(group) if group.enums_type.as_ref().map(String::as_ref) != Some("bitmask") => {
+                    used.insert(&group.ident[..]);
+                },
+                _ => {},
+            }
+        }
+    }
+    used
+}
+
+/// Creates an `enum_name` function, which returns the name of the enum with a value, or `None`
+/// if there is no such enum in the registry.
+///
+/// `value_ty` is the type of the values, for example `"types::GLenum"`.
+pub fn write_enum_name_fn<W>(registry: &Registry, value_ty: &str, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        "/// Returns the name of the enum that has the value `value`, as in
+        /// `enum_name(0x0DE1) == Some(\"TEXTURE_2D\")`.
+        ///
+        /// Many enums share their values with others, such as `LINES` and `ONE`. Of those, the
+        /// name of the one from the earliest version is returned, so `enum_name_in` is more
+        /// useful when the group of the value is known.
+        #[allow(dead_code)]
+        pub fn enum_name(value: {value_ty}) -> Option<&'static str> {{
+            match value {{",
+        value_ty = value_ty,
+    )?;
+    write_arms(
+        &names_by_value(&registry.enums, enum_type(registry.api)),
+        dest,
+    )?;
+    writeln!(
+        dest,
+        "}}
+        }}"
+    )
+}
+
+/// Creates an `enum_name_in` function, which returns the name of the enum with a value in one of
+/// `groups`, or `None` if the group has no enum with that value.
+///
+/// `value_ty` is the type of the values, for example `"types::GLenum"`.
+pub fn write_enum_name_in_fn<W>(
+    registry: &Registry,
+    groups: &BTreeSet<&str>,
+    value_ty: &str,
+    dest: &mut W,
+) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let enums: BTreeMap<&str, &Enum> = registry
+        .enums
+        .iter()
+        .map(|enm| (&enm.ident[..], enm))
+        .collect();
+
+    writeln!(
+        dest,
+        "/// Returns the name of the enum in `group` that has the value `value`, as in
+        /// `enum_name_in(\"TextureTarget\", 0x0DE1) == Some(\"TEXTURE_2D\")`.
+        #[allow(dead_code, unused_variables)]
+        pub fn enum_name_in(group: &str, value: {value_ty}) -> Option<&'static str> {{
+            match group {{",
+        value_ty = value_ty,
+    )?;
+
+    for group in groups
+        .iter()
+        .filter_map(|ident| registry.groups.get(*ident))
+    {
+        let members: BTreeSet<&str> = group.enums.iter().map(|ident| &ident[..]).collect();
+        let members = members
+            .into_iter()
+            .filter_map(|ident| enums.get(ident).cloned());
+        let names = names_by_value(members, enum_type(registry.api));
+        if names.is_empty() {
+            continue;
+        }
+        writeln!(dest, "\"{}\" => match value {{", group.ident)?;
+        write_arms(&names, dest)?;
+        writeln!(dest, "}},")?;
+    }
+
+    writeln!(
+        dest,
+        "_ => None,
+            }}
+        }}"
+    )
+}
+
+/// Creates the `enum_name` and `enum_name_in` functions that are enabled by
+/// `GeneratorOptions::enum_names`. `enum_name_in` knows about every group that isn't a bitmask.
+pub fn write_enum_names<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let value_ty = format!("types::{}", enum_type(registry.api));
+    let groups = registry
+        .groups
+        .values()
+        .filter(|group| group.enums_type.as_ref().map(String::as_ref) != Some("bitmask"))
+        .map(|group| &group.ident[..])
+        .collect();
+
+    write_enum_name_fn(registry, &value_ty, dest)?;
+    write_enum_name_in_fn(registry, &groups, &value_ty, dest)
+}
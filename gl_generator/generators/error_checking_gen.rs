@@ -0,0 +1,517 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use registry::{Cmd, Registry};
+use std::io;
+use Api;
+
+use super::{global_gen, struct_gen, CommandStyle, GeneratorOptions};
+
+/// A generator that calls `GetError` after each command, and reports the errors as values of a
+/// `GlError` enum in an `errors` module.
+///
+/// Commands that can't be checked return what they normally would. These are `GetError`
+/// itself, the commands of APIs that have no `GetError`, such as GLX, and `Begin` along with
+/// the commands that may be called between `Begin` and `End`, where `GetError` may not be. Their
+/// errors are reported by the next command that is checked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ErrorCheckingGenerator {
+    /// Whether the commands are free functions, as with `GlobalGenerator`, or methods of a
+    /// struct, as with `StructGenerator`.
+    pub style: CommandStyle,
+    /// How the errors are reported.
+    pub report: ErrorReport,
+}
+
+/// How the bindings written by `ErrorCheckingGenerator` report errors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorReport {
+    /// Each command returns an `errors::Result`, with the first error that `GetError` returned.
+    Result,
+    /// Each command returns what it normally would, and passes errors to the hook installed
+    /// with `errors::set_hook`, along with the name of the command and its arguments. The
+    /// default hook logs the errors with `log::error!`, so the crate that includes the bindings
+    /// must depend on `log`. Installing `errors::panic_hook` panics on errors instead.
+    Hook,
+}
+
+impl super::Generator for ErrorCheckingGenerator {
+    fn write<W>(&self, registry: &Registry, dest: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        match self.style {
+            CommandStyle::Functions => {
+                global_gen::write_header(dest)?;
+                global_gen::write_metaloadfn(dest)?;
+                global_gen::write_type_aliases(registry, dest)?;
+                global_gen::write_enums(registry, dest)?;
+                write_errors_mod(self.report, dest)?;
+                write_check_fn(registry, self.style, dest)?;
+                write_fns(registry, self.report, dest)?;
+                global_gen::write_fnptr_struct_def(dest)?;
+                global_gen::write_ptrs(registry, dest)?;
+                global_gen::write_fn_mods(registry, dest)?;
+                global_gen::write_panicking_fns(registry, dest)?;
+                global_gen::write_load_fn(registry, dest)?;
+            },
+            CommandStyle::Methods => {
+                struct_gen::write_header(dest)?;
+                struct_gen::write_type_aliases(registry, dest)?;
+                struct_gen::write_enums(registry, dest)?;
+                write_errors_mod(self.report, dest)?;
+                struct_gen::write_fnptr_struct_def(dest)?;
+                struct_gen::write_panicking_fns(registry, dest)?;
+                struct_gen::write_struct(registry, dest)?;
+                write_impl(registry, self.report, dest)?;
+            },
+        }
+        Ok(())
+    }
+
+    fn command_style(&self) -> CommandStyle {
+        self.style
+    }
+
+    /// Like the default implementation, except that with `ErrorReport::Result`, the wrappers
+    /// written by `GeneratorOptions::write_modules` return the `errors::Result` of the command.
+    fn write_with_options<W>(
+        &self,
+        registry: &Registry,
+        options: &GeneratorOptions,
+        dest: &mut W,
+    ) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let registry = options.apply(registry);
+        self.write(&registry, dest)?;
+
+        let mut wrapped = registry.clone();
+        wrapped.cmds = registry
+            .cmds
+            .iter()
+            .map(|cmd| {
+                let mut cmd = cmd.clone();
+                cmd.proto.ty = gen_return_type(&registry, &cmd, self.report).into();
+                cmd
+            })
+            .collect();
+        options.write_modules(&wrapped, self.style, dest)
+    }
+}
+
+/// Whether a command may be called between `Begin` and `End`, from its name.
+fn is_allowed_in_begin_end(ident: &str) -> bool {
+    // These are followed by the number of components, as in `Vertex3f` or `VertexAttribI4i`
+    const COUNTED: &[&str] = &[
+        "Color",
+        "ColorP",
+        "EvalCoord",
+        "EvalPoint",
+        "MultiTexCoord",
+        "MultiTexCoordP",
+        "Normal",
+        "NormalP",
+        "SecondaryColor",
+        "SecondaryColorP",
+        "TexCoord",
+        "TexCoordP",
+        "Vertex",
+        "VertexAttrib",
+        "VertexAttribI",
+        "VertexAttribL",
+        "VertexAttribP",
+        "VertexP",
+    ];
+    // These are followed by the type of their parameters, as in `Materialfv` or `Indexub`
+    const TYPED: &[&str] = &["EdgeFlag", "FogCoord", "Index", "Material"];
+
+    let followed_by = |prefix: &str, test: &dyn Fn(char) -> bool| {
+        ident.starts_with(prefix)
+            && match ident[prefix.len()..].chars().next() {
+                Some(c) => test(c),
+                None => true,
+            }
+    };
+    ident == "ArrayElement"
+        || ident == "CallList"
+        || ident == "CallLists"
+        || COUNTED
+            .iter()
+            .any(|prefix| followed_by(prefix, &|c| c.is_ascii_digit()))
+        || TYPED
+            .iter()
+            .any(|prefix| followed_by(prefix, &|c| c.is_ascii_lowercase()))
+}
+
+/// Whether `GetError` is called after a command.
+fn is_checked(registry: &Registry, cmd: &Cmd) -> bool {
+    let has_cmd = |ident: &str| registry.cmds.iter().any(|cmd| cmd.proto.ident == ident);
+    let gl = match registry.api {
+        Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => true,
+        Api::Glx | Api::Wgl | Api::Egl => false,
+    };
+    let immediate_mode = has_cmd("Begin")
+        && (cmd.proto.ident == "Begin" || is_allowed_in_begin_end(&cmd.proto.ident));
+    gl && has_cmd("GetError") && cmd.proto.ident != "GetError" && !immediate_mode
+}
+
+/// Creates an `errors` module which contains the `GlError` enum, and either a `Result` alias or
+/// the hook that errors are passed to.
+fn write_errors_mod<W>(report: ErrorReport, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+        pub mod errors {{
+            #![allow(dead_code)]
+
+            use std::error;
+            use std::fmt;
+            use super::types;
+
+            /// An error returned by `GetError`.
+            #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+            pub enum GlError {{
+                /// `INVALID_ENUM`: an enum argument was out of range.
+                InvalidEnum,
+                /// `INVALID_VALUE`: a numeric argument was out of range.
+                InvalidValue,
+                /// `INVALID_OPERATION`: the command is not allowed in the current state.
+                InvalidOperation,
+                /// `STACK_OVERFLOW`: the command would have overflowed a stack.
+                StackOverflow,
+                /// `STACK_UNDERFLOW`: the command would have underflowed a stack.
+                StackUnderflow,
+                /// `OUT_OF_MEMORY`: there was not enough memory left to run the command.
+                OutOfMemory,
+                /// `INVALID_FRAMEBUFFER_OPERATION`: the framebuffer is not complete.
+                InvalidFramebufferOperation,
+                /// `CONTEXT_LOST`: the context was lost, for example by a graphics card reset.
+                ContextLost,
+                /// Any other error code, such as one defined by an extension.
+                Other(types::GLenum),
+            }}
+
+            impl GlError {{
+                /// Decodes a value returned by `GetError`, or returns `None` for `NO_ERROR`.
+                pub fn from_code(code: types::GLenum) -> Option<GlError> {{
+                    match code {{
+                        0 => None,
+                        0x0500 => Some(GlError::InvalidEnum),
+                        0x0501 => Some(GlError::InvalidValue),
+                        0x0502 => Some(GlError::InvalidOperation),
+                        0x0503 => Some(GlError::StackOverflow),
+                        0x0504 => Some(GlError::StackUnderflow),
+                        0x0505 => Some(GlError::OutOfMemory),
+                        0x0506 => Some(GlError::InvalidFramebufferOperation),
+                        0x0507 => Some(GlError::ContextLost),
+                        code => Some(GlError::Other(code)),
+                    }}
+                }}
+
+                /// The value that `GetError` returned.
+                pub fn code(&self) -> types::GLenum {{
+                    match *self {{
+                        GlError::InvalidEnum => 0x0500,
+                        GlError::InvalidValue => 0x0501,
+                        GlError::InvalidOperation => 0x0502,
+                        GlError::StackOverflow => 0x0503,
+                        GlError::StackUnderflow => 0x0504,
+                        GlError::OutOfMemory => 0x0505,
+                        GlError::InvalidFramebufferOperation => 0x0506,
+                        GlError::ContextLost => 0x0507,
+                        GlError::Other(code) => code,
+                    }}
+                }}
+            }}
+
+            impl fmt::Display for GlError {{
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{
+                    match *self {{
+                        GlError::InvalidEnum => write!(f, "INVALID_ENUM"),
+                        GlError::InvalidValue => write!(f, "INVALID_VALUE"),
+                        GlError::InvalidOperation => write!(f, "INVALID_OPERATION"),
+                        GlError::StackOverflow => write!(f, "STACK_OVERFLOW"),
+                        GlError::StackUnderflow => write!(f, "STACK_UNDERFLOW"),
+                        GlError::OutOfMemory => write!(f, "OUT_OF_MEMORY"),
+                        GlError::InvalidFramebufferOperation => {{
+                            write!(f, "INVALID_FRAMEBUFFER_OPERATION")
+                        }},
+                        GlError::ContextLost => write!(f, "CONTEXT_LOST"),
+                        GlError::Other(code) => write!(f, "error 0x{{:04X}}", code),
+                    }}
+                }}
+            }}
+
+            impl error::Error for GlError {{}}
+    "#
+    )?;
+
+    match report {
+        ErrorReport::Result => writeln!(
+            dest,
+            "
+            /// The result of a command that was checked with `GetError`.
+            pub type Result<T> = ::std::result::Result<T, GlError>;
+        "
+        )?,
+        ErrorReport::Hook => writeln!(
+            dest,
+            r#"
+            use std::mem;
+            use std::sync::atomic::{{AtomicUsize, Ordering}};
+
+            /// A function that is called with each error, along with the name of the command
+            /// that caused it and the command's arguments.
+            pub type Hook = fn(GlError, &'static str, &[&dyn fmt::Debug]);
+
+            /// The hook that was installed with `set_hook`, or zero for `default_hook`.
+            static HOOK: AtomicUsize = AtomicUsize::new(0);
+
+            /// Installs the hook that errors are passed to, in place of `default_hook`.
+            pub fn set_hook(hook: Hook) {{
+                HOOK.store(hook as usize, Ordering::SeqCst);
+            }}
+
+            fn join_args(args: &[&dyn fmt::Debug]) -> String {{
+                args.iter()
+                    .map(|arg| format!("{{:?}}", arg))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }}
+
+            /// The hook that is used until `set_hook` is called, which logs the error.
+            pub fn default_hook(error: GlError, command: &'static str, args: &[&dyn fmt::Debug]) {{
+                ::log::error!("{{}} after {{}}({{}})", error, command, join_args(args));
+            }}
+
+            /// A hook that panics on errors, for tests and debug builds. Install it with
+            /// `set_hook(panic_hook)`.
+            pub fn panic_hook(error: GlError, command: &'static str, args: &[&dyn fmt::Debug]) {{
+                panic!("{{}} after {{}}({{}})", error, command, join_args(args))
+            }}
+
+            #[doc(hidden)]
+            #[inline(never)]
+            pub fn report(error: GlError, command: &'static str, args: &[&dyn fmt::Debug]) {{
+                let hook = match HOOK.load(Ordering::SeqCst) {{
+                    0 => default_hook,
+                    hook => unsafe {{ mem::transmute::<usize, Hook>(hook) }},
+                }};
+                hook(error, command, args)
+            }}
+        "#
+        )?,
+    }
+
+    writeln!(dest, "}}")
+}
+
+/// Creates the `check_error` function, or method with `CommandStyle::Methods`, which calls
+/// `GetError` until there are no errors left, and returns the first one.
+fn write_check_fn<W>(registry: &Registry, style: CommandStyle, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    if !registry.cmds.iter().any(|cmd| is_checked(registry, cmd)) {
+        return Ok(());
+    }
+
+    let (self_param, get_error) = match style {
+        CommandStyle::Functions => ("", "storage::GetError.f"),
+        CommandStyle::Methods => ("&self", "self.GetError.f"),
+    };
+    writeln!(
+        dest,
+        "#[inline(never)]
+        fn check_error({self_param}) -> Option<errors::GlError> {{
+            let get_error = unsafe {{
+                __gl_imports::mem::transmute::<_, extern \"system\" fn() -> types::GLenum>({get_error})
+            }};
+            let error = errors::GlError::from_code(get_error());
+            // Each kind of error has a flag of its own, so there may be more to clear. A lost
+            // context may report `CONTEXT_LOST` forever, so this gives up eventually.
+            if error.is_some() {{
+                for _ in 0..16 {{
+                    if get_error() == 0 {{
+                        break;
+                    }}
+                }}
+            }}
+            error
+        }}",
+        self_param = self_param,
+        get_error = get_error,
+    )
+}
+
+/// Writes the body of the function or method that calls a command. `callee` is the function
+/// pointer to call, and `check_error` calls the function created by `write_check_fn`.
+fn write_call<W>(
+    registry: &Registry,
+    cmd: &Cmd,
+    report: ErrorReport,
+    callee: &str,
+    check_error: &str,
+    dest: &mut W,
+) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let idents = super::gen_parameters(cmd, true, false);
+    let typed_params = super::gen_parameters(cmd, false, true);
+
+    writeln!(dest,
+        "let r = __gl_imports::mem::transmute::<_, extern \"system\" fn({typed_params}) -> {return_suffix}>\
+            ({callee})({idents});",
+        typed_params = typed_params.join(", "),
+        return_suffix = cmd.proto.ty,
+        callee = callee,
+        idents = idents.join(", "),
+    )?;
+
+    if !is_checked(registry, cmd) {
+        return writeln!(dest, "r");
+    }
+
+    match report {
+        ErrorReport::Result => writeln!(
+            dest,
+            "match {check_error} {{ Some(error) => Err(error), None => Ok(r) }}",
+            check_error = check_error,
+        ),
+        ErrorReport::Hook => {
+            let args = idents
+                .iter()
+                .zip(typed_params.iter())
+                .map(|(ident, ty)| {
+                    if ty.contains("GLDEBUGPROC") {
+                        "&\"<callback>\"".to_string()
+                    } else {
+                        format!("&{}", ident)
+                    }
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                dest,
+                "if let Some(error) = {check_error} {{
+                    errors::report(error, \"{symbol}\", &[{args}]);
+                }}
+                r",
+                check_error = check_error,
+                symbol = super::gen_symbol_name(registry.api, &cmd.proto.ident),
+                args = args.join(", "),
+            )
+        },
+    }
+}
+
+/// The return type of the function or method that calls a command.
+fn gen_return_type(registry: &Registry, cmd: &Cmd, report: ErrorReport) -> String {
+    match report {
+        ErrorReport::Result if is_checked(registry, cmd) => {
+            format!("errors::Result<{}>", cmd.proto.ty)
+        },
+        _ => cmd.proto.ty.to_string(),
+    }
+}
+
+/// Creates the functions corresponding to the GL commands.
+///
+/// The function calls the corresponding function pointer stored in the `storage` module created
+///  by `global_gen::write_ptrs`, and then `check_error`.
+fn write_fns<W>(registry: &Registry, report: ErrorReport, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    for cmd in &registry.cmds {
+        if let Some(v) = registry.aliases.get(&cmd.proto.ident) {
+            writeln!(dest, "/// Fallbacks: {}", v.join(", "))?;
+        }
+        if let Some(v) = super::gen_since_version(registry, cmd) {
+            writeln!(
+                dest,
+                "/// Available since version {}, so it may not be loaded.",
+                v
+            )?;
+        }
+
+        writeln!(
+            dest,
+            "#[allow(non_snake_case, unused_variables, dead_code)] #[inline]
+            pub unsafe fn {name}({params}) -> {return_suffix} {{",
+            name = cmd.proto.ident,
+            params = super::gen_parameters(cmd, true, true).join(", "),
+            return_suffix = gen_return_type(registry, cmd, report),
+        )?;
+        write_call(
+            registry,
+            cmd,
+            report,
+            &format!("storage::{}.f", cmd.proto.ident),
+            "check_error()",
+            dest,
+        )?;
+        writeln!(dest, "}}")?;
+    }
+
+    Ok(())
+}
+
+/// Creates the `impl` of the structure created by `struct_gen::write_struct`.
+fn write_impl<W>(registry: &Registry, report: ErrorReport, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        "impl {api} {{",
+        api = super::gen_struct_name(registry.api)
+    )?;
+
+    struct_gen::write_load_fn(registry, dest)?;
+    write_check_fn(registry, CommandStyle::Methods, dest)?;
+
+    for cmd in &registry.cmds {
+        writeln!(
+            dest,
+            "#[allow(non_snake_case, unused_variables, dead_code)]
+            #[inline] pub unsafe fn {name}(&self, {params}) -> {return_suffix} {{",
+            name = cmd.proto.ident,
+            params = super::gen_parameters(cmd, true, true).join(", "),
+            return_suffix = gen_return_type(registry, cmd, report),
+        )?;
+        write_call(
+            registry,
+            cmd,
+            report,
+            &format!("self.{}.f", cmd.proto.ident),
+            "self.check_error()",
+            dest,
+        )?;
+        writeln!(dest, "}}")?;
+    }
+
+    writeln!(
+        dest,
+        "}}
+
+        unsafe impl __gl_imports::Send for {api} {{}}",
+        api = super::gen_struct_name(registry.api)
+    )
+}
@@ -39,7 +39,7 @@ impl super::Generator for GlobalGenerator {
 
 /// Creates a `__gl_imports` module which contains all the external symbols that we need for the
 ///  bindings.
-fn write_header<W>(dest: &mut W) -> io::Result<()>
+pub(super) fn write_header<W>(dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -55,7 +55,7 @@ where
 }
 
 /// Creates the metaloadfn function for fallbacks
-fn write_metaloadfn<W>(dest: &mut W) -> io::Result<()>
+pub(super) fn write_metaloadfn<W>(dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -82,7 +82,7 @@ where
 /// Creates a `types` module which contains all the type aliases.
 ///
 /// See also `generators::gen_types`.
-fn write_type_aliases<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_type_aliases<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -94,7 +94,7 @@ where
     "#
     )?;
 
-    super::gen_types(registry.api, dest)?;
+    super::gen_types(registry, dest)?;
 
     writeln!(
         dest,
@@ -105,7 +105,7 @@ where
 }
 
 /// Creates all the `<enum>` elements at the root of the bindings.
-fn write_enums<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_enums<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -128,6 +128,13 @@ where
         if let Some(v) = registry.aliases.get(&cmd.proto.ident) {
             writeln!(dest, "/// Fallbacks: {}", v.join(", "))?;
         }
+        if let Some(v) = super::gen_since_version(registry, cmd) {
+            writeln!(
+                dest,
+                "/// Available since version {}, so it may not be loaded.",
+                v
+            )?;
+        }
 
         writeln!(dest,
             "#[allow(non_snake_case, unused_variables, dead_code)] #[inline]
@@ -147,7 +154,7 @@ where
 }
 
 /// Creates a `FnPtr` structure which contains the store for a single binding.
-fn write_fnptr_struct_def<W>(dest: &mut W) -> io::Result<()>
+pub(super) fn write_fnptr_struct_def<W>(dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -175,7 +182,7 @@ where
 }
 
 /// Creates a `storage` module which contains a static `FnPtr` per GL command in the registry.
-fn write_ptrs<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_ptrs<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -206,7 +213,7 @@ where
 ///
 /// Each module contains `is_loaded` and `load_with` which interact with the `storage` module
 ///  created by `write_ptrs`.
-fn write_fn_mods<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_fn_mods<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -260,7 +267,7 @@ where
 /// Creates a `missing_fn_panic` function.
 ///
 /// This function is the mock that is called if the real function could not be called.
-fn write_panicking_fns<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_panicking_fns<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -278,7 +285,7 @@ where
 /// Creates the `load_with` function.
 ///
 /// The function calls `load_with` in each module created by `write_fn_mods`.
-fn write_load_fn<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_load_fn<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -0,0 +1,331 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use registry::{Binding, Enum, Group, Registry};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+/// Changes the type of every `GLenum` binding that is tagged with a known group to the newtype
+/// for that group, and the type of every `GLbitfield` binding that is tagged with a bitmask group
+/// to the flags type for that group.
+pub fn apply(registry: &mut Registry) {
+    let cmds = registry
+        .cmds
+        .iter()
+        .cloned()
+        .map(|mut cmd| {
+            retype(&mut cmd.proto, &registry.groups);
+            for param in &mut cmd.params {
+                retype(param, &registry.groups);
+            }
+            cmd
+        })
+        .collect();
+    registry.cmds = cmds;
+}
+
+fn retype(binding: &mut Binding, groups: &BTreeMap<String, Group>) {
+    let group = match binding.group.as_ref().and_then(|group| groups.get(group)) {
+        Some(group) => group,
+        None => return,
+    };
+    let raw_ty = if is_bitmask(group) {
+        "types::GLbitfield"
+    } else {
+        "types::GLenum"
+    };
+    let ty = match binding.ty.as_ref() {
+        ty if ty == raw_ty => format!("groups::{}", group.ident),
+        ty if ty == format!("*const {}", raw_ty) => format!("*const groups::{}", group.ident),
+        ty if ty == format!("*mut {}", raw_ty) => format!("*mut groups::{}", group.ident),
+        _ => return,
+    };
+    binding.ty = Cow::Owned(ty);
+}
+
+pub fn is_bitmask(group: &Group) -> bool {
+    group.enums_type.as_ref().map(String::as_ref) == Some("bitmask")
+}
+
+/// Returns the groups whose newtypes are used by the commands in the registry.
+fn used_groups(registry: &Registry) -> BTreeSet<&str> {
+    let mut used = BTreeSet::new();
+    for cmd in &registry.cmds {
+        for binding in Some(&cmd.proto).into_iter().chain(&cmd.params) {
+            match binding.group {
+                Some(ref group) if binding.ty.contains("groups::") => {
+                    used.insert(group.as_ref());
+                },
+                _ => {},
+            }
+        }
+    }
+    used
+}
+
+/// Creates a `groups` module which contains a newtype for each group used by the registry.
+pub fn write_groups_mod<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+        pub mod groups {{
+            #![allow(non_upper_case_globals, dead_code)]
+    "#
+    )?;
+
+    let enums: BTreeMap<&str, &Enum> = registry
+        .enums
+        .iter()
+        .map(|enm| (enm.ident.as_ref(), enm))
+        .collect();
+
+    for ident in used_groups(registry) {
+        let group = &registry.groups[ident];
+        if is_bitmask(group) {
+            write_flags(group, &enums, dest)?;
+        } else {
+            write_newtype(group, &enums, dest)?;
+        }
+    }
+
+    writeln!(dest, "}}")
+}
+
+/// Creates a `#[repr(transparent)]` newtype with an associated constant for each of the group's
+/// enums that are present in the registry.
+fn write_newtype<W>(group: &Group, enums: &BTreeMap<&str, &Enum>, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        "/// `GLenum` values from the `{ident}` group.
+        ///
+        /// Values that are missing from the registry can still be passed by wrapping them, as in
+        /// `{ident}(value)`, and the raw value is available as `.0`.
+        #[repr(transparent)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub struct {ident}(pub super::types::GLenum);
+
+        impl {ident} {{",
+        ident = group.ident,
+    )?;
+
+    let members: BTreeSet<&str> = group.enums.iter().map(|enm| enm.as_ref()).collect();
+    for enm in members.into_iter().filter_map(|ident| enums.get(ident)) {
+        if enm.ty != "GLenum" {
+            continue;
+        }
+        writeln!(
+            dest,
+            "pub const {name}: {ident} = {ident}(super::{name});",
+            name = enm.ident,
+            ident = group.ident,
+        )?;
+    }
+
+    writeln!(
+        dest,
+        "}}
+
+        impl From<{ident}> for super::types::GLenum {{
+            #[inline]
+            fn from(value: {ident}) -> super::types::GLenum {{
+                value.0
+            }}
+        }}
+
+        impl From<super::types::GLenum> for {ident} {{
+            #[inline]
+            fn from(value: super::types::GLenum) -> {ident} {{
+                {ident}(value)
+            }}
+        }}",
+        ident = group.ident,
+    )
+}
+
+/// Creates a `#[repr(transparent)]` flags type with an associated constant for each of the
+/// group's flags that are present in the registry. It supports the same basic operations as the
+/// types generated by the `bitflags` crate.
+fn write_flags<W>(group: &Group, enums: &BTreeMap<&str, &Enum>, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let members: BTreeSet<&str> = group.enums.iter().map(|enm| enm.as_ref()).collect();
+    let flags: Vec<&str> = members
+        .into_iter()
+        .filter_map(|ident| enums.get(ident))
+        .filter(|enm| enm.ty == "GLenum" || enm.ty == "GLuint")
+        .map(|enm| enm.ident.as_ref())
+        .collect();
+
+    writeln!(
+        dest,
+        "/// `GLbitfield` flags from the `{ident}` group.
+        ///
+        /// Flags that are missing from the registry can still be passed with `from_bits`, and
+        /// the raw bits are available from `bits`.
+        #[repr(transparent)]
+        #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+        pub struct {ident}(super::types::GLbitfield);
+
+        impl {ident} {{",
+        ident = group.ident,
+    )?;
+
+    for flag in &flags {
+        writeln!(
+            dest,
+            "pub const {name}: {ident} = {ident}(super::{name} as super::types::GLbitfield);",
+            name = flag,
+            ident = group.ident,
+        )?;
+    }
+
+    writeln!(
+        dest,
+        "const NAMES: &'static [(&'static str, {ident})] = &[{names}];
+
+            /// Returns a set with no flags.
+            #[inline]
+            pub fn empty() -> {ident} {{
+                {ident}(0)
+            }}
+
+            /// Creates a set from raw bits, keeping any bits that don't correspond to a flag.
+            #[inline]
+            pub fn from_bits(bits: super::types::GLbitfield) -> {ident} {{
+                {ident}(bits)
+            }}
+
+            /// Returns the raw bits of the set.
+            #[inline]
+            pub fn bits(&self) -> super::types::GLbitfield {{
+                self.0
+            }}
+
+            /// Returns true if no flags are set.
+            #[inline]
+            pub fn is_empty(&self) -> bool {{
+                self.0 == 0
+            }}
+
+            /// Returns true if all of the flags in `other` are set.
+            #[inline]
+            pub fn contains(&self, other: {ident}) -> bool {{
+                self.0 & other.0 == other.0
+            }}
+
+            /// Sets the flags in `other`.
+            #[inline]
+            pub fn insert(&mut self, other: {ident}) {{
+                self.0 |= other.0;
+            }}
+
+            /// Clears the flags in `other`.
+            #[inline]
+            pub fn remove(&mut self, other: {ident}) {{
+                self.0 &= !other.0;
+            }}
+        }}
+
+        impl ::std::ops::BitOr for {ident} {{
+            type Output = {ident};
+
+            #[inline]
+            fn bitor(self, other: {ident}) -> {ident} {{
+                {ident}(self.0 | other.0)
+            }}
+        }}
+
+        impl ::std::ops::BitOrAssign for {ident} {{
+            #[inline]
+            fn bitor_assign(&mut self, other: {ident}) {{
+                self.0 |= other.0;
+            }}
+        }}
+
+        impl ::std::ops::BitAnd for {ident} {{
+            type Output = {ident};
+
+            #[inline]
+            fn bitand(self, other: {ident}) -> {ident} {{
+                {ident}(self.0 & other.0)
+            }}
+        }}
+
+        impl ::std::ops::BitAndAssign for {ident} {{
+            #[inline]
+            fn bitand_assign(&mut self, other: {ident}) {{
+                self.0 &= other.0;
+            }}
+        }}
+
+        impl ::std::fmt::Debug for {ident} {{
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
+                write!(f, \"{ident}(\")?;
+                let mut remaining = self.0;
+                let mut first = true;
+                for &(name, flag) in {ident}::NAMES {{
+                    if flag.0 != 0 && self.contains(flag) {{
+                        if !first {{
+                            write!(f, \" | \")?;
+                        }}
+                        write!(f, \"{{}}\", name)?;
+                        remaining &= !flag.0;
+                        first = false;
+                    }}
+                }}
+                if remaining != 0 {{
+                    if !first {{
+                        write!(f, \" | \")?;
+                    }}
+                    write!(f, \"{{:#x}}\", remaining)?;
+                }} else if first {{
+                    write!(f, \"empty\")?;
+                }}
+                write!(f, \")\")
+            }}
+        }}
+
+        impl From<{ident}> for super::types::GLbitfield {{
+            #[inline]
+            fn from(value: {ident}) -> super::types::GLbitfield {{
+                value.0
+            }}
+        }}
+
+        impl From<super::types::GLbitfield> for {ident} {{
+            #[inline]
+            fn from(bits: super::types::GLbitfield) -> {ident} {{
+                {ident}(bits)
+            }}
+        }}",
+        ident = group.ident,
+        names = flags
+            .iter()
+            .map(|flag| format!(
+                "(\"{flag}\", {ident}::{flag})",
+                flag = flag,
+                ident = group.ident
+            ))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
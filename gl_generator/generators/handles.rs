@@ -0,0 +1,128 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use registry::{Binding, Registry};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io;
+
+/// Generates the name of the handle type for a class of objects.
+///
+/// Example results: `"Buffer"`, `"VertexArray"`, `"ProgramPipeline"`.
+fn gen_handle_name(class: &str) -> String {
+    class
+        .split(&[' ', '_'][..])
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
+
+/// Changes the type of every `GLuint` binding that refers to a class of objects to the handle
+/// type for that class.
+pub fn apply(registry: &mut Registry) {
+    let cmds = registry
+        .cmds
+        .iter()
+        .cloned()
+        .map(|mut cmd| {
+            retype(&mut cmd.proto);
+            for param in &mut cmd.params {
+                retype(param);
+            }
+            cmd
+        })
+        .collect();
+    registry.cmds = cmds;
+}
+
+fn retype(binding: &mut Binding) {
+    let name = match binding.class {
+        Some(ref class) => gen_handle_name(class),
+        None => return,
+    };
+    if name.is_empty() || !name.chars().all(char::is_alphanumeric) {
+        return;
+    }
+    let ty = match binding.ty.as_ref() {
+        "types::GLuint" => format!("handles::{}", name),
+        "*const types::GLuint" => format!("*const handles::{}", name),
+        "*mut types::GLuint" => format!("*mut handles::{}", name),
+        _ => return,
+    };
+    binding.ty = Cow::Owned(ty);
+}
+
+/// Creates a `handles` module which contains a newtype for each class of objects that is used by
+/// the registry.
+pub fn write_handles_mod<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+        pub mod handles {{
+            #![allow(dead_code)]
+    "#
+    )?;
+
+    let mut classes = BTreeMap::new();
+    for cmd in &registry.cmds {
+        for binding in Some(&cmd.proto).into_iter().chain(&cmd.params) {
+            match binding.class {
+                Some(ref class) if binding.ty.contains("handles::") => {
+                    classes.insert(gen_handle_name(class), class.as_str());
+                },
+                _ => {},
+            }
+        }
+    }
+
+    for (name, class) in classes {
+        writeln!(
+            dest,
+            "/// The name of a {class} object.
+            ///
+            /// `{name}(0)`, which is also the default, is the name that most commands treat as
+            /// no {class} object.
+            #[repr(transparent)]
+            #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct {name}(pub super::types::GLuint);
+
+            impl From<{name}> for super::types::GLuint {{
+                #[inline]
+                fn from(handle: {name}) -> super::types::GLuint {{
+                    handle.0
+                }}
+            }}
+
+            impl From<super::types::GLuint> for {name} {{
+                #[inline]
+                fn from(name: super::types::GLuint) -> {name} {{
+                    {name}(name)
+                }}
+            }}",
+            name = name,
+            class = class,
+        )?;
+    }
+
+    writeln!(dest, "}}")
+}
@@ -17,10 +17,21 @@ use std::io;
 use Api;
 
 pub mod debug_struct_gen;
+pub mod error_checking_gen;
 pub mod global_gen;
 pub mod static_gen;
 pub mod static_struct_gen;
 pub mod struct_gen;
+pub mod tracing_gen;
+
+mod enum_names;
+mod groups;
+mod handles;
+mod replay;
+mod slices;
+mod types;
+
+pub use self::types::TypeMap;
 
 /// Trait for a bindings generator.
 ///
@@ -30,6 +41,118 @@ pub trait Generator {
     fn write<W>(&self, registry: &Registry, dest: &mut W) -> io::Result<()>
     where
         W: io::Write;
+
+    /// How the commands written by this generator are called. This is used to generate wrappers
+    /// around them.
+    fn command_style(&self) -> CommandStyle {
+        CommandStyle::Functions
+    }
+
+    /// Builds the GL bindings, along with the extras enabled in `options`.
+    ///
+    /// The default implementation calls `write` with a copy of the registry whose types have
+    /// been rewritten by `GeneratorOptions::apply`, and then writes the modules that define those
+    /// types, and any wrappers, with `GeneratorOptions::write_modules`.
+    fn write_with_options<W>(
+        &self,
+        registry: &Registry,
+        options: &GeneratorOptions,
+        dest: &mut W,
+    ) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        let registry = options.apply(registry);
+        self.write(&registry, dest)?;
+        options.write_modules(&registry, self.command_style(), dest)
+    }
+}
+
+/// How the commands in a set of bindings are called.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CommandStyle {
+    /// Commands are free functions at the root of the bindings, as with `GlobalGenerator`.
+    Functions,
+    /// Commands are methods of a struct at the root of the bindings that is named after the API,
+    /// as with `StructGenerator`.
+    Methods,
+}
+
+/// Optional extras for the generated bindings. Everything is disabled by default.
+///
+/// See https://github.com/brendanzab/gl-rs/tree/master/gl_generator#generator-options
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    /// Generate a `#[repr(transparent)]` newtype for each enum group in a `groups` module, and
+    /// use it in place of `GLenum` for the parameters and return values tagged with that group.
+    /// Bitmask groups become flags types that are used in place of `GLbitfield`.
+    pub typed_groups: bool,
+    /// Generate a `#[repr(transparent)]` newtype for each class of object name in a `handles`
+    /// module, such as `Buffer` or `Texture`, and use it in place of `GLuint` for the parameters
+    /// and return values that refer to objects of that class.
+    pub typed_handles: bool,
+    /// Generate wrappers in a `slices` module for the commands that take arrays along with their
+    /// length, or arrays of a fixed length, which take slices and arrays instead.
+    pub slices: bool,
+    /// Generate `enum_name` and `enum_name_in` functions, which return the name of an enum from
+    /// its value, such as `"TEXTURE_2D"` from `0x0DE1`. `enum_name_in` only looks in one group,
+    /// which tells apart the enums that share a value.
+    pub enum_names: bool,
+    /// Generate a `replay` module, which makes the calls from a trace that was written by the
+    /// bindings from `TracingGenerator` through these bindings. The names of the objects that the
+    /// trace creates, such as buffers and textures, are translated into the names of the objects
+    /// that the replay creates. The module reads the trace itself, so the crate that includes
+    /// the bindings doesn't need to depend on `gl_generator`.
+    pub replay: bool,
+    /// Rust definitions to use in place of the types from the registry.
+    pub type_map: TypeMap,
+}
+
+impl GeneratorOptions {
+    /// Returns a copy of `registry`, with its types rewritten to use the types enabled by these
+    /// options.
+    pub fn apply(&self, registry: &Registry) -> Registry {
+        let mut registry = registry.clone();
+        if !self.type_map.is_empty() {
+            self.type_map.apply(&mut registry);
+        }
+        if self.typed_groups {
+            groups::apply(&mut registry);
+        }
+        if self.typed_handles {
+            handles::apply(&mut registry);
+        }
+        registry
+    }
+
+    /// Writes the modules that define the types used by a registry returned from `apply`, along
+    /// with any wrappers around its commands.
+    pub fn write_modules<W>(
+        &self,
+        registry: &Registry,
+        style: CommandStyle,
+        dest: &mut W,
+    ) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        if self.typed_groups {
+            groups::write_groups_mod(registry, dest)?;
+        }
+        if self.typed_handles {
+            handles::write_handles_mod(registry, dest)?;
+        }
+        if self.slices {
+            slices::write_slices_mod(registry, style, dest)?;
+        }
+        if self.enum_names {
+            enum_names::write_enum_names(registry, dest)?;
+        }
+        if self.replay {
+            replay::write_replay_mod(registry, style, dest)?;
+        }
+        Ok(())
+    }
 }
 
 pub fn gen_struct_name(api: Api) -> &'static str {
@@ -65,26 +188,14 @@ where
 
 /// Generates all the type aliases for a namespace.
 ///
-/// Aliases are either `pub type = ...` or `#[repr(C)] pub struct ... { ... }` and contain all the
-/// things that we can't obtain from the XML files.
-pub fn gen_types<W>(api: Api, dest: &mut W) -> io::Result<()>
+/// The aliases, function pointer types and structs are generated from the types in the
+/// registry. Types that only come from platform headers are defined by a small table, and any
+/// others must be declared at the same level where the bindings are included.
+pub fn gen_types<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
-    if let Api::Egl = api {
-        writeln!(dest, "{}", include_str!("templates/types/egl.rs"))?;
-        return Ok(());
-    }
-
-    writeln!(dest, "{}", include_str!("templates/types/gl.rs"))?;
-
-    match api {
-        Api::Glx => writeln!(dest, "{}", include_str!("templates/types/glx.rs"))?,
-        Api::Wgl => writeln!(dest, "{}", include_str!("templates/types/wgl.rs"))?,
-        _ => {},
-    }
-
-    Ok(())
+    types::write_types(registry, dest)
 }
 
 /// Generates the list of Rust `Arg`s that a `Cmd` requires.
@@ -106,6 +217,19 @@ pub fn gen_parameters(cmd: &Cmd, with_idents: bool, with_types: bool) -> Vec<Str
         .collect()
 }
 
+/// Generates the version that introduced a `Cmd`, if it is newer than the registry's minimum
+/// version. Such commands might not be loaded at runtime.
+///
+/// Example results: `Some("4.5")`, `None`.
+pub fn gen_since_version(registry: &Registry, cmd: &Cmd) -> Option<String> {
+    match cmd.introduced_in {
+        Some((major, minor)) if (major, minor) > registry.min_version => {
+            Some(format!("{}.{}", major, minor))
+        },
+        _ => None,
+    }
+}
+
 /// Generates the native symbol name of a `Cmd`.
 ///
 /// Example results: `"glClear"`, `"wglCreateContext"`, etc.
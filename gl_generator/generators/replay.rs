@@ -0,0 +1,588 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::groups::is_bitmask;
+use super::slices::qualify;
+use super::tracing_gen::is_fn_ptr;
+use super::CommandStyle;
+use registry::{Binding, Cmd, Registry};
+use std::io;
+
+/// The expression that gives the argument at `index` for a parameter.
+fn gen_arg(registry: &Registry, binding: &Binding, index: usize) -> String {
+    if is_fn_ptr(registry, &binding.c_ty) {
+        // callbacks can't be recorded
+        return "None".to_string();
+    }
+    let bitmask = binding
+        .group
+        .as_ref()
+        .and_then(|group| registry.groups.get(group))
+        .map(is_bitmask)
+        == Some(true);
+    if binding.ty.starts_with("groups::") && bitmask {
+        format!("super::{}::from_bits(args.arg({})?)", binding.ty, index)
+    } else if binding.ty.starts_with("groups::") || binding.ty.starts_with("handles::") {
+        format!("super::{}(args.arg({})?)", binding.ty, index)
+    } else {
+        format!("args.arg({})?", index)
+    }
+}
+
+/// Writes a function that replays a call of a command through the bindings, which returns false
+/// if the call was skipped.
+///
+/// Object names that are passed to the command are translated before the call, and the names
+/// that it creates, either by writing them to a buffer or by returning them, are recorded after
+/// it.
+fn write_replay_fn<W>(
+    registry: &Registry,
+    cmd: &Cmd,
+    style: CommandStyle,
+    dest: &mut W,
+) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let mut before = Vec::new();
+    let mut args = Vec::new();
+    let mut after = Vec::new();
+
+    for (index, binding) in cmd.params.iter().enumerate() {
+        let pointee = if binding.ty.starts_with("*const ") {
+            Some(&binding.ty[7..])
+        } else if binding.ty.starts_with("*mut ") {
+            // the memory that the command writes must be in the trace
+            before.push(format!(
+                "if args.points_to_unknown({}) {{ return Ok(false); }}",
+                index
+            ));
+            Some(&binding.ty[5..])
+        } else {
+            None
+        };
+
+        match (binding.class.as_ref(), pointee) {
+            (Some(class), Some(pointee)) => {
+                let size = format!("::std::mem::size_of::<{}>()", qualify(pointee));
+                if binding.ty.starts_with("*mut ") {
+                    after.push(format!(
+                        "args.insert_names({}, \"{}\", {})?;",
+                        index, class, size
+                    ));
+                } else {
+                    before.push(format!(
+                        "args.map_names({}, \"{}\", {})?;",
+                        index, class, size
+                    ));
+                }
+            },
+            (Some(class), None) => {
+                before.push(format!("args.map_name({}, \"{}\")?;", index, class));
+            },
+            (None, _) => {},
+        }
+
+        args.push(gen_arg(registry, binding, index));
+    }
+
+    let call = format!(
+        "{callee}{name}({args})",
+        callee = match style {
+            CommandStyle::Functions => "super::",
+            CommandStyle::Methods => "gl.",
+        },
+        name = cmd.proto.ident,
+        args = args.join(", "),
+    );
+    let checked = cmd.proto.ty.starts_with("errors::");
+    let call = match cmd.proto.class {
+        Some(ref class) if !cmd.proto.c_ty.is_void() => {
+            let name = if cmd.proto.ty.contains("handles::") {
+                "ret.0"
+            } else {
+                "ret"
+            };
+            let insert = format!(
+                "args.insert_return(\"{}\", {} as usize as u64);",
+                class, name
+            );
+            if checked {
+                format!("if let Ok(ret) = {} {{ {} }}", call, insert)
+            } else {
+                format!("let ret = {}; {}", call, insert)
+            }
+        },
+        _ if checked => format!("let _ = {};", call),
+        _ => format!("{};", call),
+    };
+
+    writeln!(
+        dest,
+        "unsafe fn {name}({gl_param}args: &mut ReplayArgs) -> Result<bool, ReplayError> {{
+            {before}
+            {call}
+            {after}
+            Ok(true)
+        }}",
+        name = cmd.proto.ident,
+        gl_param = match style {
+            CommandStyle::Functions => String::new(),
+            CommandStyle::Methods =>
+                format!("gl: &super::{}, ", super::gen_struct_name(registry.api)),
+        },
+        before = before.join("\n"),
+        call = call,
+        after = after.join("\n"),
+    )
+}
+
+/// Writes the `TraceReader` from `gl_generator`, so that the bindings can read traces without
+/// depending on it.
+fn write_reader<W>(dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let reader = include_str!("../trace_reader.rs");
+    // leave out the license and the comments at the top of the file
+    let start = reader.find("///").unwrap_or(0);
+    writeln!(dest, "{}", &reader[start..])
+}
+
+/// Writes `NameMap`, which pairs up the names of the objects in a trace with those in the
+/// replay, and `ReplayArgs`, which the functions that replay each command read their arguments
+/// from.
+fn write_replay_args<W>(dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+            /// The names of the objects in a trace, and the names of the objects that were created in their
+            /// place when it was replayed.
+            ///
+            /// Names are kept for each class of objects, such as `"buffer"` or `"program"`, as the classes
+            /// are given by the registry. A name that was not created by the replay, such as `0`, is used
+            /// as it is.
+            #[derive(Clone, Debug, Default, PartialEq, Eq)]
+            pub struct NameMap {{
+                names: BTreeMap<String, BTreeMap<u64, u64>>,
+                addresses: bool,
+            }}
+
+            impl NameMap {{
+                pub fn new() -> NameMap {{
+                    NameMap::default()
+                }}
+
+                /// Passes the pointers to memory that is not in the trace as they were recorded, rather than
+                /// failing with `ReplayError::InvalidArgument`. This is right for traces whose pointers are
+                /// offsets into buffer objects, such as those given to `VertexAttribPointer`, but not for
+                /// pointers to client memory.
+                pub fn pass_addresses(mut self, pass: bool) -> NameMap {{
+                    self.addresses = pass;
+                    self
+                }}
+
+                /// Records that the object of `class` that is called `recorded` in the trace is called
+                /// `replayed` in the replay.
+                pub fn insert(&mut self, class: &str, recorded: u64, replayed: u64) {{
+                    self.names
+                        .entry(class.to_string())
+                        .or_default()
+                        .insert(recorded, replayed);
+                }}
+
+                /// Returns the name in the replay of the object of `class` that is called `recorded` in the
+                /// trace, if the replay created one.
+                pub fn get(&self, class: &str, recorded: u64) -> Option<u64> {{
+                    self.names
+                        .get(class)
+                        .and_then(|names| names.get(&recorded))
+                        .cloned()
+                }}
+            }}
+
+            /// An error from replaying a trace.
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            pub enum ReplayError {{
+                /// The trace could not be read.
+                Trace(TraceError),
+                /// The trace calls a command that the bindings don't have.
+                UnknownCommand(String),
+                /// An argument in the trace is missing, or can't be passed as the parameter at `index`,
+                /// such as a float for an integer parameter.
+                InvalidArgument {{ command: String, index: usize }},
+            }}
+
+            impl fmt::Display for ReplayError {{
+                fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {{
+                    match *self {{
+                        ReplayError::Trace(ref err) => err.fmt(fmt),
+                        ReplayError::UnknownCommand(ref command) => {{
+                            write!(fmt, "the bindings don't have `{{}}`", command)
+                        }},
+                        ReplayError::InvalidArgument {{ ref command, index }} => write!(
+                            fmt,
+                            "argument {{}} of a call of `{{}}` does not fit its parameter",
+                            index, command
+                        ),
+                    }}
+                }}
+            }}
+
+            impl error::Error for ReplayError {{}}
+
+            impl From<TraceError> for ReplayError {{
+                fn from(err: TraceError) -> ReplayError {{
+                    ReplayError::Trace(err)
+                }}
+            }}
+
+            /// A parameter type that can be given an argument from a trace.
+            ///
+            /// This is implemented for the primitive types and raw pointers. Types that are defined by a
+            /// `TypeMap` must implement it for the commands that take them to be replayed.
+            pub trait ReplayArg: Sized {{
+                /// Reads the argument at `index`.
+                fn replay_arg(args: &mut ReplayArgs, index: usize) -> Result<Self, ReplayError>;
+            }}
+
+            macro_rules! impl_replay_arg {{
+                ($read:ident: $($ty:ty),*) => {{
+                    $(
+                        impl ReplayArg for $ty {{
+                            #[inline]
+                            fn replay_arg(args: &mut ReplayArgs, index: usize) -> Result<$ty, ReplayError> {{
+                                args.$read(index).map(|value| value as $ty)
+                            }}
+                        }}
+                    )*
+                }};
+            }}
+
+            impl_replay_arg!(int: u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+            impl_replay_arg!(float: f32, f64);
+
+            impl<T> ReplayArg for *const T {{
+                #[inline]
+                fn replay_arg(args: &mut ReplayArgs, index: usize) -> Result<*const T, ReplayError> {{
+                    args.pointer(index).map(|ptr| ptr as *const T)
+                }}
+            }}
+
+            impl<T> ReplayArg for *mut T {{
+                #[inline]
+                fn replay_arg(args: &mut ReplayArgs, index: usize) -> Result<*mut T, ReplayError> {{
+                    args.pointer(index).map(|ptr| ptr as *mut T)
+                }}
+            }}
+
+            /// The arguments of a call that is being replayed.
+            ///
+            /// The buffers and strings in the trace are copied before they are passed to the command, so
+            /// that the names that it writes to a buffer can be compared with the names in the trace.
+            pub struct ReplayArgs<'a> {{
+                command: &'a str,
+                /// The arguments as they were recorded.
+                recorded: Vec<TraceValue>,
+                /// The arguments that are passed to the command, after their names have been translated.
+                values: Vec<TraceValue>,
+                ret: Option<TraceValue>,
+                /// The copies of the buffers and strings that have been passed to the command, by argument.
+                /// These are `u64`s so that any element type is aligned.
+                copies: Vec<Vec<u64>>,
+                /// Whether each argument is an object name, which is passed as it is even when it is a
+                /// pointer, such as a `GLsync`.
+                handles: Vec<bool>,
+                names: &'a mut NameMap,
+            }}
+
+            impl<'a> ReplayArgs<'a> {{
+                /// Prepares to replay `call`, which is a call of `command`.
+                pub fn new(command: &'a str, call: TraceCall, names: &'a mut NameMap) -> ReplayArgs<'a> {{
+                    ReplayArgs {{
+                        command,
+                        values: call.args.clone(),
+                        copies: vec![Vec::new(); call.args.len()],
+                        handles: vec![false; call.args.len()],
+                        recorded: call.args,
+                        ret: call.ret,
+                        names,
+                    }}
+                }}
+
+                fn invalid(&self, index: usize) -> ReplayError {{
+                    ReplayError::InvalidArgument {{
+                        command: self.command.to_string(),
+                        index,
+                    }}
+                }}
+
+                /// Reads the argument at `index` as a `T`.
+                #[inline]
+                pub fn arg<T: ReplayArg>(&mut self, index: usize) -> Result<T, ReplayError> {{
+                    T::replay_arg(self, index)
+                }}
+
+                /// Reads an integer argument, or the address of a pointer.
+                pub fn int(&self, index: usize) -> Result<u64, ReplayError> {{
+                    self.values
+                        .get(index)
+                        .and_then(TraceValue::as_u64)
+                        .ok_or_else(|| self.invalid(index))
+                }}
+
+                /// Reads a floating-point argument.
+                pub fn float(&self, index: usize) -> Result<f64, ReplayError> {{
+                    match self.values.get(index) {{
+                        Some(&TraceValue::F32(value)) => Ok(value as f64),
+                        Some(&TraceValue::F64(value)) => Ok(value),
+                        _ => Err(self.invalid(index)),
+                    }}
+                }}
+
+                /// Reads a pointer argument. A buffer or string in the trace is copied, and a pointer to the
+                /// copy is returned. Strings are nul-terminated. Null pointers and object names are returned
+                /// as they are, and other pointers only if the `NameMap` passes addresses.
+                pub fn pointer(&mut self, index: usize) -> Result<*mut c_void, ReplayError> {{
+                    let data = match self.values.get(index) {{
+                        Some(&TraceValue::Pointer(address))
+                            if address == 0 || self.handles[index] || self.names.addresses =>
+                        {{
+                            return Ok(address as usize as *mut c_void)
+                        }},
+                        Some(TraceValue::Buffer {{ data, .. }}) | Some(TraceValue::String {{ data, .. }}) => data,
+                        _ => return Err(self.invalid(index)),
+                    }};
+                    // there is always a zero byte after the data, which terminates strings
+                    let mut copy = vec![0u64; data.len() / 8 + 1];
+                    unsafe {{
+                        ptr::copy_nonoverlapping(data.as_ptr(), copy.as_mut_ptr() as *mut u8, data.len());
+                    }}
+                    let ptr = copy.as_mut_ptr() as *mut c_void;
+                    self.copies[index] = copy;
+                    Ok(ptr)
+                }}
+
+                /// Returns true if the argument is a pointer to memory that is not in the trace.
+                ///
+                /// The bindings skip the calls that would write to such memory, such as `GetIntegerv`.
+                pub fn points_to_unknown(&self, index: usize) -> bool {{
+                    match self.values.get(index) {{
+                        Some(&TraceValue::Pointer(address)) => address != 0,
+                        _ => false,
+                    }}
+                }}
+
+                /// Translates the object name at `index` into the name of the object of `class` that the
+                /// replay created in its place.
+                pub fn map_name(&mut self, index: usize, class: &str) -> Result<(), ReplayError> {{
+                    let recorded = self.int(index)?;
+                    self.handles[index] = true;
+                    let name = match self.names.get(class, recorded) {{
+                        Some(name) => name,
+                        None => return Ok(()),
+                    }};
+                    self.values[index] = match self.values[index] {{
+                        TraceValue::U8(_) => TraceValue::U8(name as u8),
+                        TraceValue::I8(_) => TraceValue::I8(name as i8),
+                        TraceValue::U16(_) => TraceValue::U16(name as u16),
+                        TraceValue::I16(_) => TraceValue::I16(name as i16),
+                        TraceValue::U32(_) => TraceValue::U32(name as u32),
+                        TraceValue::I32(_) => TraceValue::I32(name as i32),
+                        TraceValue::U64(_) => TraceValue::U64(name),
+                        TraceValue::I64(_) => TraceValue::I64(name as i64),
+                        TraceValue::Pointer(_) => TraceValue::Pointer(name),
+                        _ => return Err(self.invalid(index)),
+                    }};
+                    Ok(())
+                }}
+
+                /// Translates the object names in the buffer at `index`, whose elements are `size` bytes.
+                pub fn map_names(&mut self, index: usize, class: &str, size: usize) -> Result<(), ReplayError> {{
+                    let names = &*self.names;
+                    match self.values.get_mut(index) {{
+                        Some(&mut TraceValue::Buffer {{ ref mut data, .. }}) if size == 4 || size == 8 => {{
+                            for element in data.chunks_mut(size) {{
+                                if let Some(name) = read_object_name(element).and_then(|name| names.get(class, name)) {{
+                                    write_object_name(element, name);
+                                }}
+                            }}
+                            return Ok(());
+                        }},
+                        Some(&mut TraceValue::Pointer(_)) => return Ok(()),
+                        _ => {{}},
+                    }}
+                    Err(self.invalid(index))
+                }}
+
+                /// Records the object names that the command wrote to the buffer at `index`, whose elements
+                /// are `size` bytes, as the names of the objects of `class` in the trace.
+                pub fn insert_names(
+                    &mut self,
+                    index: usize,
+                    class: &str,
+                    size: usize,
+                ) -> Result<(), ReplayError> {{
+                    let recorded = match self.recorded.get(index) {{
+                        Some(TraceValue::Buffer {{ data, .. }}) if size == 4 || size == 8 => data,
+                        Some(&TraceValue::Pointer(_)) => return Ok(()),
+                        _ => return Err(self.invalid(index)),
+                    }};
+                    let copy = &self.copies[index];
+                    let replayed = unsafe {{ slice::from_raw_parts(copy.as_ptr() as *const u8, recorded.len()) }};
+                    for (recorded, replayed) in recorded.chunks(size).zip(replayed.chunks(size)) {{
+                        if let (Some(recorded), Some(replayed)) = (read_object_name(recorded), read_object_name(replayed)) {{
+                            self.names.insert(class, recorded, replayed);
+                        }}
+                    }}
+                    Ok(())
+                }}
+
+                /// Records `name`, which the command returned, as the name of the object of `class` that it
+                /// returned in the trace.
+                pub fn insert_return(&mut self, class: &str, name: u64) {{
+                    if let Some(recorded) = self.ret.as_ref().and_then(TraceValue::as_u64) {{
+                        self.names.insert(class, recorded, name);
+                    }}
+                }}
+            }}
+
+            /// Reads an object name of 4 or 8 bytes, in native byte order.
+            fn read_object_name(bytes: &[u8]) -> Option<u64> {{
+                match bytes.len() {{
+                    4 => Some(u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64),
+                    8 => {{
+                        let mut array = [0; 8];
+                        array.copy_from_slice(bytes);
+                        Some(u64::from_ne_bytes(array))
+                    }},
+                    _ => None,
+                }}
+            }}
+
+            /// Writes an object name over one of 4 or 8 bytes, in native byte order.
+            fn write_object_name(bytes: &mut [u8], name: u64) {{
+                match bytes.len() {{
+                    4 => bytes.copy_from_slice(&(name as u32).to_ne_bytes()),
+                    8 => bytes.copy_from_slice(&name.to_ne_bytes()),
+                    _ => {{}},
+                }}
+            }}"#
+    )
+}
+
+/// Creates a `replay` module, which reads a trace and makes its calls through the commands of the
+/// bindings.
+///
+/// With `CommandStyle::Methods`, the calls are made through the struct that holds the commands,
+/// which is given to `replay::replay`.
+pub fn write_replay_mod<W>(registry: &Registry, style: CommandStyle, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let api = super::gen_struct_name(registry.api);
+    let (gl_ty, gl_param, gl_arg) = match style {
+        CommandStyle::Functions => (String::new(), String::new(), ""),
+        CommandStyle::Methods => (
+            format!("&super::{}, ", api),
+            format!("gl: &super::{}, ", api),
+            "gl, ",
+        ),
+    };
+
+    writeln!(
+        dest,
+        r#"
+        pub mod replay {{
+            #![allow(non_snake_case, unused_variables, dead_code)]
+
+            use std::collections::BTreeMap;
+            use std::error;
+            use std::fmt;
+            use std::io::{{self, Read}};
+            use std::os::raw::c_void;
+            use std::ptr;
+            use std::slice;"#
+    )?;
+    write_reader(dest)?;
+    write_replay_args(dest)?;
+
+    writeln!(
+        dest,
+        r#"
+            type Command = unsafe fn({gl_ty}&mut ReplayArgs) -> Result<bool, ReplayError>;
+
+            /// Makes each call from `trace`{through}, and returns the number of calls that were
+            /// made.
+            ///
+            /// `names` translates the names of the objects that the trace refers to into the
+            /// names of the objects that were created in their place. It can be shared by several
+            /// traces that were recorded one after the other.
+            ///
+            /// Calls that would write to memory that is not in the trace, such as `GetIntegerv`,
+            /// are skipped. Other pointers to memory that is not in the trace fail with
+            /// `ReplayError::InvalidArgument`, unless `names` passes addresses, as it should for
+            /// offsets into buffer objects.
+            pub unsafe fn replay<R: io::Read>({gl_param}mut trace: TraceReader<R>, names: &mut NameMap) -> Result<usize, ReplayError> {{
+                let mut count = 0;
+                while let Some(call) = trace.read_call()? {{
+                    let command = &trace.commands()[call.command].name;
+                    if replay_call({gl_arg}command, call, names)? {{
+                        count += 1;
+                    }}
+                }}
+                Ok(count)
+            }}
+
+            /// Makes a call of `command` from a trace{through}. Returns false if the call was
+            /// skipped.
+            pub unsafe fn replay_call({gl_param}command: &str, call: TraceCall, names: &mut NameMap) -> Result<bool, ReplayError> {{
+                let replay = match COMMANDS.binary_search_by(|&(name, _)| name.cmp(command)) {{
+                    Ok(index) => COMMANDS[index].1,
+                    Err(_) => return Err(ReplayError::UnknownCommand(command.to_string())),
+                }};
+                replay({gl_arg}&mut ReplayArgs::new(command, call, names))
+            }}
+
+            /// The commands, sorted by name, and the functions that replay their calls.
+            const COMMANDS: &'static [(&'static str, Command)] = &["#,
+        gl_ty = gl_ty,
+        through = match style {
+            CommandStyle::Functions => "",
+            CommandStyle::Methods => " through `gl`",
+        },
+        gl_param = gl_param,
+        gl_arg = gl_arg,
+    )?;
+
+    let mut cmds: Vec<_> = registry
+        .cmds
+        .iter()
+        .map(|cmd| (super::gen_symbol_name(registry.api, &cmd.proto.ident), cmd))
+        .collect();
+    cmds.sort_by(|a, b| a.0.cmp(&b.0));
+    for &(ref symbol, cmd) in &cmds {
+        writeln!(dest, "(\"{}\", {}),", symbol, cmd.proto.ident)?;
+    }
+    writeln!(dest, "];")?;
+
+    for cmd in &registry.cmds {
+        write_replay_fn(registry, cmd, style, dest)?;
+    }
+
+    writeln!(dest, "}}")
+}
@@ -0,0 +1,249 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::CommandStyle;
+use registry::{Binding, Cmd, Len, Registry};
+use std::io;
+
+/// The types of the parameters that can be derived from the length of a slice.
+const COUNT_TYPES: &[&str] = &[
+    "types::GLsizei",
+    "types::GLint",
+    "types::GLuint",
+    "types::GLsizeiptr",
+];
+
+/// Returns whether a pointer is mutable, along with the type that it points to, if it can be
+/// used as the element type of a slice.
+fn element(binding: &Binding) -> Option<(bool, &str)> {
+    let (mutable, pointee) = if binding.ty.starts_with("*const ") {
+        (false, &binding.ty[7..])
+    } else if binding.ty.starts_with("*mut ") {
+        (true, &binding.ty[5..])
+    } else {
+        return None;
+    };
+    if pointee.contains("c_void") {
+        return None;
+    }
+    Some((mutable, pointee))
+}
+
+/// Returns the pointer parameter whose length is given by `count`, along with the number of
+/// elements per count, if it is the only such parameter.
+fn sliced_by<'a>(cmd: &'a Cmd, count: &Binding) -> Option<(&'a Binding, usize)> {
+    if !COUNT_TYPES.contains(&count.ty.as_ref()) {
+        return None;
+    }
+    let mut pointers = cmd.params.iter().filter_map(|binding| match binding.len {
+        Some(Len::Param { ref ident, factor }) if *ident == count.ident => Some((binding, factor)),
+        _ => None,
+    });
+    let pointer = pointers.next()?;
+    if pointers.next().is_some() || element(pointer.0).is_none() {
+        return None;
+    }
+    Some(pointer)
+}
+
+/// Returns true if a pointer parameter is replaced by a slice.
+fn is_sliced(cmd: &Cmd, pointer: &Binding) -> bool {
+    let count = match pointer.len {
+        Some(Len::Param { ref ident, .. }) => cmd.params.iter().find(|b| b.ident == *ident),
+        _ => None,
+    };
+    match count.and_then(|count| sliced_by(cmd, count)) {
+        Some((sliced, _)) => sliced.ident == pointer.ident,
+        None => false,
+    }
+}
+
+/// Qualifies the paths in a type, so that it can be used in a module inside the bindings.
+///
+/// Paths inside other types are qualified too, as in `[super::types::GLfloat; 16]` or
+/// `super::errors::Result<super::types::GLuint>`.
+pub fn qualify(ty: &str) -> String {
+    let is_path_char = |c: char| c.is_alphanumeric() || c == '_' || c == ':';
+    let mut qualified = String::new();
+    let mut rest = ty;
+    while let Some(start) = rest.find(is_path_char) {
+        let end = rest[start..]
+            .find(|c| !is_path_char(c))
+            .map_or(rest.len(), |len| start + len);
+        let path = &rest[start..end];
+        qualified.push_str(&rest[..start]);
+        if path.contains("::") && !path.starts_with("::") {
+            qualified.push_str("super::");
+        }
+        qualified.push_str(path);
+        rest = &rest[end..];
+    }
+    qualified.push_str(rest);
+    qualified
+}
+
+/// Generates the parameters of the wrapper around a `Cmd`, along with the arguments that the
+/// wrapper passes to the command. Returns `None` if the command has no parameters that can be
+/// replaced by slices or arrays.
+fn gen_wrapper(cmd: &Cmd) -> Option<(Vec<String>, Vec<String>)> {
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    let mut wrapped = false;
+
+    for binding in &cmd.params {
+        let mutability = |mutable| if mutable { "mut " } else { "" };
+
+        if let Some((pointer, factor)) = sliced_by(cmd, binding) {
+            // the count is replaced by a slice
+            let (mutable, elem) = element(pointer).unwrap();
+            let elem = match factor {
+                1 => qualify(elem),
+                _ => format!("[{}; {}]", qualify(elem), factor),
+            };
+            params.push(format!(
+                "{}: &{}[{}]",
+                pointer.ident,
+                mutability(mutable),
+                elem
+            ));
+            args.push(format!(
+                "<{ty} as ::std::convert::TryFrom<usize>>::try_from({ptr}.len())
+                    .expect(\"`{ptr}` has more elements than `{count}` can hold\")",
+                ty = qualify(&binding.ty),
+                ptr = pointer.ident,
+                count = binding.ident,
+            ));
+            wrapped = true;
+        } else if is_sliced(cmd, binding) {
+            // the pointer comes from the slice that replaced the count
+            let (mutable, elem) = element(binding).unwrap();
+            let ptr = if mutable { "as_mut_ptr" } else { "as_ptr" };
+            match binding.len {
+                Some(Len::Param { factor: 1, .. }) => {
+                    args.push(format!("{}.{}()", binding.ident, ptr));
+                },
+                _ => args.push(format!(
+                    "{}.{}() as *{} {}",
+                    binding.ident,
+                    ptr,
+                    if mutable { "mut" } else { "const" },
+                    qualify(elem)
+                )),
+            }
+        } else if let (Some(&Len::Fixed(len)), Some((mutable, elem))) =
+            (binding.len.as_ref(), element(binding))
+        {
+            // a pointer to a fixed number of elements is replaced by a reference
+            let ty = match len {
+                1 => qualify(elem),
+                _ => format!("[{}; {}]", qualify(elem), len),
+            };
+            params.push(format!("{}: &{}{}", binding.ident, mutability(mutable), ty));
+            args.push(match len {
+                1 => binding.ident.clone(),
+                _ if mutable => format!("{}.as_mut_ptr()", binding.ident),
+                _ => format!("{}.as_ptr()", binding.ident),
+            });
+            wrapped = true;
+        } else {
+            params.push(format!("{}: {}", binding.ident, qualify(&binding.ty)));
+            args.push(binding.ident.clone());
+        }
+    }
+
+    if wrapped {
+        Some((params, args))
+    } else {
+        None
+    }
+}
+
+/// Creates a `slices` module which contains a wrapper around each command that takes arrays,
+/// where the arrays are replaced by slices or references to arrays.
+///
+/// With `CommandStyle::Methods`, the wrappers are methods of a `slices::Slices` struct, which is
+/// returned by a `slices` method on the struct that holds the commands.
+pub fn write_slices_mod<W>(registry: &Registry, style: CommandStyle, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let api = super::gen_struct_name(registry.api);
+
+    writeln!(
+        dest,
+        r#"
+        pub mod slices {{
+            #![allow(non_snake_case, dead_code)]
+    "#
+    )?;
+
+    if style == CommandStyle::Methods {
+        writeln!(
+            dest,
+            "/// Wrappers around the commands of a `{api}` that take slices instead of pointers.
+            #[derive(Copy, Clone)]
+            pub struct Slices<'a>(pub &'a super::{api});
+
+            impl<'a> Slices<'a> {{",
+            api = api,
+        )?;
+    }
+
+    for cmd in &registry.cmds {
+        let (params, args) = match gen_wrapper(cmd) {
+            Some(wrapper) => wrapper,
+            None => continue,
+        };
+        let (self_param, callee) = match style {
+            CommandStyle::Functions => ("", "super::"),
+            CommandStyle::Methods if params.is_empty() => ("&self", "self.0."),
+            CommandStyle::Methods => ("&self, ", "self.0."),
+        };
+        writeln!(
+            dest,
+            "#[inline]
+            pub unsafe fn {name}({self_param}{params}) -> {return_suffix} {{
+                {callee}{name}({args})
+            }}",
+            name = cmd.proto.ident,
+            self_param = self_param,
+            params = params.join(", "),
+            return_suffix = qualify(&cmd.proto.ty),
+            callee = callee,
+            args = args.join(", "),
+        )?;
+    }
+
+    if style == CommandStyle::Methods {
+        writeln!(dest, "}}")?;
+    }
+
+    writeln!(dest, "}}")?;
+
+    if style == CommandStyle::Methods {
+        writeln!(
+            dest,
+            "impl {api} {{
+                /// Returns wrappers around the commands that take slices instead of pointers.
+                #[inline]
+                pub fn slices<'a>(&'a self) -> slices::Slices<'a> {{
+                    slices::Slices(self)
+                }}
+            }}",
+            api = api,
+        )?;
+    }
+
+    Ok(())
+}
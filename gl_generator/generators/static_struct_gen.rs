@@ -31,6 +31,10 @@ impl super::Generator for StaticStructGenerator {
         write_fns(registry, dest)?;
         Ok(())
     }
+
+    fn command_style(&self) -> super::CommandStyle {
+        super::CommandStyle::Methods
+    }
 }
 
 /// Creates a `__gl_imports` module which contains all the external symbols that we need for the
@@ -65,7 +69,7 @@ where
     "#
     )?;
 
-    super::gen_types(registry.api, dest)?;
+    super::gen_types(registry, dest)?;
 
     writeln!(dest, "}}")
 }
@@ -32,11 +32,15 @@ impl super::Generator for StructGenerator {
         write_impl(registry, dest)?;
         Ok(())
     }
+
+    fn command_style(&self) -> super::CommandStyle {
+        super::CommandStyle::Methods
+    }
 }
 
 /// Creates a `__gl_imports` module which contains all the external symbols that we need for the
 ///  bindings.
-fn write_header<W>(dest: &mut W) -> io::Result<()>
+pub(super) fn write_header<W>(dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -55,7 +59,7 @@ where
 /// Creates a `types` module which contains all the type aliases.
 ///
 /// See also `generators::gen_types`.
-fn write_type_aliases<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_type_aliases<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -67,13 +71,13 @@ where
     "#
     )?;
 
-    super::gen_types(registry.api, dest)?;
+    super::gen_types(registry, dest)?;
 
     writeln!(dest, "}}")
 }
 
 /// Creates all the `<enum>` elements at the root of the bindings.
-fn write_enums<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_enums<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -85,7 +89,7 @@ where
 }
 
 /// Creates a `FnPtr` structure which contains the store for a single binding.
-fn write_fnptr_struct_def<W>(dest: &mut W) -> io::Result<()>
+pub(super) fn write_fnptr_struct_def<W>(dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -130,7 +134,7 @@ where
 /// Creates a `panicking` module which contains one function per GL command.
 ///
 /// These functions are the mocks that are called if the real function could not be loaded.
-fn write_panicking_fns<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_panicking_fns<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -147,7 +151,7 @@ where
 /// Creates a structure which stores all the `FnPtr` of the bindings.
 ///
 /// The name of the struct corresponds to the namespace.
-fn write_struct<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+pub(super) fn write_struct<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
@@ -164,6 +168,13 @@ where
         if let Some(v) = registry.aliases.get(&cmd.proto.ident) {
             writeln!(dest, "/// Fallbacks: {}", v.join(", "))?;
         }
+        if let Some(v) = super::gen_since_version(registry, cmd) {
+            writeln!(
+                dest,
+                "/// Available since version {}, so it may not be loaded.",
+                v
+            )?;
+        }
         writeln!(dest, "pub {name}: FnPtr,", name = cmd.proto.ident)?;
     }
     writeln!(dest, "_priv: ()")?;
@@ -173,11 +184,49 @@ where
 
 /// Creates the `impl` of the structure created by `write_struct`.
 fn write_impl<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        "impl {api} {{",
+        api = super::gen_struct_name(registry.api)
+    )?;
+
+    write_load_fn(registry, dest)?;
+
+    for cmd in &registry.cmds {
+        writeln!(dest,
+            "#[allow(non_snake_case, unused_variables, dead_code)]
+            #[inline] pub unsafe fn {name}(&self, {params}) -> {return_suffix} {{ \
+                __gl_imports::mem::transmute::<_, extern \"system\" fn({typed_params}) -> {return_suffix}>\
+                    (self.{name}.f)({idents}) \
+            }}",
+            name = cmd.proto.ident,
+            params = super::gen_parameters(cmd, true, true).join(", "),
+            typed_params = super::gen_parameters(cmd, false, true).join(", "),
+            return_suffix = cmd.proto.ty,
+            idents = super::gen_parameters(cmd, true, false).join(", "),
+        )?
+    }
+
+    writeln!(
+        dest,
+        "}}
+
+        unsafe impl __gl_imports::Send for {api} {{}}",
+        api = super::gen_struct_name(registry.api)
+    )
+}
+
+/// Creates the `load_with` function, inside the `impl` of the structure created by
+/// `write_struct`.
+pub(super) fn write_load_fn<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
 where
     W: io::Write,
 {
     writeln!(dest,
-                  "impl {api} {{
+                  "
             /// Load each OpenGL symbol using a custom load function. This allows for the
             /// use of functions like `glfwGetProcAddress` or `SDL_GL_GetProcAddress`.
             ///
@@ -229,28 +278,5 @@ where
         dest,
         "}}
         }}"
-    )?;
-
-    for cmd in &registry.cmds {
-        writeln!(dest,
-            "#[allow(non_snake_case, unused_variables, dead_code)]
-            #[inline] pub unsafe fn {name}(&self, {params}) -> {return_suffix} {{ \
-                __gl_imports::mem::transmute::<_, extern \"system\" fn({typed_params}) -> {return_suffix}>\
-                    (self.{name}.f)({idents}) \
-            }}",
-            name = cmd.proto.ident,
-            params = super::gen_parameters(cmd, true, true).join(", "),
-            typed_params = super::gen_parameters(cmd, false, true).join(", "),
-            return_suffix = cmd.proto.ty,
-            idents = super::gen_parameters(cmd, true, false).join(", "),
-        )?
-    }
-
-    writeln!(
-        dest,
-        "}}
-
-        unsafe impl __gl_imports::Send for {api} {{}}",
-        api = super::gen_struct_name(registry.api)
     )
 }
@@ -1,108 +0,0 @@
-// Common types from OpenGL 1.1
-pub type GLenum = super::__gl_imports::raw::c_uint;
-pub type GLboolean = super::__gl_imports::raw::c_uchar;
-pub type GLbitfield = super::__gl_imports::raw::c_uint;
-pub type GLvoid = super::__gl_imports::raw::c_void;
-pub type GLbyte = super::__gl_imports::raw::c_char;
-pub type GLshort = super::__gl_imports::raw::c_short;
-pub type GLint = super::__gl_imports::raw::c_int;
-pub type GLclampx = super::__gl_imports::raw::c_int;
-pub type GLubyte = super::__gl_imports::raw::c_uchar;
-pub type GLushort = super::__gl_imports::raw::c_ushort;
-pub type GLuint = super::__gl_imports::raw::c_uint;
-pub type GLsizei = super::__gl_imports::raw::c_int;
-pub type GLfloat = super::__gl_imports::raw::c_float;
-pub type GLclampf = super::__gl_imports::raw::c_float;
-pub type GLdouble = super::__gl_imports::raw::c_double;
-pub type GLclampd = super::__gl_imports::raw::c_double;
-pub type GLeglImageOES = *const super::__gl_imports::raw::c_void;
-pub type GLchar = super::__gl_imports::raw::c_char;
-pub type GLcharARB = super::__gl_imports::raw::c_char;
-
-#[cfg(target_os = "macos")]
-pub type GLhandleARB = *const super::__gl_imports::raw::c_void;
-#[cfg(not(target_os = "macos"))]
-pub type GLhandleARB = super::__gl_imports::raw::c_uint;
-
-pub type GLhalfARB = super::__gl_imports::raw::c_ushort;
-pub type GLhalf = super::__gl_imports::raw::c_ushort;
-
-// Must be 32 bits
-pub type GLfixed = GLint;
-
-pub type GLintptr = isize;
-pub type GLsizeiptr = isize;
-pub type GLint64 = i64;
-pub type GLuint64 = u64;
-pub type GLintptrARB = isize;
-pub type GLsizeiptrARB = isize;
-pub type GLint64EXT = i64;
-pub type GLuint64EXT = u64;
-
-pub enum __GLsync {}
-pub type GLsync = *const __GLsync;
-
-// compatible with OpenCL cl_context
-pub enum _cl_context {}
-pub enum _cl_event {}
-
-pub type GLDEBUGPROC = Option<extern "system" fn(source: GLenum,
-                                                 gltype: GLenum,
-                                                 id: GLuint,
-                                                 severity: GLenum,
-                                                 length: GLsizei,
-                                                 message: *const GLchar,
-                                                 userParam: *mut super::__gl_imports::raw::c_void)>;
-pub type GLDEBUGPROCARB = Option<extern "system" fn(source: GLenum,
-                                                    gltype: GLenum,
-                                                    id: GLuint,
-                                                    severity: GLenum,
-                                                    length: GLsizei,
-                                                    message: *const GLchar,
-                                                    userParam: *mut super::__gl_imports::raw::c_void)>;
-pub type GLDEBUGPROCKHR = Option<extern "system" fn(source: GLenum,
-                                                    gltype: GLenum,
-                                                    id: GLuint,
-                                                    severity: GLenum,
-                                                    length: GLsizei,
-                                                    message: *const GLchar,
-                                                    userParam: *mut super::__gl_imports::raw::c_void)>;
-
-// GLES 1 types
-// "pub type GLclampx = i32;",
-
-// GLES 1/2 types (tagged for GLES 1)
-// "pub type GLbyte = i8;",
-// "pub type GLubyte = u8;",
-// "pub type GLfloat = GLfloat;",
-// "pub type GLclampf = GLfloat;",
-// "pub type GLfixed = i32;",
-// "pub type GLint64 = i64;",
-// "pub type GLuint64 = u64;",
-// "pub type GLintptr = intptr_t;",
-// "pub type GLsizeiptr = ssize_t;",
-
-// GLES 1/2 types (tagged for GLES 2 - attribute syntax is limited)
-// "pub type GLbyte = i8;",
-// "pub type GLubyte = u8;",
-// "pub type GLfloat = GLfloat;",
-// "pub type GLclampf = GLfloat;",
-// "pub type GLfixed = i32;",
-// "pub type GLint64 = i64;",
-// "pub type GLuint64 = u64;",
-// "pub type GLint64EXT = i64;",
-// "pub type GLuint64EXT = u64;",
-// "pub type GLintptr = intptr_t;",
-// "pub type GLsizeiptr = ssize_t;",
-
-// GLES 2 types (none currently)
-
-// Vendor extension types
-pub type GLDEBUGPROCAMD = Option<extern "system" fn(id: GLuint,
-                                                    category: GLenum,
-                                                    severity: GLenum,
-                                                    length: GLsizei,
-                                                    message: *const GLchar,
-                                                    userParam: *mut super::__gl_imports::raw::c_void)>;
-pub type GLhalfNV = super::__gl_imports::raw::c_ushort;
-pub type GLvdpauSurfaceNV = GLintptr;
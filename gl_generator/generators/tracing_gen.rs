@@ -0,0 +1,653 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use registry::{Binding, CType, Cmd, Len, Registry, TypeDef};
+use std::io;
+use trace::{self, tag};
+
+use super::struct_gen;
+
+/// A generator that writes the same struct as `StructGenerator`, which can also record each
+/// call in a binary trace.
+///
+/// Tracing is started with `start_trace`, which takes any `io::Write`. Each call is recorded
+/// after it returns, with its arguments and return value. Pointers are recorded along with the
+/// data that they point to, when the registry gives its length. The traces can be read with
+/// `TraceReader`.
+#[allow(missing_copy_implementations)]
+#[derive(Debug)]
+pub struct TracingGenerator;
+
+impl super::Generator for TracingGenerator {
+    fn write<W>(&self, registry: &Registry, dest: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        write_header(dest)?;
+        struct_gen::write_type_aliases(registry, dest)?;
+        struct_gen::write_enums(registry, dest)?;
+        struct_gen::write_fnptr_struct_def(dest)?;
+        struct_gen::write_panicking_fns(registry, dest)?;
+        write_trace_mod(registry, dest)?;
+        write_struct(registry, dest)?;
+        write_impl(registry, dest)?;
+        Ok(())
+    }
+
+    fn command_style(&self) -> super::CommandStyle {
+        super::CommandStyle::Methods
+    }
+}
+
+/// Creates a `__gl_imports` module which contains all the external symbols that we need for the
+///  bindings.
+fn write_header<W>(dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+        mod __gl_imports {{
+            pub use std::ffi::CStr;
+            pub use std::io;
+            pub use std::mem;
+            pub use std::marker::Send;
+            pub use std::os::raw;
+            pub use std::slice;
+            pub use std::sync::{{Arc, Mutex}};
+        }}
+    "#
+    )
+}
+
+/// The primitive types that the scalar arguments are made of, with the tag that they are
+/// written with, and the expression that turns `self` into the bytes that follow the tag.
+const SCALARS: &[(&str, u8, &str)] = &[
+    ("u8", tag::U8, "self.to_le_bytes()"),
+    ("i8", tag::I8, "self.to_le_bytes()"),
+    ("u16", tag::U16, "self.to_le_bytes()"),
+    ("i16", tag::I16, "self.to_le_bytes()"),
+    ("u32", tag::U32, "self.to_le_bytes()"),
+    ("i32", tag::I32, "self.to_le_bytes()"),
+    ("u64", tag::U64, "self.to_le_bytes()"),
+    ("i64", tag::I64, "self.to_le_bytes()"),
+    ("usize", tag::U64, "(*self as u64).to_le_bytes()"),
+    ("isize", tag::I64, "(*self as i64).to_le_bytes()"),
+    ("f32", tag::F32, "self.to_bits().to_le_bytes()"),
+    ("f64", tag::F64, "self.to_bits().to_le_bytes()"),
+];
+
+/// Creates a `trace` module, with the `TraceArg` trait that writes arguments to a trace, and
+/// the `Tracer` that each struct writes its calls to.
+fn write_trace_mod<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        r#"
+        /// Recording the commands that are called through a `{api}` in a binary trace, which can be
+        /// read with `gl_generator::TraceReader`.
+        pub mod trace {{
+            use super::__gl_imports::{{io, mem, raw, slice, Arc, CStr, Mutex}};
+
+            /// The names of the commands, with the number of parameters that each takes, and
+            /// whether it returns a value. Calls refer to their command by its index.
+            const COMMANDS: &'static [(&'static str, u8, bool)] = &["#,
+        api = super::gen_struct_name(registry.api),
+    )?;
+    for cmd in &registry.cmds {
+        writeln!(
+            dest,
+            "(\"{}\", {}, {}),",
+            super::gen_symbol_name(registry.api, &cmd.proto.ident),
+            cmd.params.len(),
+            !cmd.proto.c_ty.is_void(),
+        )?;
+    }
+    writeln!(
+        dest,
+        r#"];
+
+            /// A value that can be written to a trace.
+            ///
+            /// This is implemented for the primitive types and raw pointers. Types that are
+            /// defined by a `TypeMap` must implement it to be used with a `{api}`.
+            pub trait TraceArg {{
+                /// Appends the value to `trace`, as a tag followed by its bytes.
+                fn write_arg(&self, trace: &mut Vec<u8>);
+            }}"#,
+        api = super::gen_struct_name(registry.api),
+    )?;
+
+    for &(ty, tag, bytes) in SCALARS {
+        if !ty.starts_with('f') {
+            writeln!(
+                dest,
+                "impl TraceCount for {ty} {{
+                    #[inline]
+                    fn count(self) -> usize {{
+                        if self > 0 {{ self as usize }} else {{ 0 }}
+                    }}
+                }}",
+                ty = ty,
+            )?;
+        }
+        writeln!(
+            dest,
+            "impl TraceArg for {ty} {{
+                #[inline]
+                fn write_arg(&self, trace: &mut Vec<u8>) {{
+                    trace.push({tag});
+                    trace.extend_from_slice(&{bytes});
+                }}
+            }}",
+            ty = ty,
+            tag = tag,
+            bytes = bytes,
+        )?;
+    }
+
+    writeln!(
+        dest,
+        r#"
+            impl<T> TraceArg for *const T {{
+                #[inline]
+                fn write_arg(&self, trace: &mut Vec<u8>) {{
+                    trace.push({pointer});
+                    trace.extend_from_slice(&(*self as usize as u64).to_le_bytes());
+                }}
+            }}
+
+            impl<T> TraceArg for *mut T {{
+                #[inline]
+                fn write_arg(&self, trace: &mut Vec<u8>) {{
+                    (*self as *const T).write_arg(trace)
+                }}
+            }}
+
+            /// A pointer to the elements of a buffer.
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub trait TracePtr {{
+                fn address(&self) -> *const u8;
+                fn element_size(&self) -> usize;
+            }}
+
+            impl<T> TracePtr for *const T {{
+                fn address(&self) -> *const u8 {{
+                    *self as *const u8
+                }}
+                fn element_size(&self) -> usize {{
+                    mem::size_of::<T>()
+                }}
+            }}
+
+            impl<T> TracePtr for *mut T {{
+                fn address(&self) -> *const u8 {{
+                    *self as *const u8
+                }}
+                fn element_size(&self) -> usize {{
+                    mem::size_of::<T>()
+                }}
+            }}
+
+            /// Appends the address and the bytes of a buffer. The length is written as a `u32`, so
+            /// only the first 4 GiB of larger buffers are recorded.
+            #[allow(dead_code)]
+            fn write_bytes(trace: &mut Vec<u8>, tag: u8, address: *const u8, bytes: &[u8]) {{
+                let len = bytes.len().min(::std::u32::MAX as usize);
+                trace.push(tag);
+                trace.extend_from_slice(&(address as usize as u64).to_le_bytes());
+                trace.extend_from_slice(&(len as u32).to_le_bytes());
+                trace.extend_from_slice(&bytes[..len]);
+            }}
+
+            /// Appends `len` elements from `ptr`, or only the pointer if it is null.
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub unsafe fn write_buffer<P: TracePtr>(trace: &mut Vec<u8>, ptr: P, len: usize) {{
+                let address = ptr.address();
+                if address.is_null() {{
+                    return address.write_arg(trace);
+                }}
+                let bytes = slice::from_raw_parts(address, len * ptr.element_size());
+                write_bytes(trace, {buffer}, address, bytes);
+            }}
+
+            /// Appends the nul-terminated string at `ptr`, or only the pointer if it is null.
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub unsafe fn write_string<P: TracePtr>(trace: &mut Vec<u8>, ptr: P) {{
+                let address = ptr.address();
+                if address.is_null() {{
+                    return address.write_arg(trace);
+                }}
+                let bytes = CStr::from_ptr(address as *const raw::c_char).to_bytes();
+                write_bytes(trace, {string}, address, bytes);
+            }}
+
+            /// Appends the first `length` bytes of the string at `ptr`, which need not be
+            /// nul-terminated, or the nul-terminated string if `length` is negative.
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub unsafe fn write_string_with_len<P: TracePtr>(trace: &mut Vec<u8>, ptr: P, length: i64) {{
+                let address = ptr.address();
+                if address.is_null() || length < 0 {{
+                    return write_string(trace, ptr);
+                }}
+                let bytes = slice::from_raw_parts(address, length as usize);
+                write_bytes(trace, {string}, address, bytes);
+            }}
+
+            /// A parameter that gives the number of elements in a buffer.
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            pub trait TraceCount {{
+                /// The number of elements, or zero if the count is negative.
+                fn count(self) -> usize;
+            }}
+
+            struct Output {{
+                out: Box<dyn io::Write + Send>,
+                /// The first error from writing `out`, after which nothing more is written.
+                error: Option<io::Error>,
+            }}
+
+            /// Where the calls are written. This is shared by the clones of a struct.
+            #[doc(hidden)]
+            #[derive(Clone, Default)]
+            pub struct Tracer {{
+                output: Option<Arc<Mutex<Output>>>,
+            }}
+
+            impl Tracer {{
+                /// Writes the header of a trace to `out`.
+                pub fn start<W>(out: W) -> io::Result<Tracer> where W: io::Write + Send + 'static {{
+                    let mut header = b"{magic}".to_vec();
+                    header.extend_from_slice(&{version}u16.to_le_bytes());
+                    header.push({api_len});
+                    header.extend_from_slice(b"{api}");
+                    header.extend_from_slice(&(COMMANDS.len() as u16).to_le_bytes());
+                    for &(name, params, returns) in COMMANDS {{
+                        header.push(name.len() as u8);
+                        header.extend_from_slice(name.as_bytes());
+                        header.push(params);
+                        header.push(returns as u8);
+                    }}
+
+                    let mut out: Box<dyn io::Write + Send> = Box::new(out);
+                    out.write_all(&header)?;
+                    Ok(Tracer {{
+                        output: Some(Arc::new(Mutex::new(Output {{ out, error: None }}))),
+                    }})
+                }}
+
+                #[inline]
+                pub fn is_tracing(&self) -> bool {{
+                    self.output.is_some()
+                }}
+
+                /// Starts the record of a call of the command at `id` in `COMMANDS`.
+                pub fn begin(&self, id: u16) -> Vec<u8> {{
+                    let mut call = Vec::with_capacity(64);
+                    call.extend_from_slice(&id.to_le_bytes());
+                    call
+                }}
+
+                /// Writes the record of a call, unless writing has already failed.
+                pub fn end(&self, call: Vec<u8>) {{
+                    if let Some(ref output) = self.output {{
+                        let mut output = output.lock().unwrap_or_else(|err| err.into_inner());
+                        if output.error.is_none() {{
+                            if let Err(err) = output.out.write_all(&call) {{
+                                output.error = Some(err);
+                            }}
+                        }}
+                    }}
+                }}
+
+                /// Flushes the output, and returns the first error from writing it.
+                pub fn finish(self) -> io::Result<()> {{
+                    match self.output {{
+                        Some(output) => {{
+                            let mut output = output.lock().unwrap_or_else(|err| err.into_inner());
+                            match output.error.take() {{
+                                Some(err) => Err(err),
+                                None => output.out.flush(),
+                            }}
+                        }},
+                        None => Ok(()),
+                    }}
+                }}
+            }}
+        }}"#,
+        pointer = tag::POINTER,
+        buffer = tag::BUFFER,
+        string = tag::STRING,
+        magic = String::from_utf8_lossy(&trace::MAGIC[..]).replace('\0', "\\0"),
+        version = trace::VERSION,
+        api_len = registry.api.to_string().len(),
+        api = registry.api,
+    )
+}
+
+/// Creates a structure which stores all the `FnPtr` of the bindings, and the `Tracer` that the
+/// calls are written to.
+///
+/// The name of the struct corresponds to the namespace.
+fn write_struct<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        "
+        #[allow(non_camel_case_types, non_snake_case, dead_code)]
+        #[derive(Clone)]
+        pub struct {api} {{",
+        api = super::gen_struct_name(registry.api)
+    )?;
+
+    for cmd in &registry.cmds {
+        if let Some(v) = registry.aliases.get(&cmd.proto.ident) {
+            writeln!(dest, "/// Fallbacks: {}", v.join(", "))?;
+        }
+        if let Some(v) = super::gen_since_version(registry, cmd) {
+            writeln!(
+                dest,
+                "/// Available since version {}, so it may not be loaded.",
+                v
+            )?;
+        }
+        writeln!(dest, "pub {name}: FnPtr,", name = cmd.proto.ident)?;
+    }
+    writeln!(dest, "tracer: trace::Tracer,")?;
+    writeln!(dest, "_priv: ()")?;
+
+    writeln!(dest, "}}")
+}
+
+/// Returns true if a type is one of the function pointers from the `types` module, which are
+/// `Option`s of Rust functions. Pointers to functions without parameters, such as
+/// `__GLXextFuncPtr`, are raw pointers instead.
+pub(crate) fn is_fn_ptr(registry: &Registry, ty: &CType) -> bool {
+    match *ty {
+        CType::Named(ref name) => match registry.types.get(name) {
+            Some(TypeDef::Function { params, .. }) => !params.is_empty(),
+            Some(TypeDef::Alias(ty)) => is_fn_ptr(registry, ty),
+            _ => false,
+        },
+        CType::Const(ref ty) => is_fn_ptr(registry, ty),
+        _ => false,
+    }
+}
+
+/// Returns true if a type is a pointer to constant characters.
+fn is_string(ty: &CType) -> bool {
+    match *ty {
+        CType::Pointer(ref ty) => match **ty {
+            CType::Const(ref ty) => match **ty {
+                CType::Named(ref name) => name == "GLchar" || name == "GLcharARB" || name == "char",
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The expression that gives the number of elements that a pointer parameter points to, if
+/// the registry says.
+fn gen_len(cmd: &Cmd, binding: &Binding) -> Option<String> {
+    match binding.c_ty {
+        CType::Pointer(_) => {},
+        _ => return None,
+    }
+    match binding.len {
+        Some(Len::Fixed(len)) => Some(len.to_string()),
+        Some(Len::Param { ref ident, factor }) => {
+            let count = cmd.params.iter().find(|param| param.ident == *ident)?;
+            match count.c_ty {
+                CType::Named(_) if !count.ty.contains("::") || count.ty.starts_with("types::") => {
+                    Some(format!(
+                        "trace::TraceCount::count({}) * {}",
+                        count.ident, factor
+                    ))
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+/// The statement that writes a parameter to the trace.
+fn gen_write_param(registry: &Registry, cmd: &Cmd, binding: &Binding) -> String {
+    if let Some(len) = gen_len(cmd, binding) {
+        return format!(
+            "trace::write_buffer(&mut call, {}, {});",
+            binding.ident, len
+        );
+    }
+    if !is_string(&binding.c_ty) {
+        return gen_write(registry, binding, &binding.ident);
+    }
+    let params = match binding.len {
+        None => return format!("trace::write_string(&mut call, {});", binding.ident),
+        Some(Len::Other(ref len)) => compsize_params(len),
+        _ => Vec::new(),
+    };
+    match params[..] {
+        // the length of the string is computed from the string itself
+        [string] if string == binding.ident => {
+            format!("trace::write_string(&mut call, {});", binding.ident)
+        },
+        // the string is `length` bytes long, or nul-terminated if `length` is negative
+        [string, length]
+            if string == binding.ident && cmd.params.iter().any(|param| param.ident == length) =>
+        {
+            format!(
+                "trace::write_string_with_len(&mut call, {}, {} as i64);",
+                binding.ident, length
+            )
+        },
+        _ => gen_write(registry, binding, &binding.ident),
+    }
+}
+
+/// The parameters that a `len="COMPSIZE(...)"` attribute names, or none if the length is
+/// another expression.
+fn compsize_params(len: &str) -> Vec<&str> {
+    if len.starts_with("COMPSIZE(") && len.ends_with(')') {
+        len[9..len.len() - 1]
+            .split(',')
+            .filter(|param| !param.is_empty())
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// The statement that writes `value`, the value of a parameter or the return value, to the
+/// trace, without what it points to.
+fn gen_write(registry: &Registry, binding: &Binding, value: &str) -> String {
+    let arg = if is_fn_ptr(registry, &binding.c_ty) {
+        format!(
+            "&({}.map_or(0, |f| f as usize) as *const __gl_imports::raw::c_void)",
+            value
+        )
+    } else if binding.ty.starts_with("groups::") {
+        let bitmask = binding
+            .group
+            .as_ref()
+            .and_then(|group| registry.groups.get(group))
+            .and_then(|group| group.enums_type.as_ref())
+            .map(String::as_ref)
+            == Some("bitmask");
+        if bitmask {
+            format!("&{}.bits()", value)
+        } else {
+            format!("&{}.0", value)
+        }
+    } else if binding.ty.starts_with("handles::") {
+        format!("&{}.0", value)
+    } else {
+        format!("&{}", value)
+    };
+    format!("trace::TraceArg::write_arg({}, &mut call);", arg)
+}
+
+/// Creates the `impl` of the structure created by `write_struct`.
+fn write_impl<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(
+        dest,
+        "impl {api} {{",
+        api = super::gen_struct_name(registry.api)
+    )?;
+
+    write_load_fn(registry, dest)?;
+
+    writeln!(
+        dest,
+        "
+        /// Starts writing each command that is called through this struct or its clones to
+        /// `out`, after it returns, along with its arguments and return value. Any trace that
+        /// was started before is finished first.
+        #[allow(dead_code)]
+        pub fn start_trace<W>(&mut self, out: W) -> __gl_imports::io::Result<()>
+        where
+            W: __gl_imports::io::Write + __gl_imports::Send + 'static,
+        {{
+            self.finish_trace()?;
+            self.tracer = trace::Tracer::start(out)?;
+            Ok(())
+        }}
+
+        /// Stops tracing, and flushes the trace. If any call could not be written, this returns
+        /// the error, and the calls after it were not written either.
+        #[allow(dead_code)]
+        pub fn finish_trace(&mut self) -> __gl_imports::io::Result<()> {{
+            __gl_imports::mem::replace(&mut self.tracer, trace::Tracer::default()).finish()
+        }}"
+    )?;
+
+    for (id, cmd) in registry.cmds.iter().enumerate() {
+        let mut writes: Vec<_> = cmd
+            .params
+            .iter()
+            .map(|param| gen_write_param(registry, cmd, param))
+            .collect();
+        if !cmd.proto.c_ty.is_void() {
+            // named so that it can't shadow a parameter
+            writes.push(gen_write(registry, &cmd.proto, "__ret"));
+        }
+
+        writeln!(dest,
+            "#[allow(non_snake_case, unused_variables, dead_code)]
+            #[inline] pub unsafe fn {name}(&self, {params}) -> {return_suffix} {{ \
+                let __ret = __gl_imports::mem::transmute::<_, extern \"system\" fn({typed_params}) -> {return_suffix}>\
+                    (self.{name}.f)({idents});
+                if self.tracer.is_tracing() {{
+                    let {mutability}call = self.tracer.begin({id});
+                    {writes}
+                    self.tracer.end(call);
+                }}
+                __ret
+            }}",
+            name = cmd.proto.ident,
+            params = super::gen_parameters(cmd, true, true).join(", "),
+            typed_params = super::gen_parameters(cmd, false, true).join(", "),
+            return_suffix = cmd.proto.ty,
+            idents = super::gen_parameters(cmd, true, false).join(", "),
+            id = id,
+            mutability = if writes.is_empty() { "" } else { "mut " },
+            writes = writes.join("\n"),
+        )?
+    }
+
+    writeln!(
+        dest,
+        "}}
+
+        unsafe impl __gl_imports::Send for {api} {{}}",
+        api = super::gen_struct_name(registry.api)
+    )
+}
+
+/// Creates the `load_with` function, inside the `impl` of the structure created by
+/// `write_struct`.
+fn write_load_fn<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    writeln!(dest,
+                  "
+            /// Load each OpenGL symbol using a custom load function. This allows for the
+            /// use of functions like `glfwGetProcAddress` or `SDL_GL_GetProcAddress`.
+            ///
+            /// ~~~ignore
+            /// let gl = Gl::load_with(|s| glfw.get_proc_address(s));
+            /// ~~~
+            #[allow(dead_code, unused_variables)]
+            pub fn load_with<F>(mut loadfn: F) -> {api} where F: FnMut(&'static str) -> *const __gl_imports::raw::c_void {{
+                #[inline(never)]
+                fn do_metaloadfn(loadfn: &mut dyn FnMut(&'static str) -> *const __gl_imports::raw::c_void,
+                                 symbol: &'static str,
+                                 symbols: &[&'static str])
+                                 -> *const __gl_imports::raw::c_void {{
+                    let mut ptr = loadfn(symbol);
+                    if ptr.is_null() {{
+                        for &sym in symbols {{
+                            ptr = loadfn(sym);
+                            if !ptr.is_null() {{ break; }}
+                        }}
+                    }}
+                    ptr
+                }}
+                let mut metaloadfn = |symbol: &'static str, symbols: &[&'static str]| {{
+                    do_metaloadfn(&mut loadfn, symbol, symbols)
+                }};
+                {api} {{",
+                  api = super::gen_struct_name(registry.api))?;
+
+    for cmd in &registry.cmds {
+        writeln!(
+            dest,
+            "{name}: FnPtr::new(metaloadfn(\"{symbol}\", &[{fallbacks}])),",
+            name = cmd.proto.ident,
+            symbol = super::gen_symbol_name(registry.api, &cmd.proto.ident),
+            fallbacks = match registry.aliases.get(&cmd.proto.ident) {
+                Some(fbs) => fbs
+                    .iter()
+                    .map(|name| format!("\"{}\"", super::gen_symbol_name(registry.api, name)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => String::new(),
+            },
+        )?
+    }
+    writeln!(dest, "tracer: trace::Tracer::default(),")?;
+    writeln!(dest, "_priv: ()")?;
+
+    writeln!(
+        dest,
+        "}}
+        }}"
+    )
+}
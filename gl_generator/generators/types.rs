@@ -0,0 +1,396 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use registry::{builtin, parse_type, Api, CType, Registry, TypeDef};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+
+/// Declarations of the types that the registries only refer to, because they come from platform
+/// headers such as `<X11/Xlib.h>` and `<windows.h>`, and of the types that keep the definitions
+/// they had before the `types` module was generated from the registries. These replace the
+/// declarations from the registries.
+const DECLARATIONS: &[&str] = &[
+    // Definitions from 0.14
+    "typedef char GLbyte;",
+    "typedef void (APIENTRY *GLDEBUGPROC)(GLenum source, GLenum gltype, GLuint id, \
+     GLenum severity, GLsizei length, const GLchar *message, void *userParam);",
+    "typedef void (APIENTRY *GLDEBUGPROCARB)(GLenum source, GLenum gltype, GLuint id, \
+     GLenum severity, GLsizei length, const GLchar *message, void *userParam);",
+    "typedef void (APIENTRY *GLDEBUGPROCKHR)(GLenum source, GLenum gltype, GLuint id, \
+     GLenum severity, GLsizei length, const GLchar *message, void *userParam);",
+    "typedef void *GLXContext;",
+    "typedef void *GLXFBConfig;",
+    "typedef void *GLXFBConfigSGIX;",
+    "typedef int GLXVideoDeviceNV;",
+    // Xlib
+    "typedef unsigned long XID;",
+    "typedef int Bool;",
+    "typedef int Status;",
+    "struct Display;",
+    "struct Visual;",
+    "typedef unsigned long VisualID;",
+    "typedef XID Colormap;",
+    "typedef XID Font;",
+    "typedef XID Pixmap;",
+    "typedef XID Window;",
+    "typedef struct { Visual *visual; VisualID visualid; int screen, depth, class; \
+     unsigned long red_mask, green_mask, blue_mask; int colormap_size, bits_per_rgb; } \
+     XVisualInfo;",
+    // SGI digital media libraries, from `<dmedia/dm_buffer.h>`, `<dmedia/dm_params.h>` and
+    // `<dmedia/vl.h>`
+    "struct _DMbuffer;",
+    "typedef struct _DMbuffer *DMbuffer;",
+    "struct DMparams;",
+    "struct _VLServer;",
+    "typedef struct _VLServer *VLServer;",
+    "typedef int VLNode;",
+    "typedef int VLPath;",
+    // Windows
+    "typedef int BOOL;",
+    "typedef unsigned char BYTE;",
+    "typedef char CHAR;",
+    "typedef unsigned long DWORD;",
+    "typedef float FLOAT;",
+    "typedef int INT;",
+    "typedef int32_t INT32;",
+    "typedef int64_t INT64;",
+    "typedef long LONG;",
+    "typedef unsigned int UINT;",
+    "typedef unsigned short USHORT;",
+    "typedef unsigned short WORD;",
+    "typedef const char *LPCSTR;",
+    "typedef void *LPVOID;",
+    "typedef void *PVOID;",
+    "typedef DWORD COLORREF;",
+    "typedef PVOID HANDLE;",
+    "typedef HANDLE HDC;",
+    "typedef HANDLE HENHMETAFILE;",
+    "typedef void *HGLRC;",
+    "typedef int (WINAPI *PROC)();",
+    "typedef struct { LONG left, top, right, bottom; } RECT;",
+    "typedef struct { FLOAT x, y; } POINTFLOAT;",
+    "typedef struct { FLOAT gmfBlackBoxX, gmfBlackBoxY; POINTFLOAT gmfptGlyphOrigin; \
+     FLOAT gmfCellIncX, gmfCellIncY; } GLYPHMETRICSFLOAT;",
+    "typedef GLYPHMETRICSFLOAT *LPGLYPHMETRICSFLOAT;",
+    "typedef struct { WORD nSize, nVersion; DWORD dwFlags; BYTE iPixelType, cColorBits, cRedBits, \
+     cRedShift, cGreenBits, cGreenShift, cBlueBits, cBlueShift, cAlphaBits, cAlphaShift, \
+     cAccumBits, cAccumRedBits, cAccumGreenBits, cAccumBlueBits, cAccumAlphaBits, cDepthBits, \
+     cStencilBits, cAuxBuffers, iLayerType, bReserved; COLORREF crTransparent; } \
+     LAYERPLANEDESCRIPTOR;",
+    "typedef struct { WORD nSize, nVersion; DWORD dwFlags; BYTE iPixelType, cColorBits, cRedBits, \
+     cRedShift, cGreenBits, cGreenShift, cBlueBits, cBlueShift, cAlphaBits, cAlphaShift, \
+     cAccumBits, cAccumRedBits, cAccumGreenBits, cAccumBlueBits, cAccumAlphaBits, cDepthBits, \
+     cStencilBits, cAuxBuffers, iLayerType, bReserved; DWORD dwLayerMask, dwVisibleMask, \
+     dwDamageMask; } PIXELFORMATDESCRIPTOR;",
+];
+
+/// Declarations that replace the ones above in the EGL bindings.
+const EGL_DECLARATIONS: &[&str] = &[
+    // Definitions from 0.14
+    "typedef EGLBoolean Bool;",
+];
+
+/// The types that C cannot declare for Rust: `GLhandleARB` depends on the preprocessor, and
+/// `VOID` is the return type of some WGL commands.
+const RUST_TYPES: &[(&str, &str)] = &[
+    (
+        "GLhandleARB",
+        "#[cfg(target_os = \"macos\")]
+         pub type GLhandleARB = *const super::__gl_imports::raw::c_void;
+         #[cfg(not(target_os = \"macos\"))]
+         pub type GLhandleARB = super::__gl_imports::raw::c_uint;",
+    ),
+    ("VOID", "pub type VOID = ();"),
+];
+
+/// The types that EGL bindings take from the level where they are included, as documented at the
+/// root of the crate.
+const EGL_PLATFORM_TYPES: &[&str] = &[
+    "khronos_utime_nanoseconds_t",
+    "khronos_uint64_t",
+    "khronos_ssize_t",
+    "EGLNativeDisplayType",
+    "EGLNativePixmapType",
+    "EGLNativeWindowType",
+    "EGLint",
+    "NativeDisplayType",
+    "NativePixmapType",
+    "NativeWindowType",
+];
+
+/// Rust definitions to use in place of the types from the registry, for
+/// `GeneratorOptions::type_map`.
+///
+/// The types are looked up by their C names, and may be typedefs from the registry, such as
+/// `GLsizeiptr`, types from platform headers, such as `EGLNativeWindowType`, or builtin types,
+/// such as `khronos_ssize_t`. The generated `types` module defines each type with its Rust
+/// definition, and the commands and constants refer to it there.
+///
+/// ```no_run
+/// # use gl_generator::TypeMap;
+/// let type_map = TypeMap::new()
+///     .alias("GLsizeiptr", "usize")
+///     .alias("EGLNativeWindowType", "::my_windowing::NativeWindow")
+///     .define(
+///         "GLboolean",
+///         "#[repr(transparent)] #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///          pub struct GLboolean(pub bool);",
+///     )
+///     .constants("GLboolean", "types::GLboolean({} != 0)");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeMap {
+    defs: BTreeMap<String, String>,
+    constants: BTreeMap<String, String>,
+}
+
+impl TypeMap {
+    pub fn new() -> TypeMap {
+        TypeMap::default()
+    }
+
+    /// Defines the named type as an alias of a Rust type, as in `pub type GLsizeiptr = usize;`.
+    /// The path to the Rust type is relative to the `types` module.
+    pub fn alias(self, name: &str, ty: &str) -> TypeMap {
+        let items = format!("pub type {} = {};", name, ty);
+        self.define(name, &items)
+    }
+
+    /// Defines the named type with Rust items, which are written to the `types` module as they
+    /// are, and must declare a type with that name. The type should implement `Copy`, `Clone`
+    /// and `Debug`, like the types it replaces.
+    pub fn define(mut self, name: &str, items: &str) -> TypeMap {
+        self.defs.insert(name.to_string(), items.to_string());
+        self
+    }
+
+    /// Sets the expression used for the constants of the named type, where `{}` stands for the
+    /// value from the registry, for example `"types::GLboolean({} != 0)"`. The expression is
+    /// relative to the root of the bindings.
+    ///
+    /// By default the value is used as it is, which only works for aliases of numeric types.
+    pub fn constants(mut self, name: &str, expr: &str) -> TypeMap {
+        self.constants.insert(name.to_string(), expr.to_string());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty() && self.constants.is_empty()
+    }
+
+    /// Replaces the definitions of the types in the registry with the ones from this map, and
+    /// updates the commands and constants to refer to them.
+    pub fn apply(&self, registry: &mut Registry) {
+        for (name, items) in &self.defs {
+            registry
+                .types
+                .insert(name.clone(), TypeDef::Rust(items.clone()));
+        }
+
+        // builtin types that are redefined are now found in the types module
+        let cmds = {
+            let types = &registry.types;
+            let name = |name: &str| {
+                if types.contains_key(name) {
+                    format!("types::{}", name)
+                } else {
+                    CType::Named(name.to_string()).to_rust_path("types::", "__gl_imports::raw::")
+                }
+            };
+            registry
+                .cmds
+                .iter()
+                .cloned()
+                .map(|mut cmd| {
+                    for binding in Some(&mut cmd.proto).into_iter().chain(&mut cmd.params) {
+                        binding.ty = Cow::Owned(binding.c_ty.to_rust_binding(&name));
+                    }
+                    cmd
+                })
+                .collect()
+        };
+        registry.cmds = cmds;
+
+        let enums = registry
+            .enums
+            .iter()
+            .cloned()
+            .map(|mut enm| {
+                if let Some(expr) = self.constants.get(enm.ty.as_ref()) {
+                    enm.value = expr.replace("{}", &enm.value);
+                    enm.cast = false;
+                }
+                enm
+            })
+            .collect();
+        registry.enums = enums;
+    }
+}
+
+/// Returns `true` if the named type is one that the bindings take from the level where they are
+/// included.
+fn is_platform_type(registry: &Registry, name: &str) -> bool {
+    registry.api == Api::Egl && EGL_PLATFORM_TYPES.contains(&name)
+}
+
+/// Converts a type for use inside the `types` module.
+fn to_rust(ty: &CType, registry: &Registry) -> String {
+    ty.to_rust(&|name: &str| {
+        if registry.types.contains_key(name) || is_platform_type(registry, name) {
+            name.to_string()
+        } else {
+            CType::Named(name.to_string()).to_rust_path("", "super::__gl_imports::raw::")
+        }
+    })
+}
+
+/// Writes the type definitions of the registry, followed by the definitions of the platform types
+/// that they need.
+pub fn write_types<W>(registry: &Registry, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    let egl_declarations = match registry.api {
+        Api::Egl => EGL_DECLARATIONS,
+        _ => &[],
+    };
+    let declarations: BTreeMap<_, _> = DECLARATIONS
+        .iter()
+        .chain(egl_declarations)
+        .map(|decl| match parse_type(decl, &|_: &str| None) {
+            Ok(result) => result,
+            Err(err) => panic!("could not parse `{}`: {}", decl, err),
+        })
+        .collect();
+
+    let mut written = BTreeSet::new();
+    if registry.api == Api::Egl {
+        for &name in EGL_PLATFORM_TYPES {
+            if let Some(&TypeDef::Rust(_)) = registry.types.get(name) {
+                continue;
+            }
+            writeln!(
+                dest,
+                "#[allow(dead_code)] pub type {name} = super::{name};",
+                name = name
+            )?;
+            written.insert(name);
+        }
+    }
+
+    let mut pending: Vec<_> = registry.types.keys().rev().map(String::as_str).collect();
+    while let Some(name) = pending.pop() {
+        if !written.insert(name) {
+            continue;
+        }
+        if let Some(TypeDef::Rust(items)) = registry.types.get(name) {
+            writeln!(dest, "{}", items)?;
+            continue;
+        }
+        if let Some(&(_, items)) = RUST_TYPES.iter().find(|&&(rust, _)| rust == name) {
+            writeln!(dest, "{}", items)?;
+            continue;
+        }
+        let def = declarations.get(name).or_else(|| registry.types.get(name));
+        match def {
+            Some(&TypeDef::External) | None => {
+                writeln!(dest, "pub type {name} = super::{name};", name = name)?
+            },
+            Some(def) => {
+                write_def(registry, name, def, dest)?;
+                for ty in def.dependencies() {
+                    match *ty {
+                        CType::Named(ref name) | CType::Struct(ref name)
+                            if builtin(name).is_none() =>
+                        {
+                            pending.push(name)
+                        },
+                        _ => {},
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the definition of a type that is declared in C.
+fn write_def<W>(registry: &Registry, name: &str, def: &TypeDef, dest: &mut W) -> io::Result<()>
+where
+    W: io::Write,
+{
+    match *def {
+        // handles are `*const` pointers, as they were in 0.14
+        TypeDef::Alias(CType::Pointer(ref ty)) => {
+            writeln!(
+                dest,
+                "pub type {} = *const {};",
+                name,
+                to_rust(ty, registry)
+            )
+        },
+        TypeDef::Alias(ref ty) => writeln!(dest, "pub type {} = {};", name, to_rust(ty, registry)),
+        // pointers to any function, which must be cast to the right type to be called
+        TypeDef::Function { ref params, .. } if params.is_empty() => writeln!(
+            dest,
+            "pub enum {fn_name} {{}} pub type {name} = *mut {fn_name};",
+            name = name,
+            fn_name = format!("__{}_fn", name.trim_start_matches('_')),
+        ),
+        TypeDef::Function {
+            ref ret,
+            ref params,
+            apientry,
+        } => {
+            let params: Vec<_> = params
+                .iter()
+                .map(|(ident, ty)| match *ident {
+                    Some(ref ident) => format!("{}: {}", ident, to_rust(ty, registry)),
+                    None => to_rust(ty, registry),
+                })
+                .collect();
+            writeln!(
+                dest,
+                "pub type {name} = Option<extern \"{abi}\" fn({params}){ret}>;",
+                name = name,
+                abi = if apientry { "system" } else { "C" },
+                params = params.join(", "),
+                ret = if ret.is_void() {
+                    String::new()
+                } else {
+                    format!(" -> {}", to_rust(ret, registry))
+                },
+            )
+        },
+        TypeDef::Struct(ref fields) | TypeDef::Union(ref fields) => {
+            let keyword = match *def {
+                TypeDef::Union(_) => "union",
+                _ => "struct",
+            };
+            writeln!(
+                dest,
+                "#[repr(C)] #[derive(Copy, Clone)] pub {} {} {{",
+                keyword, name
+            )?;
+            for (ident, ty) in fields {
+                writeln!(dest, "pub {}: {},", ident, to_rust(ty, registry))?;
+            }
+            writeln!(dest, "}}")
+        },
+        TypeDef::Opaque => writeln!(dest, "pub enum {} {{}}", name),
+        TypeDef::External | TypeDef::Rust(_) => Ok(()),
+    }
+}
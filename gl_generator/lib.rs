@@ -62,6 +62,9 @@
 
 #[macro_use]
 extern crate log;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
 extern crate xml;
 
 #[cfg(feature = "unstable_generator_utils")]
@@ -70,12 +73,16 @@ pub mod generators;
 mod generators;
 
 mod registry;
+mod trace;
 
 pub use generators::debug_struct_gen::DebugStructGenerator;
+pub use generators::error_checking_gen::{ErrorCheckingGenerator, ErrorReport};
 pub use generators::global_gen::GlobalGenerator;
 pub use generators::static_gen::StaticGenerator;
 pub use generators::static_struct_gen::StaticStructGenerator;
 pub use generators::struct_gen::StructGenerator;
-pub use generators::Generator;
+pub use generators::tracing_gen::TracingGenerator;
+pub use generators::{CommandStyle, Generator, GeneratorOptions, TypeMap};
 
 pub use registry::*;
+pub use trace::{TraceCall, TraceCommand, TraceError, TraceReader, TraceValue};
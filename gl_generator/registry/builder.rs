@@ -0,0 +1,186 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ops::RangeInclusive;
+#[cfg(feature = "serde")]
+use std::path::PathBuf;
+
+#[cfg(feature = "serde")]
+use registry::{bundled_sources, cache};
+use registry::{Filter, Registry, RegistryError, Supplement};
+use {Api, Fallbacks, Profile};
+
+/// Builds a `Registry`, selecting extensions by name, pattern, or vendor.
+///
+/// ```no_run
+/// # use gl_generator::{Api, RegistryBuilder};
+/// let registry = RegistryBuilder::new(Api::Gl, (4, 5))
+///     .extensions_matching("GL_ARB_*")
+///     .vendors(&["EXT", "KHR"])
+///     .exclude_extensions(&["GL_ARB_shading_language_include"])
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RegistryBuilder {
+    filter: Filter,
+    supplements: Vec<Supplement>,
+    #[cfg(feature = "serde")]
+    cache_dir: Option<PathBuf>,
+}
+
+impl RegistryBuilder {
+    /// Starts building a registry for the core profile of `api`, with fallbacks enabled and no
+    /// extensions.
+    pub fn new(api: Api, version: (u8, u8)) -> RegistryBuilder {
+        RegistryBuilder {
+            filter: Filter::new(api, version, Profile::Core, Fallbacks::All, []),
+            supplements: Supplement::defaults(),
+            #[cfg(feature = "serde")]
+            cache_dir: None,
+        }
+    }
+
+    pub fn version(mut self, version: (u8, u8)) -> RegistryBuilder {
+        self.filter.version = version;
+        self
+    }
+
+    /// Includes everything up to the end of `range`, while targeting the start of the range as
+    /// the oldest supported version. Commands introduced after the start of the range are
+    /// documented as such, and should be checked with `is_loaded` before they are called.
+    pub fn version_range(mut self, range: RangeInclusive<(u8, u8)>) -> RegistryBuilder {
+        self.filter.min_version = Some(*range.start());
+        self.filter.version = *range.end();
+        self
+    }
+
+    pub fn profile(mut self, profile: Profile) -> RegistryBuilder {
+        self.filter.profile = profile;
+        self
+    }
+
+    pub fn fallbacks(mut self, fallbacks: Fallbacks) -> RegistryBuilder {
+        self.filter.fallbacks = fallbacks;
+        self
+    }
+
+    /// Includes extensions by their exact names. It is an error for any of these not to exist,
+    /// or not to support the API.
+    pub fn extensions<I, S>(mut self, names: I) -> RegistryBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let names = names.into_iter().map(|name| name.as_ref().to_string());
+        self.filter.extensions.extend(names);
+        self
+    }
+
+    /// Includes every extension matching `pattern` that supports the API. `*` matches any
+    /// sequence of characters, and `?` matches any single character.
+    ///
+    /// It is an error for the pattern not to match any extensions.
+    pub fn extensions_matching(mut self, pattern: &str) -> RegistryBuilder {
+        self.filter.extensions.insert(pattern.to_string());
+        self
+    }
+
+    /// Includes every extension that supports the API.
+    pub fn all_extensions(self) -> RegistryBuilder {
+        self.extensions_matching("*")
+    }
+
+    /// Includes every extension from the given vendors that supports the API. For example, the
+    /// vendor `"ARB"` selects `GL_ARB_*` extensions when building a GL registry.
+    pub fn vendors<I, S>(mut self, vendors: I) -> RegistryBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let prefix = extension_prefix(self.filter.api);
+        for vendor in vendors {
+            let pattern = format!("{}_{}_*", prefix, vendor.as_ref());
+            self.filter.extensions.insert(pattern);
+        }
+        self
+    }
+
+    /// Leaves out extensions, even if they were selected by a pattern or vendor. The names may
+    /// contain wildcards.
+    pub fn exclude_extensions<I, S>(mut self, names: I) -> RegistryBuilder
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let names = names.into_iter().map(|name| name.as_ref().to_string());
+        self.filter.excluded_extensions.extend(names);
+        self
+    }
+
+    /// Adds a supplemental registry, which is parsed if any of the requested extensions need it.
+    /// The ANGLE registries from `Supplement::defaults` are included unless
+    /// `clear_supplements` is called.
+    pub fn supplement(mut self, supplement: Supplement) -> RegistryBuilder {
+        self.supplements.push(supplement);
+        self
+    }
+
+    /// Removes all of the supplemental registries, including the defaults.
+    pub fn clear_supplements(mut self) -> RegistryBuilder {
+        self.supplements.clear();
+        self
+    }
+
+    /// Stores a snapshot of the built registry in `dir`, and reuses it in later builds with the
+    /// same filter and XML instead of parsing the XML again. Requires the `serde` feature.
+    ///
+    /// A directory that is shared between crates, such as one under the workspace's target
+    /// directory, lets their build scripts share the snapshots too. If a snapshot can't be stored,
+    /// the registry is still returned and Cargo shows a warning.
+    #[cfg(feature = "serde")]
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, dir: P) -> RegistryBuilder {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Returns the filter that has been built up so far, for use with `Registry::from_sources`.
+    pub fn filter(&self) -> &Filter {
+        &self.filter
+    }
+
+    /// Builds the registry from the Khronos XML bundled with `khronos_api`, along with any
+    /// supplemental registries that the requested extensions need.
+    pub fn build(&self) -> Result<Registry, RegistryError> {
+        #[cfg(feature = "serde")]
+        {
+            if let Some(ref dir) = self.cache_dir {
+                let sources = bundled_sources(&self.filter, &self.supplements);
+                return cache::load_or_build(dir, &self.filter, &sources, || {
+                    Registry::from_filter_with_supplements(&self.filter, &self.supplements)
+                });
+            }
+        }
+        Registry::from_filter_with_supplements(&self.filter, &self.supplements)
+    }
+}
+
+fn extension_prefix(api: Api) -> &'static str {
+    match api {
+        Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => "GL",
+        Api::Glx => "GLX",
+        Api::Wgl => "WGL",
+        Api::Egl => "EGL",
+    }
+}
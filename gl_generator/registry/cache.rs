@@ -0,0 +1,218 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snapshots of parsed registries, stored by `RegistryBuilder::cache_dir` so that build scripts
+//! don't have to parse the same XML again.
+
+extern crate serde_json;
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+
+use registry::{Filter, Registry, RegistryError};
+
+/// The name of the snapshot of the registry built from `sources` with `filter`.
+///
+/// The version of this crate is part of the key, so that snapshots are not shared between
+/// versions that might parse the XML differently. The key is hashed with FNV-1a, rather than
+/// `DefaultHasher`, so that the names stay the same across Rust releases.
+fn file_name(filter: &Filter, sources: &[&[u8]]) -> String {
+    let filter = serde_json::to_vec(filter).expect("filters can always be serialized");
+    let mut hash = Fnv1a::new();
+    hash.write(env!("CARGO_PKG_VERSION").as_bytes());
+    hash.write_len(filter.len());
+    hash.write(&filter);
+    for source in sources {
+        hash.write_len(source.len());
+        hash.write(source);
+    }
+    format!("registry-{:016x}.json", hash.0)
+}
+
+/// The 64-bit FNV-1a hash, as specified by <http://www.isthe.com/chongo/tech/comp/fnv/>.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Fnv1a {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Writes the length of the bytes that follow, so that moving bytes from one part of the key
+    /// to the next changes the hash.
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+}
+
+/// Reads the snapshot of the registry built from `sources` with `filter` from `dir`, or calls
+/// `build` and stores a snapshot of the result if there isn't one.
+pub fn load_or_build<F>(
+    dir: &Path,
+    filter: &Filter,
+    sources: &[&[u8]],
+    build: F,
+) -> Result<Registry, RegistryError>
+where
+    F: FnOnce() -> Result<Registry, RegistryError>,
+{
+    let path = dir.join(file_name(filter, sources));
+    if let Ok(file) = File::open(&path) {
+        // A snapshot that can't be read, for example because it was cut short, is replaced
+        if let Ok(registry) = serde_json::from_reader(io::BufReader::new(file)) {
+            return Ok(registry);
+        }
+    }
+
+    let registry = build()?;
+    // The registry was built, so a cache that can't be written only costs time on the next build
+    if let Err(err) = store(dir, &path, &registry) {
+        println!(
+            "cargo:warning=could not cache the registry in {}: {}",
+            path.display(),
+            err
+        );
+    }
+    Ok(registry)
+}
+
+fn store(dir: &Path, path: &Path, registry: &Registry) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    // Build scripts can run in parallel, so the snapshot is written to a file of its own and
+    // then moved into place, where other processes will only ever see the whole of it
+    let tmp = path.with_extension(format!("{}.tmp", process::id()));
+    {
+        let mut file = io::BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut file, registry)?;
+        file.flush()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_name, load_or_build, Fnv1a};
+    use registry::{Filter, Registry};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use {Api, Fallbacks, Profile};
+
+    const SRC: &str = r#"
+        <registry>
+            <enums namespace="GL">
+                <enum value="0x0001" name="GL_FOO"/>
+            </enums>
+            <commands namespace="GL">
+                <command>
+                    <proto>void <name>glFoo</name></proto>
+                </command>
+            </commands>
+            <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                <require>
+                    <enum name="GL_FOO"/>
+                    <command name="glFoo"/>
+                </require>
+            </feature>
+        </registry>
+    "#;
+
+    fn filter() -> Filter {
+        Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, [])
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("gl_generator-{}-{}", name, ::std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_file_name() {
+        let other_filter = Filter::new(Api::Gles2, (1, 0), Profile::Core, Fallbacks::All, []);
+        let name = file_name(&filter(), &[SRC.as_bytes()]);
+        assert_eq!(name, file_name(&filter(), &[SRC.as_bytes()]));
+        assert_ne!(name, file_name(&other_filter, &[SRC.as_bytes()]));
+        assert_ne!(name, file_name(&filter(), &[b"<registry/>"]));
+        assert_ne!(
+            name,
+            file_name(&filter(), &[SRC.as_bytes(), b"<registry/>"])
+        );
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        // test vectors from the FNV specification
+        let hash = |bytes: &[u8]| {
+            let mut hash = Fnv1a::new();
+            hash.write(bytes);
+            hash.0
+        };
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn test_load_or_build() {
+        let dir = temp_dir("cache");
+        let sources = [SRC.as_bytes()];
+        let build = || Registry::from_sources(&filter(), vec![SRC.as_bytes()]);
+
+        let built = load_or_build(&dir, &filter(), &sources, build).unwrap();
+        assert!(dir.join(file_name(&filter(), &sources)).is_file());
+        let cached = load_or_build(&dir, &filter(), &sources, || panic!("not cached")).unwrap();
+        assert_eq!(cached, built);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unwritable_dir() {
+        // a file where the directory should be
+        let dir = temp_dir("unwritable");
+        fs::write(&dir, "").unwrap();
+
+        let build = || Registry::from_sources(&filter(), vec![SRC.as_bytes()]);
+        let registry = load_or_build(&dir, &filter(), &[SRC.as_bytes()], build).unwrap();
+        assert_eq!(registry.cmds.len(), 1);
+
+        fs::remove_file(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_snapshot() {
+        let dir = temp_dir("corrupt");
+        let sources = [SRC.as_bytes()];
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(file_name(&filter(), &sources)), "{\"api\":").unwrap();
+
+        let build = || Registry::from_sources(&filter(), vec![SRC.as_bytes()]);
+        let registry = load_or_build(&dir, &filter(), &sources, build).unwrap();
+        assert_eq!(registry.cmds.len(), 1);
+        let cached = load_or_build(&dir, &filter(), &sources, || panic!("not cached")).unwrap();
+        assert_eq!(cached, registry);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
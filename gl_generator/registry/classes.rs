@@ -0,0 +1,229 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use registry::Cmd;
+use std::collections::BTreeSet;
+
+/// Fills in the classes of the object names from the names of the commands, for registries that
+/// have no `class` attributes, such as the `gl.xml` bundled with `khronos_api`.
+///
+/// The classes are the objects that the core `Gen*` and `Create*` commands make, such as `buffer`
+/// from `GenBuffers` and `program` from `CreateProgram`. The names of a class are then found in:
+///
+/// - the arrays of `Gen*`, `Create*` and `Delete*` commands for that class, as in
+///   `DeleteBuffers(n, buffers)`,
+/// - the last `GLuint` parameter of `Bind*`, `Delete*` and `Is*` commands for that class, as in
+///   `BindVertexArray(array)`,
+/// - the return value of `Create*` commands, as in `CreateProgram()`,
+/// - any `GLuint` parameter that is named after the class, as in `UseProgram(program)`, and any
+///   `GLuint` array that is named after its plural, as in `BindTextures(first, count, textures)`.
+///
+/// Nothing is changed if any binding already has a class.
+pub fn infer(cmds: &mut [Cmd]) {
+    let has_classes = cmds.iter().any(|cmd| {
+        Some(&cmd.proto)
+            .into_iter()
+            .chain(&cmd.params)
+            .any(|b| b.class.is_some())
+    });
+    if has_classes {
+        return;
+    }
+
+    let classes: BTreeSet<_> = cmds.iter().filter_map(created_class).collect();
+    for cmd in cmds {
+        assign(cmd, &classes);
+    }
+}
+
+const GLUINT: &str = "types::GLuint";
+const GLUINT_ARRAYS: &[&str] = &["*const types::GLuint", "*mut types::GLuint"];
+
+/// The verbs of the commands that manage the objects of a class.
+const VERBS: &[&str] = &["Gen", "Create", "Delete", "Bind", "Is"];
+
+/// The class of the objects that a core `Gen*` or `Create*` command makes.
+fn created_class(cmd: &Cmd) -> Option<String> {
+    if strip_vendor(&cmd.proto.ident) != cmd.proto.ident {
+        return None;
+    }
+    let (verb, object) = split_verb(&cmd.proto.ident)?;
+    let makes_array = cmd.params.iter().any(|p| p.ty == "*mut types::GLuint");
+    match verb {
+        "Gen" | "Create" if makes_array => singular(&words(object)),
+        "Create" if cmd.proto.ty == GLUINT => {
+            // `CreateShaderProgramv` makes a program
+            let object = object.trim_end_matches('v');
+            words(object).pop()
+        },
+        _ => None,
+    }
+}
+
+fn assign(cmd: &mut Cmd, classes: &BTreeSet<String>) {
+    let ident = strip_vendor(&cmd.proto.ident).to_string();
+    let (verb, object) = split_verb(&ident).unwrap_or(("", ""));
+    // the class of the command itself, as in `GenBuffers` or `BindBuffer`
+    let plural = singular(&words(object)).filter(|class| classes.contains(class));
+    let single = Some(words(object).join(" ")).filter(|class| classes.contains(class));
+
+    if verb == "Create" && cmd.proto.ty == GLUINT {
+        let object = words(object.trim_end_matches('v')).pop();
+        cmd.proto.class = object.filter(|class| classes.contains(class));
+    }
+
+    let last_name = cmd.params.iter().rposition(|p| p.ty == GLUINT);
+    for (index, param) in cmd.params.iter_mut().enumerate() {
+        param.class = if param.ty == GLUINT {
+            match single {
+                Some(ref class)
+                    if Some(index) == last_name && matches!(verb, "Bind" | "Delete" | "Is") =>
+                {
+                    Some(class.clone())
+                },
+                _ => classes
+                    .iter()
+                    .find(|class| param.ident == camel_case(class))
+                    .cloned(),
+            }
+        } else if GLUINT_ARRAYS.contains(&param.ty.as_ref()) {
+            match plural {
+                Some(ref class) if matches!(verb, "Gen" | "Create" | "Delete") => {
+                    Some(class.clone())
+                },
+                _ => classes
+                    .iter()
+                    .find(|class| param.ident == camel_case(class) + "s")
+                    .cloned(),
+            }
+        } else {
+            None
+        };
+    }
+}
+
+/// Removes the vendor suffix from the name of a command, as in `GenBuffersARB`.
+fn strip_vendor(ident: &str) -> &str {
+    let suffix = ident
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_uppercase())
+        .count();
+    if suffix >= 2 {
+        &ident[..ident.len() - suffix]
+    } else {
+        ident
+    }
+}
+
+/// Splits the name of a command into one of `VERBS` and the rest of the name.
+fn split_verb(ident: &str) -> Option<(&'static str, &str)> {
+    VERBS.iter().find_map(|&verb| {
+        let object = ident.strip_prefix(verb)?;
+        if object.starts_with(|c: char| c.is_ascii_uppercase()) {
+            Some((verb, object))
+        } else {
+            None
+        }
+    })
+}
+
+/// Splits a camel case name into lowercase words, as in `"VertexArrays"` to
+/// `["vertex", "arrays"]`.
+fn words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for c in name.chars() {
+        match words.last_mut() {
+            Some(word) if !c.is_ascii_uppercase() => word.push(c),
+            _ => words.push(c.to_ascii_lowercase().to_string()),
+        }
+    }
+    words
+}
+
+/// Joins the words of a plural name into the name of a class, as in `["vertex", "arrays"]` to
+/// `"vertex array"`, or returns `None` if the name is not plural.
+fn singular(words: &[String]) -> Option<String> {
+    let (last, rest) = words.split_last()?;
+    let last = if let Some(stem) = last.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else {
+        last.strip_suffix('s')?.to_string()
+    };
+    let mut words = rest.to_vec();
+    words.push(last);
+    Some(words.join(" "))
+}
+
+/// The name of a parameter that refers to an object of a class, as in `"vertexArray"`.
+fn camel_case(class: &str) -> String {
+    let mut words = class.split(' ');
+    let first = words.next().unwrap_or("").to_string();
+    words.fold(first, |mut name, word| {
+        let mut chars = word.chars();
+        name.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        name.extend(chars);
+        name
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use registry::{Cmd, Registry};
+    use {Api, Fallbacks, Profile};
+
+    fn class_of(registry: &Registry, cmd: &str, param: &str) -> Option<String> {
+        let cmd: &Cmd = registry.cmds.iter().find(|c| c.proto.ident == cmd).unwrap();
+        match param {
+            "return" => cmd.proto.class.clone(),
+            _ => cmd
+                .params
+                .iter()
+                .find(|p| p.ident == param)
+                .unwrap()
+                .class
+                .clone(),
+        }
+    }
+
+    #[test]
+    fn test_inferred_classes() {
+        let registry = Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, []);
+        let class = |cmd, param| class_of(&registry, cmd, param);
+        let some = |class: &str| Some(class.to_string());
+
+        assert_eq!(class("GenBuffers", "buffers"), some("buffer"));
+        assert_eq!(class("GenBuffers", "n"), None);
+        assert_eq!(class("BindBuffer", "buffer"), some("buffer"));
+        assert_eq!(class("BindBuffer", "target"), None);
+        assert_eq!(class("DeleteBuffers", "buffers"), some("buffer"));
+        assert_eq!(class("CreateTextures", "textures"), some("texture"));
+        assert_eq!(class("BindTextures", "textures"), some("texture"));
+        assert_eq!(class("GenQueries", "ids"), some("query"));
+        assert_eq!(class("GenVertexArrays", "arrays"), some("vertex array"));
+        assert_eq!(class("BindVertexArray", "array"), some("vertex array"));
+        assert_eq!(class("BindSampler", "unit"), None);
+        assert_eq!(class("BindSampler", "sampler"), some("sampler"));
+        assert_eq!(
+            class("BindProgramPipeline", "pipeline"),
+            some("program pipeline")
+        );
+        assert_eq!(class("CreateProgram", "return"), some("program"));
+        assert_eq!(class("CreateShaderProgramv", "return"), some("program"));
+        assert_eq!(class("AttachShader", "program"), some("program"));
+        assert_eq!(class("AttachShader", "shader"), some("shader"));
+        assert_eq!(class("FramebufferTexture", "texture"), some("texture"));
+        assert_eq!(class("ActiveTexture", "texture"), None);
+    }
+}
@@ -0,0 +1,817 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The small subset of C declarations used by the Khronos XML registries.
+
+use registry::parse::underscore_keyword;
+
+/// A C type, as written in a declaration.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CType {
+    /// A builtin type or a typedef, for example `unsigned int` or `GLenum`.
+    Named(String),
+    /// A struct or union that is referred to by its tag, for example `struct _cl_event`.
+    Struct(String),
+    /// A `const` qualified type.
+    Const(Box<CType>),
+    /// A pointer to a type.
+    Pointer(Box<CType>),
+    /// An array with a fixed number of elements.
+    Array(Box<CType>, usize),
+}
+
+impl CType {
+    /// The named type or struct that this type is built from.
+    pub fn base(&self) -> &CType {
+        match *self {
+            CType::Named(_) | CType::Struct(_) => self,
+            CType::Const(ref ty) | CType::Pointer(ref ty) | CType::Array(ref ty, _) => ty.base(),
+        }
+    }
+
+    /// Returns `true` if the type is `void`, ignoring any qualifiers.
+    pub fn is_void(&self) -> bool {
+        match *self {
+            CType::Named(ref name) => name == "void",
+            CType::Const(ref ty) => ty.is_void(),
+            _ => false,
+        }
+    }
+
+    /// Converts the type to Rust. `name` is given the name of each named type or struct that
+    /// the type is built from, and returns the path to use for it.
+    ///
+    /// Pointers to `const` types become `*const` pointers, and all other pointers become
+    /// `*mut` pointers.
+    pub fn to_rust<F>(&self, name: &F) -> String
+    where
+        F: Fn(&str) -> String,
+    {
+        match *self {
+            CType::Named(ref ident) | CType::Struct(ref ident) => name(ident),
+            CType::Const(ref ty) => ty.to_rust(name),
+            CType::Pointer(ref ty) => match **ty {
+                CType::Const(ref ty) => format!("*const {}", ty.to_rust(name)),
+                ref ty => format!("*mut {}", ty.to_rust(name)),
+            },
+            CType::Array(ref ty, len) => format!("[{}; {}]", ty.to_rust(name), len),
+        }
+    }
+
+    /// Converts the type to Rust, where typedefs are found in the module at `types` and the
+    /// builtin C types in the module at `raw`, for example `"types::"` and
+    /// `"__gl_imports::raw::"`.
+    pub fn to_rust_path(&self, types: &str, raw: &str) -> String {
+        self.to_rust(&|name: &str| name_to_rust(name, types, raw))
+    }
+
+    /// Converts the type of a parameter or return value to Rust, like `to_rust`.
+    ///
+    /// A `void` return type becomes `()`, and arrays are passed as pointers to arrays, which are
+    /// `*const` pointers when the elements are `const`.
+    pub fn to_rust_binding<F>(&self, name: &F) -> String
+    where
+        F: Fn(&str) -> String,
+    {
+        match *self {
+            _ if self.is_void() => "()".to_string(),
+            CType::Array(ref ty, _) => match **ty {
+                CType::Const(_) => format!("*const {}", self.to_rust(name)),
+                _ => format!("*mut {}", self.to_rust(name)),
+            },
+            CType::Const(ref ty) => ty.to_rust_binding(name),
+            _ => self.to_rust(name),
+        }
+    }
+}
+
+/// Converts the name of a builtin type, typedef or struct to Rust, as in `CType::to_rust_path`.
+pub fn name_to_rust(name: &str, types: &str, raw: &str) -> String {
+    match builtin(name) {
+        Some(ty) if ty.starts_with("raw::") => format!("{}{}", raw, &ty["raw::".len()..]),
+        Some(ty) => ty.to_string(),
+        None => format!("{}{}", types, name),
+    }
+}
+
+/// The definition of a type from the `<types>` section of a registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TypeDef {
+    /// `typedef <ty> <name>;`
+    Alias(CType),
+    /// `typedef <ret> (*<name>)(<params>);`
+    Function {
+        ret: CType,
+        /// The parameters, which may be unnamed.
+        params: Vec<(Option<String>, CType)>,
+        /// Whether the function uses the system calling convention, for example because it was
+        /// declared with `APIENTRY`.
+        apientry: bool,
+    },
+    /// `struct <name> { <fields> };`
+    Struct(Vec<(String, CType)>),
+    /// `union <name> { <fields> };`
+    Union(Vec<(String, CType)>),
+    /// `struct <name>;`, a struct that is only ever used behind a pointer.
+    Opaque,
+    /// A type that comes from a platform header, such as `Display` from Xlib, or whose
+    /// declaration could not be parsed. The registry only refers to these types.
+    External,
+    /// Rust items that define the type in place of the declaration from the registry, such as
+    /// `pub type GLsizeiptr = usize;`.
+    Rust(String),
+}
+
+impl TypeDef {
+    /// The named types and structs that the definition refers to.
+    pub fn dependencies(&self) -> Vec<&CType> {
+        match *self {
+            TypeDef::Alias(ref ty) => vec![ty.base()],
+            TypeDef::Function {
+                ref ret,
+                ref params,
+                ..
+            } => Some(ret)
+                .into_iter()
+                .chain(params.iter().map(|(_, ty)| ty))
+                .map(CType::base)
+                .collect(),
+            TypeDef::Struct(ref fields) | TypeDef::Union(ref fields) => {
+                fields.iter().map(|(_, ty)| ty.base()).collect()
+            },
+            TypeDef::Opaque | TypeDef::External | TypeDef::Rust(_) => Vec::new(),
+        }
+    }
+}
+
+/// Converts a builtin C type to Rust, returning `None` if the name is not builtin. This covers
+/// the C keywords, `<stdint.h>` and `<KHR/khrplatform.h>`.
+///
+/// Types from `std::os::raw` are returned with a `raw::` prefix, for example `raw::c_uint`.
+pub fn builtin(name: &str) -> Option<&'static str> {
+    let ty = match name {
+        "void" => "raw::c_void",
+        "char" => "raw::c_char",
+        "signed char" => "raw::c_schar",
+        "unsigned char" => "raw::c_uchar",
+        "short" => "raw::c_short",
+        "unsigned short" => "raw::c_ushort",
+        "int" => "raw::c_int",
+        "unsigned int" => "raw::c_uint",
+        "long" => "raw::c_long",
+        "unsigned long" => "raw::c_ulong",
+        "long long" => "raw::c_longlong",
+        "unsigned long long" => "raw::c_ulonglong",
+        "float" => "raw::c_float",
+        "double" => "raw::c_double",
+
+        "int8_t" | "khronos_int8_t" => "i8",
+        "uint8_t" | "khronos_uint8_t" => "u8",
+        "int16_t" | "khronos_int16_t" => "i16",
+        "uint16_t" | "khronos_uint16_t" => "u16",
+        "int32_t" | "khronos_int32_t" => "i32",
+        "uint32_t" | "khronos_uint32_t" => "u32",
+        "int64_t" | "khronos_int64_t" | "khronos_stime_nanoseconds_t" => "i64",
+        "uint64_t" | "khronos_uint64_t" | "khronos_utime_nanoseconds_t" => "u64",
+        "intptr_t" | "ptrdiff_t" | "ssize_t" | "khronos_intptr_t" | "khronos_ssize_t" => "isize",
+        "uintptr_t" | "size_t" | "khronos_uintptr_t" | "khronos_usize_t" => "usize",
+        "khronos_float_t" => "raw::c_float",
+
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// Parses the contents of a `<type>` element, returning the name and definition of the type.
+///
+/// `len_of` is used to look up array lengths that are given by a constant, such as
+/// `GLX_HYPERPIPE_PIPE_NAME_LENGTH_SGIX`.
+pub fn parse_type<F>(src: &str, len_of: &F) -> Result<(String, TypeDef), String>
+where
+    F: Fn(&str) -> Option<usize>,
+{
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        len_of,
+    };
+    let result = parser.type_decl()?;
+    parser.eat(";");
+    match parser.peek() {
+        None => Ok(result),
+        Some(token) => Err(format!("unexpected `{}` after the declaration", token)),
+    }
+}
+
+/// Parses the type of a parameter or return value, without its name, for example
+/// `const GLchar *const*` or `GLuint [2]`.
+pub fn parse_declaration(src: &str) -> Result<CType, String> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        len_of: &|_: &str| None,
+    };
+    let base = parser.base_type()?;
+    let ty = match parser.declarator(base)? {
+        (None, ty) => ty,
+        (Some(name), _) => return Err(format!("unexpected `{}` after the type", name)),
+    };
+    match parser.peek() {
+        None => Ok(ty),
+        Some(token) => Err(format!("unexpected `{}` after the type", token)),
+    }
+}
+
+/// Splits a declaration into identifiers, numbers and punctuation, dropping comments.
+fn tokenize(src: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => last = c,
+                        None => return Err("unterminated comment".to_string()),
+                    }
+                }
+            },
+            '/' if chars.peek() == Some(&'/') => {
+                while let Some(&c) = chars.peek() {
+                    if c == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            },
+            '*' | '(' | ')' | '[' | ']' | '{' | '}' | ',' | ';' => tokens.push(c.to_string()),
+            c if c.is_whitespace() => {},
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut token = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    token.push(c);
+                    chars.next();
+                }
+                tokens.push(token);
+            },
+            c => return Err(format!("unexpected `{}`", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_ident(token: &str) -> bool {
+    match token.chars().next() {
+        Some(c) => c.is_alphabetic() || c == '_',
+        None => false,
+    }
+}
+
+/// Keywords that make up the names of builtin types.
+const BUILTIN_KEYWORDS: &[&str] = &[
+    "void", "char", "short", "int", "long", "float", "double", "signed", "unsigned",
+];
+
+/// Names that can appear in the declarator of a function pointer to select its calling
+/// convention.
+const APIENTRIES: &[&str] = &["APIENTRY", "EGLAPIENTRY", "WINAPI", "CALLBACK"];
+
+struct Parser<'a, F: 'a> {
+    tokens: Vec<String>,
+    pos: usize,
+    len_of: &'a F,
+}
+
+impl<'a, F> Parser<'a, F>
+where
+    F: Fn(&str) -> Option<usize>,
+{
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1).map(String::as_str)
+    }
+
+    /// Consumes the next token if it is `token`.
+    fn eat(&mut self, token: &str) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        match self.next() {
+            Some(found) if found == token => Ok(()),
+            Some(found) => Err(format!("expected `{}`, found `{}`", token, found)),
+            None => Err(format!("expected `{}`, found the end", token)),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(found) if is_ident(found) => Ok(found.to_string()),
+            Some(found) => Err(format!("expected a name, found `{}`", found)),
+            None => Err("expected a name, found the end".to_string()),
+        }
+    }
+
+    fn type_decl(&mut self) -> Result<(String, TypeDef), String> {
+        if self.eat("DECLARE_HANDLE") {
+            // typedef void *name, as `<windows.h>` declares handles without STRICT
+            self.expect("(")?;
+            let name = self.ident()?;
+            self.expect(")")?;
+            let handle = CType::Pointer(Box::new(CType::Named("void".to_string())));
+            return Ok((name, TypeDef::Alias(handle)));
+        }
+
+        if !self.eat("typedef") {
+            let is_union = self.eat("union");
+            if !is_union {
+                self.expect("struct")?;
+            }
+            let name = self.ident()?;
+            if self.peek() != Some("{") {
+                return Ok((name, TypeDef::Opaque));
+            }
+            return Ok((name, self.body(is_union)?));
+        }
+
+        // `typedef struct { ... } name;`, optionally with a tag after `struct`
+        let is_tagged = self.peek() == Some("struct") || self.peek() == Some("union");
+        let lookahead = |offset: usize| self.tokens.get(self.pos + offset).map(String::as_str);
+        if is_tagged && (lookahead(1) == Some("{") || lookahead(2) == Some("{")) {
+            let is_union = self.next() == Some("union");
+            if self.peek() != Some("{") {
+                self.ident()?;
+            }
+            let body = self.body(is_union)?;
+            return Ok((self.ident()?, body));
+        }
+
+        let base = self.base_type()?;
+        let ret = self.pointers(base);
+        if self.eat("(") {
+            let mut apientry = false;
+            while let Some(token) = self.peek() {
+                if !APIENTRIES.contains(&token) {
+                    break;
+                }
+                self.pos += 1;
+                apientry = true;
+            }
+            self.expect("*")?;
+            let name = self.ident()?;
+            self.expect(")")?;
+            let params = self.params()?;
+            return Ok((
+                name,
+                TypeDef::Function {
+                    ret,
+                    params,
+                    apientry,
+                },
+            ));
+        }
+
+        match self.declarator(ret)? {
+            (Some(name), ty) => Ok((name, TypeDef::Alias(ty))),
+            (None, _) => Err("expected a name for the typedef".to_string()),
+        }
+    }
+
+    /// Parses the fields of a struct or union, from `{` to `}`.
+    fn body(&mut self, is_union: bool) -> Result<TypeDef, String> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        while !self.eat("}") {
+            let base = self.base_type()?;
+            loop {
+                match self.declarator(base.clone())? {
+                    (Some(name), ty) => fields.push((underscore_keyword(name), ty)),
+                    (None, _) => return Err("expected a name for the field".to_string()),
+                }
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect(";")?;
+        }
+        Ok(if is_union {
+            TypeDef::Union(fields)
+        } else {
+            TypeDef::Struct(fields)
+        })
+    }
+
+    /// Parses the parameters of a function, from `(` to `)`.
+    fn params(&mut self) -> Result<Vec<(Option<String>, CType)>, String> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        if self.eat(")") {
+            return Ok(params);
+        }
+        if self.peek() == Some("void")
+            && self.tokens.get(self.pos + 1).map(String::as_str) == Some(")")
+        {
+            self.pos += 2;
+            return Ok(params);
+        }
+        loop {
+            let base = self.base_type()?;
+            let (name, ty) = self.declarator(base)?;
+            params.push((name.map(underscore_keyword), ty));
+            if !self.eat(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(params)
+    }
+
+    /// Parses a qualified builtin type, typedef name, or struct tag, for example
+    /// `const unsigned int`.
+    fn base_type(&mut self) -> Result<CType, String> {
+        let mut is_const = false;
+        let mut keywords = Vec::new();
+        let mut base = None;
+        while let Some(token) = self.peek().map(str::to_string) {
+            if token == "const" {
+                is_const = true;
+            } else if token == "struct" || token == "union" {
+                if base.is_some() || !keywords.is_empty() {
+                    break;
+                }
+                self.pos += 1;
+                base = Some(CType::Struct(self.ident()?));
+                continue;
+            } else if BUILTIN_KEYWORDS.contains(&token.as_str()) {
+                if base.is_some() {
+                    break;
+                }
+                keywords.push(token);
+            } else if is_ident(&token) && base.is_none() && keywords.is_empty() {
+                base = Some(CType::Named(token));
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        let base = match base {
+            Some(base) => base,
+            None if !keywords.is_empty() => CType::Named(builtin_name(&keywords)?),
+            None => {
+                return Err(match self.peek() {
+                    Some(token) => format!("expected a type, found `{}`", token),
+                    None => "expected a type, found the end".to_string(),
+                })
+            },
+        };
+        Ok(if is_const {
+            CType::Const(Box::new(base))
+        } else {
+            base
+        })
+    }
+
+    /// Parses any number of `*` and `* const`.
+    fn pointers(&mut self, mut ty: CType) -> CType {
+        while self.eat("*") {
+            ty = CType::Pointer(Box::new(ty));
+            if self.eat("const") {
+                ty = CType::Const(Box::new(ty));
+            }
+        }
+        ty
+    }
+
+    /// Parses the pointers, optional name and array dimensions that follow a base type.
+    fn declarator(&mut self, base: CType) -> Result<(Option<String>, CType), String> {
+        let mut ty = self.pointers(base);
+        let name = match self.peek() {
+            Some(token) if is_ident(token) => Some(token.to_string()),
+            _ => None,
+        };
+        if name.is_some() {
+            self.pos += 1;
+        }
+
+        let mut dims = Vec::new();
+        while self.eat("[") {
+            let len = self.next().map(str::to_string).unwrap_or_default();
+            let len = match len.parse() {
+                Ok(len) => len,
+                Err(_) => match (self.len_of)(&len) {
+                    Some(len) => len,
+                    None => return Err(format!("unknown array length `{}`", len)),
+                },
+            };
+            self.expect("]")?;
+            dims.push(len);
+        }
+        for len in dims.into_iter().rev() {
+            ty = CType::Array(Box::new(ty), len);
+        }
+        Ok((name, ty))
+    }
+}
+
+/// Combines keywords such as `unsigned` and `long` into the name of a builtin type.
+fn builtin_name(keywords: &[String]) -> Result<String, String> {
+    let has = |keyword: &str| keywords.iter().any(|k| k == keyword);
+    let longs = keywords.iter().filter(|k| *k == "long").count();
+    let sign = if has("unsigned") { "unsigned " } else { "" };
+
+    let name = if has("void") || has("float") || has("double") {
+        if keywords.len() != 1 {
+            return Err(format!("unsupported type `{}`", keywords.join(" ")));
+        }
+        keywords[0].clone()
+    } else if has("char") {
+        if has("signed") {
+            "signed char".to_string()
+        } else {
+            format!("{}char", sign)
+        }
+    } else if has("short") {
+        format!("{}short", sign)
+    } else if longs == 2 {
+        format!("{}long long", sign)
+    } else if longs == 1 {
+        format!("{}long", sign)
+    } else {
+        format!("{}int", sign)
+    };
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_declaration, parse_type, CType, TypeDef};
+
+    fn named(name: &str) -> CType {
+        CType::Named(name.to_string())
+    }
+
+    fn ptr(ty: CType) -> CType {
+        CType::Pointer(Box::new(ty))
+    }
+
+    fn konst(ty: CType) -> CType {
+        CType::Const(Box::new(ty))
+    }
+
+    fn no_len(_: &str) -> Option<usize> {
+        None
+    }
+
+    fn ty(src: &str) -> (String, TypeDef) {
+        parse_type(src, &no_len).unwrap()
+    }
+
+    fn rust(src: &str) -> String {
+        match ty(&format!("typedef {} name;", src)) {
+            (_, TypeDef::Alias(ty)) => ty.to_rust(&|name: &str| name.to_string()),
+            (_, def) => panic!("{:?}", def),
+        }
+    }
+
+    #[test]
+    fn test_alias() {
+        assert_eq!(
+            ty("typedef unsigned int GLenum;"),
+            ("GLenum".to_string(), TypeDef::Alias(named("unsigned int")))
+        );
+        assert_eq!(
+            ty("typedef khronos_int8_t GLbyte;"),
+            (
+                "GLbyte".to_string(),
+                TypeDef::Alias(named("khronos_int8_t"))
+            )
+        );
+        assert_eq!(
+            ty("typedef void *GLeglImageOES;"),
+            (
+                "GLeglImageOES".to_string(),
+                TypeDef::Alias(ptr(named("void")))
+            )
+        );
+        assert_eq!(
+            ty("typedef struct __GLsync *GLsync;"),
+            (
+                "GLsync".to_string(),
+                TypeDef::Alias(ptr(CType::Struct("__GLsync".to_string())))
+            )
+        );
+    }
+
+    #[test]
+    fn test_builtin_names() {
+        assert_eq!(rust("unsigned"), "unsigned int");
+        assert_eq!(rust("unsigned long int"), "unsigned long");
+        assert_eq!(rust("long long"), "long long");
+        assert_eq!(rust("signed char"), "signed char");
+        assert_eq!(rust("short int"), "short");
+    }
+
+    #[test]
+    fn test_opaque() {
+        assert_eq!(
+            ty("struct _cl_context;"),
+            ("_cl_context".to_string(), TypeDef::Opaque)
+        );
+        assert_eq!(
+            ty("DECLARE_HANDLE(HPBUFFERARB);"),
+            (
+                "HPBUFFERARB".to_string(),
+                TypeDef::Alias(ptr(CType::Named("void".to_string())))
+            )
+        );
+    }
+
+    #[test]
+    fn test_function() {
+        let (name, def) = ty(
+            "typedef void (APIENTRY *GLDEBUGPROC)(GLenum source,GLenum type,\
+                              const GLchar *message,const void *userParam);",
+        );
+        assert_eq!(name, "GLDEBUGPROC");
+        assert_eq!(
+            def,
+            TypeDef::Function {
+                ret: named("void"),
+                params: vec![
+                    (Some("source".to_string()), named("GLenum")),
+                    (Some("type_".to_string()), named("GLenum")),
+                    (Some("message".to_string()), ptr(konst(named("GLchar")))),
+                    (Some("userParam".to_string()), ptr(konst(named("void")))),
+                ],
+                apientry: true,
+            }
+        );
+
+        let (_, def) = ty("typedef void ( *__GLXextFuncPtr)(void);");
+        assert_eq!(
+            def,
+            TypeDef::Function {
+                ret: named("void"),
+                params: vec![],
+                apientry: false,
+            }
+        );
+
+        let (_, def) = ty("typedef EGLsizeiANDROID (*EGLGetBlobFuncANDROID) \
+                           (const void *key, EGLsizeiANDROID keySize, void *, EGLsizeiANDROID);");
+        match def {
+            TypeDef::Function { ret, params, .. } => {
+                assert_eq!(ret, named("EGLsizeiANDROID"));
+                assert_eq!(params[2], (None, ptr(named("void"))));
+            },
+            def => panic!("{:?}", def),
+        }
+    }
+
+    #[test]
+    fn test_struct() {
+        let src = "typedef struct {
+            int event_type;             /* GLX_DAMAGED or GLX_SAVED */
+            Display *display;           /* display the event was read from */
+            int x, y;
+            char pipeName[GLX_HYPERPIPE_PIPE_NAME_LENGTH_SGIX];
+        } GLXPbufferClobberEvent;";
+        let len_of = |name: &str| match name {
+            "GLX_HYPERPIPE_PIPE_NAME_LENGTH_SGIX" => Some(80),
+            _ => None,
+        };
+        assert_eq!(
+            parse_type(src, &len_of).unwrap(),
+            (
+                "GLXPbufferClobberEvent".to_string(),
+                TypeDef::Struct(vec![
+                    ("event_type".to_string(), named("int")),
+                    ("display".to_string(), ptr(named("Display"))),
+                    ("x".to_string(), named("int")),
+                    ("y".to_string(), named("int")),
+                    (
+                        "pipeName".to_string(),
+                        CType::Array(Box::new(named("char")), 80)
+                    ),
+                ])
+            )
+        );
+
+        let (name, def) = ty("struct EGLClientPixmapHI { void *pData; EGLint iWidth; };");
+        assert_eq!(name, "EGLClientPixmapHI");
+        assert_eq!(
+            def,
+            TypeDef::Struct(vec![
+                ("pData".to_string(), ptr(named("void"))),
+                ("iWidth".to_string(), named("EGLint")),
+            ])
+        );
+
+        let (name, def) = ty(
+            "typedef union __GLXEvent { GLXPbufferClobberEvent glxpbufferclobber; \
+                              long pad[24]; } GLXEvent;",
+        );
+        assert_eq!(name, "GLXEvent");
+        assert_eq!(
+            def,
+            TypeDef::Union(vec![
+                (
+                    "glxpbufferclobber".to_string(),
+                    named("GLXPbufferClobberEvent")
+                ),
+                ("pad".to_string(), CType::Array(Box::new(named("long")), 24)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_to_rust() {
+        assert_eq!(rust("GLenum"), "GLenum");
+        assert_eq!(
+            ty("typedef GLuint name[2];").1,
+            TypeDef::Alias(CType::Array(Box::new(named("GLuint")), 2))
+        );
+        assert_eq!(rust("const GLfloat"), "GLfloat");
+        assert_eq!(rust("GLuint *"), "*mut GLuint");
+        assert_eq!(rust("const GLuint *"), "*const GLuint");
+        assert_eq!(rust("GLuint const *"), "*const GLuint");
+        assert_eq!(rust("void **"), "*mut *mut void");
+        assert_eq!(rust("const void **"), "*mut *const void");
+        assert_eq!(rust("const GLchar *const*"), "*const *const GLchar");
+        assert_eq!(rust("struct _cl_event *"), "*mut _cl_event");
+    }
+
+    #[test]
+    fn test_declaration() {
+        assert_eq!(parse_declaration("GLenum"), Ok(named("GLenum")));
+        assert_eq!(
+            parse_declaration("const GLchar *const*"),
+            Ok(ptr(konst(ptr(konst(named("GLchar"))))))
+        );
+        assert_eq!(
+            parse_declaration("const struct AHardwareBuffer *"),
+            Ok(ptr(konst(CType::Struct("AHardwareBuffer".to_string()))))
+        );
+        assert!(parse_declaration("GLenum foo").is_err());
+        assert!(parse_declaration("GLenum )").is_err());
+    }
+
+    #[test]
+    fn test_to_rust_binding() {
+        let binding = |src: &str| {
+            parse_declaration(src)
+                .unwrap()
+                .to_rust_path("types::", "raw::")
+        };
+        let binding_ty = |src: &str| {
+            parse_declaration(src)
+                .unwrap()
+                .to_rust_binding(&|name: &str| super::name_to_rust(name, "types::", "raw::"))
+        };
+        assert_eq!(binding("unsigned long *"), "*mut raw::c_ulong");
+        assert_eq!(binding("int64_t"), "i64");
+        assert_eq!(binding("const GLXContext"), "types::GLXContext");
+        assert_eq!(binding_ty("void"), "()");
+        assert_eq!(binding_ty("const GLXContext"), "types::GLXContext");
+        assert_eq!(binding_ty("GLuint [2]"), "*mut [types::GLuint; 2]");
+        assert_eq!(binding_ty("const GLuint [2]"), "*const [types::GLuint; 2]");
+        assert_eq!(binding_ty("void *"), "*mut raw::c_void");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(parse_type("#ifdef __APPLE__", &no_len).is_err());
+        assert!(parse_type("typedef int;", &no_len).is_err());
+        assert!(parse_type("typedef char name[LENGTH];", &no_len).is_err());
+        assert!(parse_type("typedef GLuint *name);", &no_len).is_err());
+    }
+}
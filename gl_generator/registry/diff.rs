@@ -0,0 +1,458 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use registry::merge::{same_signature, same_value, signature};
+use registry::{Cmd, Enum, Group, Registry};
+
+/// The items of one kind that were added, removed or changed between two registries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Changes<T> {
+    /// The items that are only in the new registry.
+    pub added: Vec<T>,
+    /// The items that are only in the old registry.
+    pub removed: Vec<T>,
+    /// The items that are in both registries, but are defined differently, as `(old, new)`.
+    pub changed: Vec<(T, T)>,
+}
+
+impl<T> Changes<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Compares the items of two registries by name.
+    fn between<'a, I, K, S>(old: I, new: I, key: K, same: S) -> Changes<T>
+    where
+        T: Clone + 'a,
+        I: IntoIterator<Item = &'a T>,
+        K: Fn(&T) -> &str,
+        S: Fn(&T, &T) -> bool,
+    {
+        let old: BTreeMap<_, _> = old.into_iter().map(|item| (key(item), item)).collect();
+        let new: BTreeMap<_, _> = new.into_iter().map(|item| (key(item), item)).collect();
+
+        let mut changes = Changes {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for (name, &new_item) in &new {
+            match old.get(name) {
+                None => changes.added.push(new_item.clone()),
+                Some(&old_item) if !same(old_item, new_item) => {
+                    changes.changed.push((old_item.clone(), new_item.clone()))
+                },
+                Some(_) => {},
+            }
+        }
+        for (name, &old_item) in &old {
+            if !new.contains_key(name) {
+                changes.removed.push(old_item.clone());
+            }
+        }
+        changes
+    }
+}
+
+/// The differences between two registries, from `Registry::diff`.
+///
+/// Commands and enums are compared by name, and are only changed if their types or values
+/// differ, so the versions and extensions that require them are ignored. The `Display`
+/// implementation writes a report for people to read, and `to_tsv` writes one line per
+/// difference for other tools.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RegistryDiff {
+    pub cmds: Changes<Cmd>,
+    pub enums: Changes<Enum>,
+    pub groups: Changes<Group>,
+    /// The aliases of each command, by the name of the command.
+    pub aliases: Changes<(String, Vec<String>)>,
+}
+
+impl RegistryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+            && self.enums.is_empty()
+            && self.groups.is_empty()
+            && self.aliases.is_empty()
+    }
+
+    /// Lists the differences as tab-separated values, with one line per difference. Each line
+    /// has the kind of change (`added`, `removed` or `changed`), the kind of item (`command`,
+    /// `enum`, `group` or `alias`), the name of the item, and its old and new definitions, such
+    /// as the signature of a command. The old definition is empty for added items, and the new
+    /// one for removed items.
+    pub fn to_tsv(&self) -> String {
+        let mut tsv = String::new();
+        write_tsv(
+            &mut tsv,
+            "command",
+            &self.cmds,
+            |cmd| &cmd.proto.ident,
+            signature,
+        );
+        write_tsv(&mut tsv, "enum", &self.enums, |enm| &enm.ident, enum_value);
+        write_tsv(
+            &mut tsv,
+            "group",
+            &self.groups,
+            |group| &group.ident,
+            group_enums,
+        );
+        write_tsv(
+            &mut tsv,
+            "alias",
+            &self.aliases,
+            |alias| &alias.0,
+            |alias| alias.1.join(", "),
+        );
+        tsv
+    }
+}
+
+fn enum_value(enm: &Enum) -> String {
+    format!("{} = {}", enm.ty, enm.value)
+}
+
+fn group_enums(group: &Group) -> String {
+    match group.enums_type {
+        Some(ref kind) => format!("({}) {}", kind, group.enums.join(", ")),
+        None => group.enums.join(", "),
+    }
+}
+
+fn write_tsv<T, F, V>(tsv: &mut String, kind: &str, changes: &Changes<T>, name: F, value: V)
+where
+    F: Fn(&T) -> &str,
+    V: Fn(&T) -> String,
+{
+    for item in &changes.added {
+        tsv.push_str(&format!(
+            "added\t{}\t{}\t\t{}\n",
+            kind,
+            name(item),
+            value(item)
+        ));
+    }
+    for item in &changes.removed {
+        tsv.push_str(&format!(
+            "removed\t{}\t{}\t{}\t\n",
+            kind,
+            name(item),
+            value(item)
+        ));
+    }
+    for (old, new) in &changes.changed {
+        tsv.push_str(&format!(
+            "changed\t{}\t{}\t{}\t{}\n",
+            kind,
+            name(new),
+            value(old),
+            value(new)
+        ));
+    }
+}
+
+fn write_changes<T, F>(
+    fmt: &mut fmt::Formatter,
+    title: &str,
+    changes: &Changes<T>,
+    describe: F,
+) -> fmt::Result
+where
+    F: Fn(&T) -> String,
+{
+    if changes.is_empty() {
+        return Ok(());
+    }
+    writeln!(fmt, "{}:", title)?;
+    for item in &changes.added {
+        writeln!(fmt, "  + {}", describe(item))?;
+    }
+    for item in &changes.removed {
+        writeln!(fmt, "  - {}", describe(item))?;
+    }
+    for (old, new) in &changes.changed {
+        writeln!(fmt, "  ~ {}", describe(old))?;
+        writeln!(fmt, "    {}", describe(new))?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for RegistryDiff {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(fmt, "no differences");
+        }
+        write_changes(fmt, "commands", &self.cmds, |cmd| {
+            format!("{}: {}", cmd.proto.ident, signature(cmd))
+        })?;
+        write_changes(fmt, "enums", &self.enums, |enm| {
+            format!("{}: {}", enm.ident, enum_value(enm))
+        })?;
+        write_changes(fmt, "groups", &self.groups, |group| {
+            let kind = match group.enums_type {
+                Some(ref kind) => format!(" ({})", kind),
+                None => String::new(),
+            };
+            format!("{}{}: {}", group.ident, kind, group.enums.join(", "))
+        })?;
+        write_changes(fmt, "aliases", &self.aliases, |(name, aliases)| {
+            format!("{}: {}", name, aliases.join(", "))
+        })
+    }
+}
+
+impl Registry {
+    /// Compares this registry with a `new` one, which may have been built with a different
+    /// `Filter` or from different XML.
+    pub fn diff(&self, new: &Registry) -> RegistryDiff {
+        let aliases = |registry: &Registry| -> Vec<(String, Vec<String>)> {
+            registry
+                .aliases
+                .iter()
+                .map(|(name, aliases)| (name.clone(), aliases.clone()))
+                .collect()
+        };
+        let (old_aliases, new_aliases) = (aliases(self), aliases(new));
+
+        RegistryDiff {
+            cmds: Changes::between(
+                self.cmds.iter().collect::<Vec<_>>(),
+                new.cmds.iter().collect(),
+                |cmd| &cmd.proto.ident,
+                same_signature,
+            ),
+            enums: Changes::between(
+                self.enums.iter().collect::<Vec<_>>(),
+                new.enums.iter().collect(),
+                |enm| &enm.ident,
+                same_value,
+            ),
+            groups: Changes::between(
+                self.groups.values().collect::<Vec<_>>(),
+                new.groups.values().collect(),
+                |group| &group.ident,
+                |old, new| {
+                    let mut old_enums = old.enums.clone();
+                    let mut new_enums = new.enums.clone();
+                    old_enums.sort();
+                    new_enums.sort();
+                    old.enums_type == new.enums_type && old_enums == new_enums
+                },
+            ),
+            aliases: Changes::between(
+                &old_aliases,
+                &new_aliases,
+                |alias| &alias.0,
+                |old, new| {
+                    let mut old_aliases = old.1.clone();
+                    let mut new_aliases = new.1.clone();
+                    old_aliases.sort();
+                    new_aliases.sort();
+                    old_aliases == new_aliases
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use registry::{Cmd, Enum, Filter, Registry};
+    use {Api, Fallbacks, Profile};
+
+    const OLD: &str = r#"
+        <registry>
+            <types>
+                <type>typedef unsigned int <name>GLenum</name>;</type>
+                <type>typedef int <name>GLint</name>;</type>
+            </types>
+            <groups>
+                <group name="TextureTarget">
+                    <enum name="GL_TEXTURE_2D"/>
+                </group>
+            </groups>
+            <enums namespace="GL">
+                <enum value="0x0DE1" name="GL_TEXTURE_2D"/>
+                <enum value="0x0001" name="GL_FOO"/>
+                <enum value="0x0002" name="GL_BAR"/>
+            </enums>
+            <commands namespace="GL">
+                <command>
+                    <proto>void <name>glFoo</name></proto>
+                    <param><ptype>GLenum</ptype> <name>target</name></param>
+                </command>
+                <command>
+                    <proto>void <name>glBar</name></proto>
+                </command>
+                <command>
+                    <proto>void <name>glBaz</name></proto>
+                    <param><ptype>GLint</ptype> <name>value</name></param>
+                </command>
+            </commands>
+            <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                <require>
+                    <enum name="GL_TEXTURE_2D"/>
+                    <enum name="GL_FOO"/>
+                    <command name="glFoo"/>
+                    <command name="glBar"/>
+                </require>
+            </feature>
+            <feature api="gl" name="GL_VERSION_1_1" number="1.1">
+                <require>
+                    <enum name="GL_BAR"/>
+                    <command name="glBaz"/>
+                </require>
+            </feature>
+        </registry>
+    "#;
+
+    /// The old registry with `glBar` and `GL_BAR` removed, `glQux` and `GL_QUX` added,
+    /// `glFoo` and `GL_FOO` changed, and another enum in `TextureTarget`.
+    const NEW: &str = r#"
+        <registry>
+            <types>
+                <type>typedef unsigned int <name>GLenum</name>;</type>
+                <type>typedef int <name>GLint</name>;</type>
+            </types>
+            <groups>
+                <group name="TextureTarget">
+                    <enum name="GL_TEXTURE_2D"/>
+                    <enum name="GL_QUX"/>
+                </group>
+            </groups>
+            <enums namespace="GL">
+                <enum value="0x0DE1" name="GL_TEXTURE_2D"/>
+                <enum value="0x0003" name="GL_FOO"/>
+                <enum value="0x0004" name="GL_QUX"/>
+            </enums>
+            <commands namespace="GL">
+                <command>
+                    <proto>void <name>glFoo</name></proto>
+                    <param><ptype>GLint</ptype> <name>target</name></param>
+                </command>
+                <command>
+                    <proto>void <name>glQux</name></proto>
+                </command>
+                <command>
+                    <proto>void <name>glBaz</name></proto>
+                    <param><ptype>GLint</ptype> <name>value</name></param>
+                </command>
+            </commands>
+            <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                <require>
+                    <enum name="GL_TEXTURE_2D"/>
+                    <enum name="GL_FOO"/>
+                    <enum name="GL_QUX"/>
+                    <command name="glFoo"/>
+                    <command name="glQux"/>
+                    <command name="glBaz"/>
+                </require>
+            </feature>
+        </registry>
+    "#;
+
+    fn registry(src: &str, version: (u8, u8)) -> Registry {
+        let filter = Filter::new(Api::Gl, version, Profile::Core, Fallbacks::All, []);
+        Registry::from_sources(&filter, vec![src.as_bytes()]).unwrap()
+    }
+
+    fn cmds(cmds: &[Cmd]) -> Vec<&str> {
+        cmds.iter().map(|cmd| cmd.proto.ident.as_str()).collect()
+    }
+
+    fn enums(enums: &[Enum]) -> Vec<&str> {
+        enums.iter().map(|enm| enm.ident.as_str()).collect()
+    }
+
+    #[test]
+    fn test_same() {
+        let diff = registry(OLD, (1, 1)).diff(&registry(OLD, (1, 1)));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no differences\n");
+        assert_eq!(diff.to_tsv(), "");
+    }
+
+    #[test]
+    fn test_sources() {
+        let diff = registry(OLD, (1, 1)).diff(&registry(NEW, (1, 0)));
+        assert_eq!(cmds(&diff.cmds.added), ["Qux"]);
+        assert_eq!(cmds(&diff.cmds.removed), ["Bar"]);
+        assert_eq!(diff.cmds.changed.len(), 1);
+        assert_eq!(diff.cmds.changed[0].0.proto.ident, "Foo");
+        assert_eq!(diff.cmds.changed[0].1.params[0].ty, "types::GLint");
+
+        // `glBaz` moved to an earlier version, which is not a change
+        assert_eq!(enums(&diff.enums.added), ["QUX"]);
+        assert_eq!(enums(&diff.enums.removed), ["BAR"]);
+        assert_eq!(diff.enums.changed.len(), 1);
+        assert_eq!(diff.enums.changed[0].1.value, "0x0003");
+
+        assert!(diff.groups.added.is_empty() && diff.groups.removed.is_empty());
+        assert_eq!(diff.groups.changed[0].1.enums, ["TEXTURE_2D", "QUX"]);
+
+        assert_eq!(
+            diff.to_tsv(),
+            "added\tcommand\tQux\t\tfn() -> ()\n\
+             removed\tcommand\tBar\tfn() -> ()\t\n\
+             changed\tcommand\tFoo\tfn(types::GLenum) -> ()\tfn(types::GLint) -> ()\n\
+             added\tenum\tQUX\t\tGLenum = 0x0004\n\
+             removed\tenum\tBAR\tGLenum = 0x0002\t\n\
+             changed\tenum\tFOO\tGLenum = 0x0001\tGLenum = 0x0003\n\
+             changed\tgroup\tTextureTarget\tTEXTURE_2D\tTEXTURE_2D, QUX\n"
+        );
+
+        let report = diff.to_string();
+        assert!(report.contains("commands:\n  + Qux: fn() -> ()\n  - Bar: fn() -> ()\n"));
+        assert!(
+            report.contains("  ~ Foo: fn(types::GLenum) -> ()\n    Foo: fn(types::GLint) -> ()\n")
+        );
+        assert!(report.contains("  ~ FOO: GLenum = 0x0001\n    FOO: GLenum = 0x0003\n"));
+        assert!(!report.contains("aliases:"));
+    }
+
+    #[test]
+    fn test_filters() {
+        let diff = registry(OLD, (1, 0)).diff(&registry(OLD, (1, 1)));
+
+        assert_eq!(diff.cmds.added.len(), 1);
+        assert_eq!(diff.cmds.added[0].proto.ident, "Baz");
+        assert_eq!(diff.enums.added.len(), 1);
+        assert_eq!(diff.enums.added[0].ident, "BAR");
+        assert!(diff.cmds.removed.is_empty() && diff.cmds.changed.is_empty());
+        assert!(diff.enums.removed.is_empty() && diff.enums.changed.is_empty());
+        assert!(diff.groups.is_empty() && diff.aliases.is_empty());
+
+        let reverse = registry(OLD, (1, 1)).diff(&registry(OLD, (1, 0)));
+        assert_eq!(reverse.cmds.removed, diff.cmds.added);
+        assert_eq!(reverse.enums.removed, diff.enums.added);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_json() {
+        use registry::serde_json;
+        use registry::RegistryDiff;
+
+        let diff = registry(OLD, (1, 1)).diff(&registry(NEW, (1, 0)));
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(serde_json::from_str::<RegistryDiff>(&json).unwrap(), diff);
+    }
+}
@@ -0,0 +1,527 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+use std::ops::{Add, AddAssign};
+
+use registry::{Api, Cmd, Enum, Group, Profile, Registry, RegistryError, TypeDef};
+
+/// How `Registry::merge` resolves the things that the two registries define differently.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keeps the definitions from the registry that is merged into.
+    PreferLeft,
+    /// Uses the definitions from the registry that is merged in.
+    PreferRight,
+    /// Fails with `RegistryError::MergeConflicts`, leaving the registry unchanged.
+    Error,
+}
+
+/// Something that is defined differently by the two registries given to `Registry::merge`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// The registries were built for different APIs.
+    Api { left: Api, right: Api },
+    /// A command has different return or parameter types, or is removed from different profiles.
+    Cmd { left: Box<Cmd>, right: Box<Cmd> },
+    /// An enum has a different value or type, or is removed from different profiles.
+    Enum { left: Enum, right: Enum },
+    /// A group is a bitmask in one registry, but not in the other.
+    Group { left: Group, right: Group },
+    /// A type has different definitions.
+    Type {
+        name: String,
+        left: TypeDef,
+        right: TypeDef,
+    },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Conflict::Api { left, right } => write!(
+                fmt,
+                "the registries are for different APIs (`{:?}` and `{:?}`)",
+                left, right
+            ),
+            Conflict::Cmd {
+                ref left,
+                ref right,
+            } if same_signature(left, right) => write!(
+                fmt,
+                "command `{}` is removed from {} and from {}",
+                left.proto.ident,
+                removed_from(left.removed_in_profile),
+                removed_from(right.removed_in_profile)
+            ),
+            Conflict::Cmd {
+                ref left,
+                ref right,
+            } => write!(
+                fmt,
+                "command `{}` is declared as `{}` and as `{}`",
+                left.proto.ident,
+                signature(left),
+                signature(right)
+            ),
+            Conflict::Enum {
+                ref left,
+                ref right,
+            } if same_value(left, right) => write!(
+                fmt,
+                "enum `{}` is removed from {} and from {}",
+                left.ident,
+                removed_from(left.removed_in_profile),
+                removed_from(right.removed_in_profile)
+            ),
+            Conflict::Enum {
+                ref left,
+                ref right,
+            } => write!(
+                fmt,
+                "enum `{}` is defined as `{}: {}` and as `{}: {}`",
+                left.ident, left.value, left.ty, right.value, right.ty
+            ),
+            Conflict::Group {
+                ref left,
+                ref right,
+            } => write!(
+                fmt,
+                "group `{}` has the types `{}` and `{}`",
+                left.ident,
+                left.enums_type.as_deref().unwrap_or("enum"),
+                right.enums_type.as_deref().unwrap_or("enum")
+            ),
+            Conflict::Type {
+                ref name,
+                ref left,
+                ref right,
+            } => write!(
+                fmt,
+                "type `{}` is defined as `{:?}` and as `{:?}`",
+                name, left, right
+            ),
+        }
+    }
+}
+
+pub fn signature(cmd: &Cmd) -> String {
+    let params: Vec<_> = cmd.params.iter().map(|param| param.ty.as_ref()).collect();
+    format!("fn({}) -> {}", params.join(", "), cmd.proto.ty)
+}
+
+pub fn same_signature(left: &Cmd, right: &Cmd) -> bool {
+    left.proto.c_ty == right.proto.c_ty
+        && left.params.len() == right.params.len()
+        && left
+            .params
+            .iter()
+            .zip(&right.params)
+            .all(|(left, right)| left.c_ty == right.c_ty)
+}
+
+pub fn same_value(left: &Enum, right: &Enum) -> bool {
+    left.value == right.value && left.ty == right.ty && left.cast == right.cast
+}
+
+fn removed_from(profile: Option<Profile>) -> String {
+    match profile {
+        Some(profile) => format!("the `{:?}` profile", profile),
+        None => "no profile".to_string(),
+    }
+}
+
+/// The earlier of two versions, where `None` means that only extensions require the item.
+fn earliest(left: Option<(u8, u8)>, right: Option<(u8, u8)>) -> Option<(u8, u8)> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(cmp::min(left, right)),
+        (left, right) => left.or(right),
+    }
+}
+
+fn extend_unique(names: &mut Vec<String>, other: Vec<String>) {
+    for name in other {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+}
+
+impl Registry {
+    /// Merges the commands, enums, aliases, groups, extensions and types of `other` into this
+    /// registry.
+    ///
+    /// Commands and enums are matched by name, and a command or enum that both registries define
+    /// in the same way is only included once, with the versions and extensions that require it
+    /// combined. Anything that the registries define differently is a `Conflict`, which is
+    /// resolved by `policy`. The conflicts that were resolved are returned.
+    pub fn merge(
+        &mut self,
+        other: Registry,
+        policy: MergePolicy,
+    ) -> Result<Vec<Conflict>, RegistryError> {
+        match policy {
+            MergePolicy::PreferLeft => Ok(self.merge_with(other, false)),
+            MergePolicy::PreferRight => Ok(self.merge_with(other, true)),
+            MergePolicy::Error => {
+                let mut merged = self.clone();
+                let conflicts = merged.merge_with(other, false);
+                if !conflicts.is_empty() {
+                    return Err(RegistryError::MergeConflicts(conflicts));
+                }
+                *self = merged;
+                Ok(conflicts)
+            },
+        }
+    }
+
+    fn merge_with(&mut self, other: Registry, prefer_right: bool) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+
+        if self.api != other.api {
+            conflicts.push(Conflict::Api {
+                left: self.api,
+                right: other.api,
+            });
+            if prefer_right {
+                self.api = other.api;
+            }
+        }
+        self.version = cmp::max(self.version, other.version);
+        self.min_version = cmp::min(self.min_version, other.min_version);
+
+        let mut cmds: BTreeMap<_, _> = mem::take(&mut self.cmds)
+            .into_iter()
+            .map(|cmd| (cmd.proto.ident.clone(), cmd))
+            .collect();
+        for cmd in other.cmds {
+            match cmds.entry(cmd.proto.ident.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(cmd);
+                },
+                Entry::Occupied(ref mut entry)
+                    if same_signature(entry.get(), &cmd)
+                        && entry.get().removed_in_profile == cmd.removed_in_profile =>
+                {
+                    let left = entry.get_mut();
+                    left.introduced_in = earliest(left.introduced_in, cmd.introduced_in);
+                    extend_unique(&mut left.required_by_extensions, cmd.required_by_extensions);
+                },
+                Entry::Occupied(mut entry) => {
+                    conflicts.push(Conflict::Cmd {
+                        left: Box::new(entry.get().clone()),
+                        right: Box::new(cmd.clone()),
+                    });
+                    if prefer_right {
+                        entry.insert(cmd);
+                    }
+                },
+            }
+        }
+        self.cmds = cmds.into_values().collect();
+
+        let mut enums: BTreeMap<_, _> = mem::take(&mut self.enums)
+            .into_iter()
+            .map(|enm| (enm.ident.clone(), enm))
+            .collect();
+        for enm in other.enums {
+            match enums.entry(enm.ident.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(enm);
+                },
+                Entry::Occupied(ref mut entry)
+                    if same_value(entry.get(), &enm)
+                        && entry.get().removed_in_profile == enm.removed_in_profile =>
+                {
+                    let left = entry.get_mut();
+                    left.introduced_in = earliest(left.introduced_in, enm.introduced_in);
+                    extend_unique(&mut left.required_by_extensions, enm.required_by_extensions);
+                },
+                Entry::Occupied(mut entry) => {
+                    conflicts.push(Conflict::Enum {
+                        left: entry.get().clone(),
+                        right: enm.clone(),
+                    });
+                    if prefer_right {
+                        entry.insert(enm);
+                    }
+                },
+            }
+        }
+        self.enums = enums.into_values().collect();
+
+        for (name, aliases) in other.aliases {
+            extend_unique(self.aliases.entry(name).or_default(), aliases);
+        }
+
+        for (name, group) in other.groups {
+            match self.groups.entry(name) {
+                Entry::Vacant(entry) => {
+                    entry.insert(group);
+                },
+                Entry::Occupied(mut entry) => {
+                    let left = entry.get_mut();
+                    if left.enums_type != group.enums_type {
+                        if left.enums_type.is_some() && group.enums_type.is_some() {
+                            conflicts.push(Conflict::Group {
+                                left: left.clone(),
+                                right: group.clone(),
+                            });
+                            if prefer_right {
+                                left.enums_type = group.enums_type.clone();
+                            }
+                        } else {
+                            left.enums_type = left.enums_type.take().or(group.enums_type.clone());
+                        }
+                    }
+                    extend_unique(&mut left.enums, group.enums);
+                },
+            }
+        }
+
+        self.extensions.extend(other.extensions);
+
+        for (name, def) in other.types {
+            match self.types.entry(name) {
+                Entry::Vacant(entry) => {
+                    entry.insert(def);
+                },
+                Entry::Occupied(mut entry) => {
+                    let is_placeholder =
+                        |def: &TypeDef| matches!(*def, TypeDef::External | TypeDef::Opaque);
+                    if *entry.get() == def || is_placeholder(&def) {
+                        continue;
+                    }
+                    if is_placeholder(entry.get()) {
+                        entry.insert(def);
+                        continue;
+                    }
+                    conflicts.push(Conflict::Type {
+                        name: entry.key().clone(),
+                        left: entry.get().clone(),
+                        right: def.clone(),
+                    });
+                    if prefer_right {
+                        entry.insert(def);
+                    }
+                },
+            }
+        }
+
+        conflicts
+    }
+}
+
+impl Add for Registry {
+    type Output = Registry;
+
+    fn add(mut self, other: Registry) -> Registry {
+        self += other;
+        self
+    }
+}
+
+/// Merges the registries with `MergePolicy::PreferLeft`, ignoring any conflicts.
+impl AddAssign for Registry {
+    fn add_assign(&mut self, other: Self) {
+        self.merge_with(other, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use registry::{Conflict, Filter, MergePolicy, Registry, RegistryError, TypeDef};
+    use {Api, Fallbacks, Profile};
+
+    const LEFT: &str = r#"
+        <registry>
+            <types>
+                <type>typedef unsigned int <name>GLenum</name>;</type>
+            </types>
+            <groups>
+                <group name="TextureTarget">
+                    <enum name="GL_TEXTURE_2D"/>
+                </group>
+            </groups>
+            <enums namespace="GL">
+                <enum value="0x0DE1" name="GL_TEXTURE_2D"/>
+                <enum value="0x0001" name="GL_FOO"/>
+            </enums>
+            <commands namespace="GL">
+                <command>
+                    <proto>void <name>glFoo</name></proto>
+                    <param><ptype>GLenum</ptype> <name>target</name></param>
+                </command>
+            </commands>
+            <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                <require>
+                    <enum name="GL_TEXTURE_2D"/>
+                    <enum name="GL_FOO"/>
+                    <command name="glFoo"/>
+                </require>
+            </feature>
+        </registry>
+    "#;
+
+    fn registry(src: &str) -> Registry {
+        let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+        Registry::from_sources(&filter, vec![src.as_bytes()]).unwrap()
+    }
+
+    /// The left registry with `glFoo` and `GL_FOO` defined differently, and another group.
+    fn right() -> Registry {
+        let src = LEFT
+            .replace(
+                "<ptype>GLenum</ptype> <name>target",
+                "<ptype>GLint</ptype> <name>target",
+            )
+            .replace(r#"value="0x0001""#, r#"value="0x0002""#)
+            .replace("TextureTarget", "BindTarget");
+        registry(&src)
+    }
+
+    #[test]
+    fn test_identical() {
+        let mut merged = registry(LEFT);
+        let conflicts = merged.merge(registry(LEFT), MergePolicy::Error).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, registry(LEFT));
+    }
+
+    #[test]
+    fn test_prefer_left() {
+        let mut merged = registry(LEFT);
+        let conflicts = merged.merge(right(), MergePolicy::PreferLeft).unwrap();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(merged.cmds.len(), 1);
+        assert_eq!(
+            merged.cmds.iter().next().unwrap().params[0].ty,
+            "types::GLenum"
+        );
+        let foo = merged.enums.iter().find(|enm| enm.ident == "FOO").unwrap();
+        assert_eq!(foo.value, "0x0001");
+        assert_eq!(merged.types["GLint"], TypeDef::External);
+    }
+
+    #[test]
+    fn test_prefer_right() {
+        let mut merged = registry(LEFT);
+        let conflicts = merged.merge(right(), MergePolicy::PreferRight).unwrap();
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(
+            merged.cmds.iter().next().unwrap().params[0].ty,
+            "types::GLint"
+        );
+        let foo = merged.enums.iter().find(|enm| enm.ident == "FOO").unwrap();
+        assert_eq!(foo.value, "0x0002");
+    }
+
+    #[test]
+    fn test_error() {
+        let mut merged = registry(LEFT);
+        match merged.merge(right(), MergePolicy::Error) {
+            Err(RegistryError::MergeConflicts(conflicts)) => {
+                match conflicts[0] {
+                    Conflict::Cmd { ref left, .. } => assert_eq!(left.proto.ident, "Foo"),
+                    ref conflict => panic!("expected a command conflict, found {:?}", conflict),
+                }
+                assert_eq!(
+                    conflicts[1].to_string(),
+                    "enum `FOO` is defined as `0x0001: GLenum` and as `0x0002: GLenum`"
+                );
+            },
+            result => panic!("expected conflicts, found {:?}", result),
+        }
+        assert_eq!(merged, registry(LEFT));
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut merged = registry(LEFT);
+        merged += right();
+        assert_eq!(merged.groups["TextureTarget"].enums, ["TEXTURE_2D"]);
+        assert_eq!(merged.groups["BindTarget"].enums, ["TEXTURE_2D"]);
+    }
+
+    #[test]
+    fn test_api() {
+        let mut merged = registry(LEFT);
+        let mut other = registry(LEFT);
+        other.api = Api::Gles2;
+        let conflicts = merged.merge(other, MergePolicy::PreferRight).unwrap();
+        assert_eq!(
+            conflicts,
+            [Conflict::Api {
+                left: Api::Gl,
+                right: Api::Gles2,
+            }]
+        );
+        assert_eq!(merged.api, Api::Gles2);
+    }
+
+    #[test]
+    fn test_provenance() {
+        let mut merged = registry(LEFT);
+        let mut other = registry(LEFT);
+        other.cmds = other
+            .cmds
+            .into_iter()
+            .map(|mut cmd| {
+                cmd.required_by_extensions = vec!["GL_ACME_foo".to_string()];
+                cmd
+            })
+            .collect();
+        merged += other;
+        assert_eq!(merged.cmds.len(), 1);
+        let cmd = merged.cmds.iter().next().unwrap();
+        assert_eq!(cmd.introduced_in, Some((1, 0)));
+        assert_eq!(cmd.required_by_extensions, ["GL_ACME_foo"]);
+    }
+
+    #[test]
+    fn test_removed_in_profile() {
+        let mut merged = registry(LEFT);
+        let mut other = registry(LEFT);
+        other.cmds = other
+            .cmds
+            .into_iter()
+            .map(|mut cmd| {
+                cmd.removed_in_profile = Some(Profile::Core);
+                cmd
+            })
+            .collect();
+        let conflicts = merged.merge(other, MergePolicy::PreferLeft).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "command `Foo` is removed from no profile and from the `Core` profile"
+        );
+        assert_eq!(merged.cmds.iter().next().unwrap().removed_in_profile, None);
+    }
+
+    #[test]
+    fn test_newer_version() {
+        // merging a registry with a newer version of itself adds the newer commands once
+        let mut merged = Registry::new(Api::Gl, (3, 2), Profile::Core, Fallbacks::All, []);
+        let newer = Registry::new(Api::Gl, (4, 5), Profile::Core, Fallbacks::All, []);
+        let conflicts = merged.merge(newer.clone(), MergePolicy::Error).unwrap();
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.cmds.len(), newer.cmds.len());
+        assert_eq!(merged.enums.len(), newer.enums.len());
+    }
+}
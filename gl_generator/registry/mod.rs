@@ -13,21 +13,42 @@
 // limitations under the License.
 
 extern crate khronos_api;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 
 use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
+use std::error;
 use std::fmt;
+use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::io;
-use std::ops::{Add, AddAssign};
+use std::path::{Path, PathBuf};
 
-use Generator;
+use {Generator, GeneratorOptions};
 
+pub use self::builder::RegistryBuilder;
+pub(crate) use self::ctype::{builtin, parse_type};
+pub use self::ctype::{CType, TypeDef};
+pub use self::diff::{Changes, RegistryDiff};
+pub use self::merge::{Conflict, MergePolicy};
+pub use self::parse::Filter;
+pub use self::supplement::Supplement;
+
+mod builder;
+#[cfg(feature = "serde")]
+mod cache;
+mod classes;
+mod ctype;
+mod diff;
+mod merge;
 mod parse;
+mod supplement;
 
 const BYTE_ORDER_MARK: &'static [u8] = &[0xef, 0xbb, 0xbf];
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Api {
     Gl,
     Glx,
@@ -55,24 +76,35 @@ impl fmt::Display for Api {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Fallbacks {
     All,
     None,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Profile {
     Core,
     Compatibility,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum {
     pub ident: String,
     pub value: String,
     pub cast: bool,
     pub alias: Option<String>,
     pub ty: Cow<'static, str>,
+    /// The earliest version that requires this enum, or `None` if it is only required by
+    /// extensions.
+    pub introduced_in: Option<(u8, u8)>,
+    /// The names of the included extensions that require this enum.
+    pub required_by_extensions: Vec<String>,
+    /// The profile that this enum was removed from, if it was removed from a profile other than
+    /// the one that the registry was built for.
+    pub removed_in_profile: Option<Profile>,
 }
 
 impl Hash for Enum {
@@ -82,13 +114,36 @@ impl Hash for Enum {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Binding {
     pub ident: String,
     pub ty: Cow<'static, str>,
+    /// The C type of the binding, as declared in the registry.
+    pub c_ty: CType,
     pub group: Option<String>,
+    /// The number of elements that a pointer parameter points to, if known.
+    pub len: Option<Len>,
+    /// The kind of object that an object name refers to, for example `"buffer"` or
+    /// `"vertex array"`. Inferred from the names of the commands if the XML has no `class`
+    /// attributes.
+    pub class: Option<String>,
+}
+
+/// The length of an array parameter, from its `len` attribute.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Len {
+    /// A fixed number of elements, as in `len="16"`.
+    Fixed(usize),
+    /// The number of elements is given by another parameter, multiplied by `factor`, as in
+    /// `len="count"` or `len="count*16"`.
+    Param { ident: String, factor: usize },
+    /// Any other expression, such as `len="COMPSIZE(pname)"`, kept as it is.
+    Other(String),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Group {
     pub ident: String,
     pub enums_type: Option<String>,
@@ -96,12 +151,21 @@ pub struct Group {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cmd {
     pub proto: Binding,
     pub params: Vec<Binding>,
     pub alias: Option<String>,
     pub vecequiv: Option<String>,
     pub glx: Option<GlxOpcode>,
+    /// The earliest version that requires this command, or `None` if it is only required by
+    /// extensions.
+    pub introduced_in: Option<(u8, u8)>,
+    /// The names of the included extensions that require this command.
+    pub required_by_extensions: Vec<String>,
+    /// The profile that this command was removed from, if it was removed from a profile other than
+    /// the one that the registry was built for.
+    pub removed_in_profile: Option<Profile>,
 }
 
 impl Hash for Cmd {
@@ -111,21 +175,192 @@ impl Hash for Cmd {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GlxOpcode {
     pub opcode: String,
     pub name: Option<String>,
 }
 
+/// An error encountered while building a `Registry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegistryError {
+    /// The XML source was malformed.
+    Xml(String),
+    /// The XML source ended before the registry was complete.
+    UnexpectedEof,
+    /// The parser found something other than what the registry schema allows.
+    UnexpectedElement { expected: String, found: String },
+    /// An element was missing an attribute that the registry schema requires.
+    MissingAttribute { element: String, attribute: String },
+    /// An `api` or `supported` attribute named an API that we don't know about.
+    UnknownApi(String),
+    /// An `<enum>` had a `type` attribute that we can't map to a Rust type.
+    UnhandledEnumType { ident: String, ty: String },
+    /// An `<enum>` had a value in a format that we can't parse.
+    InvalidEnumValue { ident: String, value: String },
+    /// A command prototype or parameter used a C type that we can't parse.
+    ///
+    /// `param` is `None` when the type in question is the return type of the command.
+    UnknownType {
+        command: String,
+        param: Option<String>,
+        ty: String,
+    },
+    /// The declaration of a type could not be parsed.
+    InvalidType { decl: String, message: String },
+    /// The requested version is not in the registry, or is older than every version of the
+    /// requested API. Versions that only other APIs have select the newest version of the
+    /// requested API before them.
+    VersionNotFound { api: Api, version: (u8, u8) },
+    /// A `number` attribute was not a version of the form `major.minor`.
+    InvalidVersion(String),
+    /// The minimum version requested was newer than the maximum version.
+    InvalidVersionRange { min: (u8, u8), max: (u8, u8) },
+    /// A requested extension does not exist in the registry.
+    UnknownExtension(String),
+    /// A requested extension exists, but does not support the requested API.
+    UnsupportedExtension { extension: String, api: Api },
+    /// An extension pattern did not match any extensions that support the requested API.
+    NoMatchingExtensions(String),
+    /// An XML source could not be read. `path` is `None` if the source was not a file.
+    Io {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// `Registry::merge` found conflicts with `MergePolicy::Error`.
+    MergeConflicts(Vec<Conflict>),
+    /// The JSON given to `Registry::from_json` was malformed, or could not be read.
+    Json(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RegistryError::Xml(ref msg) => write!(fmt, "malformed registry XML: {}", msg),
+            RegistryError::UnexpectedEof => write!(fmt, "unexpected end of registry XML"),
+            RegistryError::UnexpectedElement {
+                ref expected,
+                ref found,
+            } => write!(fmt, "expected {}, found {}", expected, found),
+            RegistryError::MissingAttribute {
+                ref element,
+                ref attribute,
+            } => write!(
+                fmt,
+                "<{}> is missing the `{}` attribute",
+                element, attribute
+            ),
+            RegistryError::UnknownApi(ref api) => write!(fmt, "unknown API `{}`", api),
+            RegistryError::UnhandledEnumType { ref ident, ref ty } => {
+                write!(fmt, "unhandled type `{}` for enum `{}`", ty, ident)
+            },
+            RegistryError::InvalidEnumValue {
+                ref ident,
+                ref value,
+            } => write!(
+                fmt,
+                "unexpected value format `{}` for enum `{}`",
+                value, ident
+            ),
+            RegistryError::UnknownType {
+                ref command,
+                param: Some(ref param),
+                ref ty,
+            } => write!(
+                fmt,
+                "invalid type `{}` (parameter `{}` of `{}`)",
+                ty, param, command
+            ),
+            RegistryError::UnknownType {
+                ref command,
+                param: None,
+                ref ty,
+            } => write!(fmt, "invalid type `{}` (return type of `{}`)", ty, command),
+            RegistryError::VersionNotFound {
+                api,
+                version: (major, minor),
+            } => write!(
+                fmt,
+                "did not find version {}.{} of the {} API",
+                major, minor, api
+            ),
+            RegistryError::InvalidVersion(ref version) => {
+                write!(fmt, "`{}` is not a valid version number", version)
+            },
+            RegistryError::InvalidType {
+                ref decl,
+                ref message,
+            } => write!(fmt, "invalid type declaration `{}`: {}", decl, message),
+            RegistryError::InvalidVersionRange { min, max } => write!(
+                fmt,
+                "minimum version {}.{} is newer than maximum version {}.{}",
+                min.0, min.1, max.0, max.1
+            ),
+            RegistryError::UnknownExtension(ref extension) => {
+                write!(
+                    fmt,
+                    "requested extension {} is not in the registry",
+                    extension
+                )
+            },
+            RegistryError::UnsupportedExtension { ref extension, api } => write!(
+                fmt,
+                "requested {}, which doesn't support the {} API",
+                extension, api
+            ),
+            RegistryError::NoMatchingExtensions(ref pattern) => write!(
+                fmt,
+                "extension pattern `{}` did not match any extensions",
+                pattern
+            ),
+            RegistryError::Io {
+                path: Some(ref path),
+                ref message,
+            } => write!(fmt, "failed to read `{}`: {}", path.display(), message),
+            RegistryError::Io {
+                path: None,
+                ref message,
+            } => write!(fmt, "failed to read registry XML: {}", message),
+            RegistryError::MergeConflicts(ref conflicts) => {
+                write!(fmt, "conflicts between the merged registries:")?;
+                for conflict in conflicts {
+                    write!(fmt, "\n  {}", conflict)?;
+                }
+                Ok(())
+            },
+            RegistryError::Json(ref msg) => write!(fmt, "malformed registry JSON: {}", msg),
+        }
+    }
+}
+
+impl error::Error for RegistryError {}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Registry {
     pub api: Api,
+    /// The newest version included in the registry.
+    pub version: (u8, u8),
+    /// The oldest version that the bindings will be used with. Commands and enums introduced
+    /// after this version may not be available at runtime.
+    pub min_version: (u8, u8),
     pub enums: BTreeSet<Enum>,
     pub cmds: BTreeSet<Cmd>,
     pub aliases: BTreeMap<String, Vec<String>>,
     pub groups: BTreeMap<String, Group>,
+    /// The names of the extensions that were included in the registry.
+    pub extensions: BTreeSet<String>,
+    /// The types that the commands and enums use, by name.
+    pub types: BTreeMap<String, TypeDef>,
 }
 
 impl Registry {
+    /// Builds a registry from the Khronos XML bundled with `khronos_api`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registry could not be built. See `Registry::try_new` for a version of this
+    /// function that returns the error instead.
     pub fn new<'a, Exts>(
         api: Api,
         version: (u8, u8),
@@ -136,42 +371,198 @@ impl Registry {
     where
         Exts: AsRef<[&'a str]>,
     {
-        let (major, minor) = version;
-        let extensions = extensions.as_ref().iter().map(<&str>::to_string).collect();
-
-        let filter = parse::Filter {
-            api,
-            fallbacks,
-            extensions,
-            version: format!("{}.{}", major, minor),
-            profile,
-        };
+        Registry::try_new(api, version, profile, fallbacks, extensions)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        let src_raw = match api {
-            Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => khronos_api::GL_XML,
-            Api::Glx => khronos_api::GLX_XML,
-            Api::Wgl => khronos_api::WGL_XML,
-            Api::Egl => khronos_api::EGL_XML,
-        };
+    /// Builds a registry from the Khronos XML bundled with `khronos_api`, returning an error if
+    /// the XML could not be parsed, or if the requested version or extensions are not available
+    /// for `api`.
+    pub fn try_new<'a, Exts>(
+        api: Api,
+        version: (u8, u8),
+        profile: Profile,
+        fallbacks: Fallbacks,
+        extensions: Exts,
+    ) -> Result<Registry, RegistryError>
+    where
+        Exts: AsRef<[&'a str]>,
+    {
+        let filter = Filter::new(api, version, profile, fallbacks, extensions);
+        Registry::from_filter(&filter)
+    }
 
-        let src = if &src_raw[..BYTE_ORDER_MARK.len()] == BYTE_ORDER_MARK {
-            &src_raw[BYTE_ORDER_MARK.len()..]
-        } else {
-            src_raw
-        };
+    /// Builds a registry from the Khronos XML bundled with `khronos_api`, using an existing
+    /// `Filter`.
+    pub fn from_filter(filter: &Filter) -> Result<Registry, RegistryError> {
+        Registry::from_filter_with_supplements(filter, &Supplement::defaults())
+    }
+
+    /// Builds a registry from the Khronos XML bundled with `khronos_api`, along with any of
+    /// `supplements` that are needed by the extensions requested by `filter`.
+    pub fn from_filter_with_supplements(
+        filter: &Filter,
+        supplements: &[Supplement],
+    ) -> Result<Registry, RegistryError> {
+        let sources = bundled_sources(filter, supplements);
+        let mut registry = parse::from_xml(strip_bom(sources[0]), filter, true)?;
+        for src in &sources[1..] {
+            registry += parse::from_xml(strip_bom(src), filter, false)?;
+        }
+
+        registry.check_extensions(filter)?;
+        registry.add_gl_types()?;
+        registry.retain_used_types();
+        Ok(registry)
+    }
+
+    /// Builds a registry from XML sources other than the ones bundled with `khronos_api`, for
+    /// example a pinned revision of `gl.xml`.
+    ///
+    /// The first source must be a complete registry that defines the version requested by
+    /// `filter`. Any further sources are treated as supplemental registries in the style of
+    /// ANGLE's `gl_angle_ext.xml`, and only need to define extensions.
+    pub fn from_sources<I, R>(filter: &Filter, sources: I) -> Result<Registry, RegistryError>
+    where
+        I: IntoIterator<Item = R>,
+        R: io::Read,
+    {
+        let mut registry: Option<Registry> = None;
+        for mut source in sources {
+            let mut src = Vec::new();
+            source
+                .read_to_end(&mut src)
+                .map_err(|err| RegistryError::Io {
+                    path: None,
+                    message: err.to_string(),
+                })?;
+            registry = Some(match registry {
+                None => parse::from_xml(strip_bom(&src), filter, true)?,
+                Some(registry) => registry + parse::from_xml(strip_bom(&src), filter, false)?,
+            });
+        }
+
+        let mut registry = registry.ok_or(RegistryError::UnexpectedEof)?;
+        registry.check_extensions(filter)?;
+        registry.add_gl_types()?;
+        registry.retain_used_types();
+        Ok(registry)
+    }
+
+    /// Builds a registry from XML files on disk. See `Registry::from_sources` for details.
+    pub fn from_paths<I, P>(filter: &Filter, paths: I) -> Result<Registry, RegistryError>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut sources = Vec::new();
+        for path in paths {
+            let path = path.as_ref();
+            let file = File::open(path).map_err(|err| RegistryError::Io {
+                path: Some(path.to_path_buf()),
+                message: err.to_string(),
+            })?;
+            sources.push(io::BufReader::new(file));
+        }
+        Registry::from_sources(filter, sources)
+    }
+
+    /// Reads a registry that was written by `Registry::to_json`, for example one that was cached
+    /// by an earlier build. Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn from_json<R: io::Read>(src: R) -> Result<Registry, RegistryError> {
+        serde_json::from_reader(src).map_err(|err| RegistryError::Json(err.to_string()))
+    }
+
+    /// Writes the registry as JSON, which `Registry::from_json` reads back. Requires the `serde`
+    /// feature.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("registries always serialize to JSON")
+    }
+
+    /// Checks that every extension requested by `filter` made it into the registry, and that
+    /// every pattern matched at least one extension.
+    fn check_extensions(&self, filter: &Filter) -> Result<(), RegistryError> {
+        for requested in &filter.extensions {
+            if parse::is_pattern(requested) {
+                // Patterns that only matched excluded extensions are not mistakes
+                let matches = |e: &String| parse::matches_pattern(requested, e);
+                if !self.extensions.iter().any(&matches)
+                    && !filter.excluded_extensions.iter().any(&matches)
+                {
+                    return Err(RegistryError::NoMatchingExtensions(requested.clone()));
+                }
+            } else if !self.extensions.contains(requested)
+                && !filter.excluded_extensions.contains(requested)
+            {
+                return Err(RegistryError::UnknownExtension(requested.clone()));
+            }
+        }
+        Ok(())
+    }
 
-        let mut registry = parse::from_xml(src, &filter, true);
-        if filter.extensions.iter().any(|e| e.starts_with("GL_ANGLE_")) {
-            registry += parse::from_xml(khronos_api::GL_ANGLE_EXT_XML, &filter, false);
+    /// Adds the GL types that the GLX and WGL registries use without defining them, from the
+    /// `<types>` section of the bundled `gl.xml`.
+    fn add_gl_types(&mut self) -> Result<(), RegistryError> {
+        if self.api != Api::Glx && self.api != Api::Wgl {
+            return Ok(());
+        }
+        let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::None, []);
+        let gl_types = parse::types_from_xml(strip_bom(khronos_api::GL_XML), &filter)?;
+        for (name, def) in gl_types {
+            let is_defined = match self.types.get(&name) {
+                Some(&TypeDef::External) | None => false,
+                Some(_) => true,
+            };
+            if !is_defined {
+                self.types.insert(name, def);
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the types that none of the commands and enums need, apart from the ones that were
+    /// declared in 0.14, and adds the types that they need but that the XML only refers to.
+    /// Structs that are only referred to become `TypeDef::Opaque`, and everything else becomes
+    /// `TypeDef::External`.
+    fn retain_used_types(&mut self) {
+        let mut pending: Vec<_> = types_0_14(self.api)
+            .filter(|&name| self.types.contains_key(name))
+            .map(|name| CType::Named(name.to_string()))
+            .collect();
+        for cmd in &self.cmds {
+            for binding in Some(&cmd.proto).into_iter().chain(&cmd.params) {
+                pending.push(binding.c_ty.base().clone());
+            }
+        }
+        for enm in &self.enums {
+            if enm.ty != "&'static str" {
+                pending.push(CType::Named(enm.ty.to_string()));
+            }
         }
-        if filter
-            .extensions
-            .iter()
-            .any(|e| e.starts_with("EGL_ANGLE_"))
-        {
-            registry += parse::from_xml(khronos_api::EGL_ANGLE_EXT_XML, &filter, false);
+
+        let mut used = BTreeMap::new();
+        while let Some(ty) = pending.pop() {
+            let (name, def) = match ty {
+                CType::Named(ref name) if ctype::builtin(name).is_some() => continue,
+                CType::Named(name) => {
+                    let def = self.types.get(&name).cloned();
+                    (name, def.unwrap_or(TypeDef::External))
+                },
+                CType::Struct(name) => {
+                    let def = self.types.get(&name).cloned();
+                    (name, def.unwrap_or(TypeDef::Opaque))
+                },
+                _ => continue,
+            };
+            if name.is_empty() || used.contains_key(&name) {
+                continue;
+            }
+            pending.extend(def.dependencies().into_iter().cloned());
+            used.insert(name, def);
         }
-        registry
+        self.types = used;
     }
 
     pub fn write_bindings<W, G>(&self, generator: G, output: &mut W) -> io::Result<()>
@@ -179,7 +570,21 @@ impl Registry {
         G: Generator,
         W: io::Write,
     {
-        generator.write(&self, output)
+        generator.write(self, output)
+    }
+
+    /// Like `write_bindings`, but with the extras enabled in `options`.
+    pub fn write_bindings_with_options<W, G>(
+        &self,
+        generator: G,
+        options: &GeneratorOptions,
+        output: &mut W,
+    ) -> io::Result<()>
+    where
+        G: Generator,
+        W: io::Write,
+    {
+        generator.write_with_options(self, options, output)
     }
 
     /// Returns a set of all the types used in the supplied registry. This is useful
@@ -196,19 +601,500 @@ impl Registry {
     }
 }
 
-impl Add for Registry {
-    type Output = Registry;
+/// The types that the `types` module declared in 0.14, when it was copied from hand-written
+/// templates. They are kept even when no command uses them, so that code which names them, such
+/// as `gl::types::GLvoid`, still compiles.
+const GL_TYPES_0_14: &[&str] = &[
+    "GLenum",
+    "GLboolean",
+    "GLbitfield",
+    "GLvoid",
+    "GLbyte",
+    "GLshort",
+    "GLint",
+    "GLclampx",
+    "GLubyte",
+    "GLushort",
+    "GLuint",
+    "GLsizei",
+    "GLfloat",
+    "GLclampf",
+    "GLdouble",
+    "GLclampd",
+    "GLeglImageOES",
+    "GLchar",
+    "GLcharARB",
+    "GLhandleARB",
+    "GLhalfARB",
+    "GLhalf",
+    "GLfixed",
+    "GLintptr",
+    "GLsizeiptr",
+    "GLint64",
+    "GLuint64",
+    "GLintptrARB",
+    "GLsizeiptrARB",
+    "GLint64EXT",
+    "GLuint64EXT",
+    "__GLsync",
+    "GLsync",
+    "_cl_context",
+    "_cl_event",
+    "GLDEBUGPROC",
+    "GLDEBUGPROCARB",
+    "GLDEBUGPROCKHR",
+    "GLDEBUGPROCAMD",
+    "GLhalfNV",
+    "GLvdpauSurfaceNV",
+];
+
+const GLX_TYPES_0_14: &[&str] = &[
+    "XID",
+    "Bool",
+    "Display",
+    "Font",
+    "Pixmap",
+    "Visual",
+    "VisualID",
+    "Window",
+    "GLXFBConfigID",
+    "GLXFBConfig",
+    "GLXContextID",
+    "GLXContext",
+    "GLXPixmap",
+    "GLXDrawable",
+    "GLXWindow",
+    "GLXPbuffer",
+    "__GLXextFuncPtr",
+    "GLXVideoCaptureDeviceNV",
+    "GLXVideoDeviceNV",
+    "GLXVideoSourceSGIX",
+    "GLXFBConfigIDSGIX",
+    "GLXFBConfigSGIX",
+    "GLXPbufferSGIX",
+    "XVisualInfo",
+    "GLXPbufferClobberEvent",
+    "GLXBufferSwapComplete",
+    "GLXBufferClobberEventSGIX",
+    "GLXHyperpipeNetworkSGIX",
+    "GLXHyperpipeConfigSGIX",
+    "GLXPipeRect",
+    "GLXPipeRectLimits",
+];
+
+const WGL_TYPES_0_14: &[&str] = &[
+    "CHAR",
+    "HANDLE",
+    "LONG",
+    "LPCSTR",
+    "VOID",
+    "HPBUFFERARB",
+    "HPBUFFEREXT",
+    "HVIDEOOUTPUTDEVICENV",
+    "HPVIDEODEV",
+    "HPGPUNV",
+    "HGPUNV",
+    "HVIDEOINPUTDEVICENV",
+    "BOOL",
+    "BYTE",
+    "COLORREF",
+    "FLOAT",
+    "HDC",
+    "HENHMETAFILE",
+    "HGLRC",
+    "INT",
+    "PVOID",
+    "LPVOID",
+    "PROC",
+    "RECT",
+    "UINT",
+    "USHORT",
+    "WORD",
+    "INT32",
+    "INT64",
+    "DWORD",
+    "POINTFLOAT",
+    "GLYPHMETRICSFLOAT",
+    "LPGLYPHMETRICSFLOAT",
+    "LAYERPLANEDESCRIPTOR",
+    "PIXELFORMATDESCRIPTOR",
+    "_GPU_DEVICE",
+    "GPU_DEVICE",
+    "PGPU_DEVICE",
+];
+
+const EGL_TYPES_0_14: &[&str] = &[
+    "Bool",
+    "EGLBoolean",
+    "EGLenum",
+    "EGLAttribKHR",
+    "EGLAttrib",
+    "EGLConfig",
+    "EGLContext",
+    "EGLDeviceEXT",
+    "EGLDisplay",
+    "EGLSurface",
+    "EGLClientBuffer",
+    "__eglMustCastToProperFunctionPointerType",
+    "EGLImageKHR",
+    "EGLImage",
+    "EGLOutputLayerEXT",
+    "EGLOutputPortEXT",
+    "EGLSyncKHR",
+    "EGLSync",
+    "EGLTimeKHR",
+    "EGLTime",
+    "EGLSyncNV",
+    "EGLTimeNV",
+    "EGLuint64NV",
+    "EGLStreamKHR",
+    "EGLuint64KHR",
+    "EGLNativeFileDescriptorKHR",
+    "EGLsizeiANDROID",
+    "EGLSetBlobFuncANDROID",
+    "EGLGetBlobFuncANDROID",
+    "EGLClientPixmapHI",
+];
+
+/// The names of the types that the `types` module declared for `api` in 0.14.
+fn types_0_14(api: Api) -> impl Iterator<Item = &'static str> {
+    let (common, extra) = match api {
+        Api::Egl => (&[][..], EGL_TYPES_0_14),
+        Api::Glx => (GL_TYPES_0_14, GLX_TYPES_0_14),
+        Api::Wgl => (GL_TYPES_0_14, WGL_TYPES_0_14),
+        Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => (GL_TYPES_0_14, &[][..]),
+    };
+    common.iter().chain(extra).cloned()
+}
+
+/// The XML that `Registry::from_filter_with_supplements` parses: the registry bundled with
+/// `khronos_api` for the API, followed by the supplements that the requested extensions need.
+fn bundled_sources<'a>(filter: &Filter, supplements: &'a [Supplement]) -> Vec<&'a [u8]> {
+    let src = match filter.api {
+        Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => khronos_api::GL_XML,
+        Api::Glx => khronos_api::GLX_XML,
+        Api::Wgl => khronos_api::WGL_XML,
+        Api::Egl => khronos_api::EGL_XML,
+    };
+
+    let mut sources = vec![src];
+    for supplement in supplements {
+        if filter.extensions.iter().any(|e| supplement.is_needed_by(e)) {
+            sources.push(supplement.src());
+        }
+    }
+    sources
+}
 
-    fn add(mut self, other: Registry) -> Registry {
-        self += other;
-        self
+fn strip_bom(src: &[u8]) -> &[u8] {
+    if src.starts_with(BYTE_ORDER_MARK) {
+        &src[BYTE_ORDER_MARK.len()..]
+    } else {
+        src
     }
 }
 
-impl AddAssign for Registry {
-    fn add_assign(&mut self, other: Self) {
-        self.enums.extend(other.enums);
-        self.cmds.extend(other.cmds);
-        self.aliases.extend(other.aliases);
+#[cfg(test)]
+mod tests {
+    mod try_new {
+        use registry::{Registry, RegistryError};
+        use {Api, Fallbacks, Profile};
+
+        #[test]
+        fn test_version_of_other_api() {
+            // OpenGL 1.0 predates every version of GLES 2
+            let result = Registry::try_new(Api::Gles2, (1, 0), Profile::Core, Fallbacks::All, []);
+            assert_eq!(
+                result,
+                Err(RegistryError::VersionNotFound {
+                    api: Api::Gles2,
+                    version: (1, 0),
+                })
+            );
+        }
+
+        #[test]
+        fn test_version_between_features() {
+            // GLES 1.1 and GLSC 3.1 aren't features of the registry, but were accepted before
+            let result = Registry::try_new(Api::Gles1, (1, 1), Profile::Core, Fallbacks::All, []);
+            assert!(!result.unwrap().cmds.is_empty());
+            let result = Registry::try_new(Api::Glsc2, (3, 1), Profile::Core, Fallbacks::All, []);
+            assert!(!result.unwrap().cmds.is_empty());
+        }
+
+        #[test]
+        fn test_version_not_in_registry() {
+            let result = Registry::try_new(Api::Gl, (4, 7), Profile::Core, Fallbacks::All, []);
+            assert_eq!(
+                result,
+                Err(RegistryError::VersionNotFound {
+                    api: Api::Gl,
+                    version: (4, 7),
+                })
+            );
+        }
+
+        #[test]
+        fn test_glcore_version() {
+            let registry =
+                Registry::try_new(Api::GlCore, (4, 6), Profile::Core, Fallbacks::All, []).unwrap();
+            assert!(!registry.cmds.is_empty());
+            assert!(!registry.enums.is_empty());
+            // the compatibility profile is never selected for `glcore`
+            assert!(!registry.cmds.iter().any(|c| c.proto.ident == "Begin"));
+        }
+    }
+
+    mod from_sources {
+        use registry::{
+            CType, Filter, Registry, RegistryBuilder, RegistryError, TypeDef, BYTE_ORDER_MARK,
+        };
+        use std::path::PathBuf;
+        use {Api, Fallbacks, Profile};
+
+        const MAIN: &str = r#"
+            <registry>
+                <enums namespace="GL">
+                    <enum value="0x0001" name="GL_FOO"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto>void <name>glFoo</name></proto>
+                    </command>
+                </commands>
+                <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                    <require>
+                        <enum name="GL_FOO"/>
+                        <command name="glFoo"/>
+                    </require>
+                </feature>
+            </registry>
+        "#;
+
+        const VENDOR: &str = r#"
+            <registry>
+                <enums namespace="GL">
+                    <enum value="0x0002" name="GL_BAR_ACME"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto>void <name>glBarACME</name></proto>
+                    </command>
+                </commands>
+                <extensions>
+                    <extension name="GL_ACME_bar" supported="gl">
+                        <require>
+                            <enum name="GL_BAR_ACME"/>
+                            <command name="glBarACME"/>
+                        </require>
+                    </extension>
+                </extensions>
+            </registry>
+        "#;
+
+        #[test]
+        fn test_single_source() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = Registry::from_sources(&filter, vec![MAIN.as_bytes()]).unwrap();
+            assert_eq!(registry.cmds.len(), 1);
+            assert_eq!(registry.enums.len(), 1);
+        }
+
+        #[test]
+        fn test_supplemental_source() {
+            let filter = Filter::new(
+                Api::Gl,
+                (1, 0),
+                Profile::Core,
+                Fallbacks::All,
+                ["GL_ACME_bar"],
+            );
+            let sources = vec![MAIN.as_bytes(), VENDOR.as_bytes()];
+            let registry = Registry::from_sources(&filter, sources).unwrap();
+            assert_eq!(registry.cmds.len(), 2);
+            assert_eq!(registry.enums.len(), 2);
+            assert!(registry.extensions.contains("GL_ACME_bar"));
+        }
+
+        #[test]
+        fn test_byte_order_mark() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let src = [BYTE_ORDER_MARK, MAIN.as_bytes()].concat();
+            let registry = Registry::from_sources(&filter, vec![&src[..]]).unwrap();
+            assert_eq!(registry.cmds.len(), 1);
+        }
+
+        #[test]
+        fn test_unknown_extension() {
+            let filter = Filter::new(
+                Api::Gl,
+                (1, 0),
+                Profile::Core,
+                Fallbacks::All,
+                ["GL_ACME_baz"],
+            );
+            let sources = vec![MAIN.as_bytes(), VENDOR.as_bytes()];
+            assert_eq!(
+                Registry::from_sources(&filter, sources),
+                Err(RegistryError::UnknownExtension("GL_ACME_baz".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_missing_path() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            match Registry::from_paths(&filter, ["does/not/exist.xml"]) {
+                Err(RegistryError::Io { path, .. }) => {
+                    assert_eq!(path, Some(PathBuf::from("does/not/exist.xml")))
+                },
+                result => panic!("expected an IO error, found {:?}", result),
+            }
+        }
+
+        #[test]
+        fn test_vendor() {
+            let builder = RegistryBuilder::new(Api::Gl, (1, 0)).vendors(["ACME"]);
+            let sources = vec![MAIN.as_bytes(), VENDOR.as_bytes()];
+            let registry = Registry::from_sources(builder.filter(), sources).unwrap();
+            assert!(registry.extensions.contains("GL_ACME_bar"));
+        }
+
+        #[test]
+        fn test_excluded_extension() {
+            let builder = RegistryBuilder::new(Api::Gl, (1, 0))
+                .all_extensions()
+                .exclude_extensions(["GL_ACME_*"]);
+            let sources = vec![MAIN.as_bytes(), VENDOR.as_bytes()];
+            let registry = Registry::from_sources(builder.filter(), sources).unwrap();
+            assert!(registry.extensions.is_empty());
+            assert_eq!(registry.cmds.len(), 1);
+        }
+
+        #[test]
+        fn test_used_types() {
+            let src = r#"
+                <registry>
+                    <types>
+                        <type>typedef unsigned int <name>GLenum</name>;</type>
+                        <type>typedef double <name>GLdouble</name>;</type>
+                        <type>typedef struct __GLsync *<name>GLsync</name>;</type>
+                    </types>
+                    <commands namespace="GL">
+                        <command>
+                            <proto><ptype>GLsync</ptype> <name>glFenceSync</name></proto>
+                            <param><ptype>GLenum</ptype> <name>condition</name></param>
+                            <param><ptype>GLbitfield</ptype> <name>flags</name></param>
+                        </command>
+                    </commands>
+                    <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                        <require>
+                            <command name="glFenceSync"/>
+                        </require>
+                    </feature>
+                </registry>
+            "#;
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = Registry::from_sources(&filter, vec![src.as_bytes()]).unwrap();
+            let names: Vec<_> = registry.types.keys().map(String::as_str).collect();
+            // `GLdouble` is unused, but it was declared in 0.14
+            assert_eq!(
+                names,
+                ["GLbitfield", "GLdouble", "GLenum", "GLsync", "__GLsync"]
+            );
+            assert_eq!(registry.types["GLbitfield"], TypeDef::External);
+            assert_eq!(registry.types["__GLsync"], TypeDef::Opaque);
+        }
+
+        #[test]
+        fn test_glx_uses_gl_types() {
+            let src = r#"
+                <registry>
+                    <types>
+                        <type name="Display"/>
+                        <type name="GLenum"/>
+                    </types>
+                    <commands namespace="GLX">
+                        <command>
+                            <proto>void <name>glXTest</name></proto>
+                            <param><ptype>Display</ptype> *<name>dpy</name></param>
+                            <param><ptype>GLenum</ptype> <name>target</name></param>
+                        </command>
+                    </commands>
+                    <feature api="glx" name="GLX_VERSION_1_0" number="1.0">
+                        <require>
+                            <command name="glXTest"/>
+                        </require>
+                    </feature>
+                </registry>
+            "#;
+            let filter = Filter::new(Api::Glx, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = Registry::from_sources(&filter, vec![src.as_bytes()]).unwrap();
+            assert_eq!(registry.types["Display"], TypeDef::External);
+            assert_eq!(
+                registry.types["GLenum"],
+                TypeDef::Alias(CType::Named("unsigned int".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_no_matching_extensions() {
+            let builder = RegistryBuilder::new(Api::Gl, (1, 0)).extensions_matching("GL_NV_*");
+            let sources = vec![MAIN.as_bytes(), VENDOR.as_bytes()];
+            assert_eq!(
+                Registry::from_sources(builder.filter(), sources),
+                Err(RegistryError::NoMatchingExtensions("GL_NV_*".to_string()))
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    mod json {
+        use registry::{Filter, Registry, RegistryError};
+        use {Api, Fallbacks, Profile};
+
+        const SRC: &str = r#"
+            <registry>
+                <types>
+                    <type>typedef unsigned int <name>GLenum</name>;</type>
+                    <type>typedef struct __GLsync *<name>GLsync</name>;</type>
+                </types>
+                <groups>
+                    <group name="SyncCondition">
+                        <enum name="GL_SYNC_GPU_COMMANDS_COMPLETE"/>
+                    </group>
+                </groups>
+                <enums namespace="GL">
+                    <enum value="0x9117" name="GL_SYNC_GPU_COMMANDS_COMPLETE"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto><ptype>GLsync</ptype> <name>glFenceSync</name></proto>
+                        <param group="SyncCondition"><ptype>GLenum</ptype> <name>condition</name></param>
+                    </command>
+                </commands>
+                <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                    <require>
+                        <enum name="GL_SYNC_GPU_COMMANDS_COMPLETE"/>
+                        <command name="glFenceSync"/>
+                    </require>
+                </feature>
+            </registry>
+        "#;
+
+        #[test]
+        fn test_round_trip() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = Registry::from_sources(&filter, vec![SRC.as_bytes()]).unwrap();
+            let json = registry.to_json();
+            assert_eq!(Registry::from_json(json.as_bytes()), Ok(registry));
+        }
+
+        #[test]
+        fn test_malformed() {
+            match Registry::from_json(&b"{\"api\": \"Gl\""[..]) {
+                Err(RegistryError::Json(_)) => {},
+                result => panic!("expected a JSON error, found {:?}", result),
+            }
+        }
     }
 }
@@ -15,22 +15,53 @@
 extern crate khronos_api;
 
 use std::borrow::Cow;
+use std::cmp;
 use std::collections::btree_map::Entry;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::io;
 use xml::attribute::OwnedAttribute;
 use xml::reader::XmlEvent;
 use xml::EventReader as XmlEventReader;
 
-use registry::{Binding, Cmd, Enum, GlxOpcode, Group, Registry};
+use registry::{classes, ctype};
+use registry::{Binding, Cmd, Enum, GlxOpcode, Group, Len, Registry, RegistryError, TypeDef};
 use {Api, Fallbacks, Profile};
 
-pub fn from_xml<R: io::Read>(src: R, filter: &Filter, require_feature: bool) -> Registry {
+pub fn from_xml<R: io::Read>(
+    src: R,
+    filter: &Filter,
+    require_feature: bool,
+) -> Result<Registry, RegistryError> {
+    events(src).parse(filter, require_feature)
+}
+
+/// Parses only the `<types>` section of a registry, keeping the types that apply to the API of
+/// `filter`.
+pub fn types_from_xml<R: io::Read>(
+    src: R,
+    filter: &Filter,
+) -> Result<BTreeMap<String, TypeDef>, RegistryError> {
+    let mut events = events(src);
+    loop {
+        match events.next_event()? {
+            ParseEvent::Start(ref name, _) if name == "types" => {
+                let decls = events.consume_types()?;
+                return parse_types(decls, filter, &[]);
+            },
+            ParseEvent::End(ref name) if name == "registry" => return Ok(BTreeMap::new()),
+            _ => {},
+        }
+    }
+}
+
+fn events<R: io::Read>(src: R) -> impl Iterator<Item = Result<ParseEvent, RegistryError>> {
     XmlEventReader::new(src)
         .into_iter()
-        .map(Result::unwrap)
-        .filter_map(ParseEvent::from_xml)
-        .parse(filter, require_feature)
+        .filter_map(|event| match event {
+            Ok(event) => ParseEvent::from_xml(event).map(Ok),
+            Err(err) => Some(Err(RegistryError::Xml(err.to_string()))),
+        })
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -86,6 +117,23 @@ impl ParseEvent {
     }
 }
 
+impl fmt::Display for ParseEvent {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseEvent::Start(ref name, _) => write!(fmt, "<{}>", name),
+            ParseEvent::End(ref name) => write!(fmt, "</{}>", name),
+            ParseEvent::Text(ref text) => write!(fmt, "text {:?}", text),
+        }
+    }
+}
+
+fn unexpected<T, E: ToString>(expected: E, found: &ParseEvent) -> Result<T, RegistryError> {
+    Err(RegistryError::UnexpectedElement {
+        expected: expected.to_string(),
+        found: found.to_string(),
+    })
+}
+
 fn api_from_str(src: &str) -> Result<Option<Api>, ()> {
     match src {
         "gl" => Ok(Some(Api::Gl)),
@@ -101,6 +149,38 @@ fn api_from_str(src: &str) -> Result<Option<Api>, ()> {
     }
 }
 
+fn version_from_str(src: &str) -> Result<(u8, u8), ()> {
+    let mut parts = src.split('.');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(major), Some(minor), None) => Ok((
+            major.parse().map_err(|_| ())?,
+            minor.parse().map_err(|_| ())?,
+        )),
+        _ => Err(()),
+    }
+}
+
+fn len_from_str(src: &str) -> Len {
+    let (ident, factor) = match src.find('*') {
+        Some(i) => match src[i + 1..].parse() {
+            Ok(factor) => (&src[..i], factor),
+            Err(_) => return Len::Other(src.to_string()),
+        },
+        None => (src, 1),
+    };
+    if let Ok(len) = ident.parse::<usize>() {
+        return Len::Fixed(len * factor);
+    }
+    if !ident.is_empty() && ident.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        Len::Param {
+            ident: underscore_keyword(ident.to_string()),
+            factor,
+        }
+    } else {
+        Len::Other(src.to_string())
+    }
+}
+
 fn profile_from_str(src: &str) -> Result<Profile, ()> {
     match src {
         "core" => Ok(Profile::Core),
@@ -116,12 +196,21 @@ fn underscore_numeric_prefix(src: &str) -> String {
     }
 }
 
-fn underscore_keyword(ident: String) -> String {
-    match ident.as_ref() {
-        "in" => "in_".to_string(),
-        "ref" => "ref_".to_string(),
-        "type" => "type_".to_string(),
-        _ => ident,
+/// The keywords of every Rust edition, including the reserved ones, which can't be used as
+/// parameter or field names.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+pub fn underscore_keyword(ident: String) -> String {
+    if KEYWORDS.contains(&&*ident) {
+        ident + "_"
+    } else {
+        ident
     }
 }
 
@@ -143,7 +232,12 @@ fn trim_enum_prefix(ident: &str, api: Api) -> String {
     underscore_numeric_prefix(ident)
 }
 
-fn make_enum(ident: String, ty: Option<String>, value: String, alias: Option<String>) -> Enum {
+fn make_enum(
+    ident: String,
+    ty: Option<String>,
+    value: String,
+    alias: Option<String>,
+) -> Result<Enum, RegistryError> {
     let (ty, value, cast) = {
         if value.starts_with("((") && value.ends_with(")") {
             // Some enums have a value of the form `'((' type ')' expr ')'`.
@@ -157,14 +251,15 @@ fn make_enum(ident: String, ty: Option<String>, value: String, alias: Option<Str
 
                 (Cow::Owned(ty), value, true)
             } else {
-                panic!("Unexpected value format: {}", value)
+                return Err(RegistryError::InvalidEnumValue { ident, value });
             }
         } else {
             let ty = match ty {
                 Some(ref ty) if ty == "u" => "GLuint",
                 Some(ref ty) if ty == "ull" => "GLuint64",
-                Some(ty) => panic!("Unhandled enum type: {}", ty),
+                Some(ty) => return Err(RegistryError::UnhandledEnumType { ident, ty }),
                 None if value.starts_with("\"") => "&'static str",
+                None if value.starts_with('-') => "GLint",
                 None if ident == "TRUE" || ident == "FALSE" => "GLboolean",
                 None => "GLenum",
             };
@@ -172,16 +267,24 @@ fn make_enum(ident: String, ty: Option<String>, value: String, alias: Option<Str
         }
     };
 
-    Enum {
+    Ok(Enum {
         ident: ident,
         value: value,
         cast: cast,
         alias: alias,
         ty: ty,
-    }
+        introduced_in: None,
+        required_by_extensions: Vec::new(),
+        removed_in_profile: None,
+    })
 }
 
-fn make_egl_enum(ident: String, ty: Option<String>, value: String, alias: Option<String>) -> Enum {
+fn make_egl_enum(
+    ident: String,
+    ty: Option<String>,
+    value: String,
+    alias: Option<String>,
+) -> Result<Enum, RegistryError> {
     let (ty, value, cast) = {
         if value.starts_with("EGL_CAST(") && value.ends_with(")") {
             // Handling "SpecialNumbers" in the egl.xml file
@@ -193,17 +296,17 @@ fn make_egl_enum(ident: String, ty: Option<String>, value: String, alias: Option
 
                 (Cow::Owned(ty), value, true)
             } else {
-                panic!("Unexpected value format: {}", value)
+                return Err(RegistryError::InvalidEnumValue { ident, value });
             }
         } else {
             match value.chars().next() {
                 Some('-') | Some('0'..='9') => (),
-                _ => panic!("Unexpected value format: {}", value),
+                _ => return Err(RegistryError::InvalidEnumValue { ident, value }),
             }
 
             let ty = match ty {
                 Some(ref ty) if ty == "ull" => "EGLuint64KHR",
-                Some(ty) => panic!("Unhandled enum type: {}", ty),
+                Some(ty) => return Err(RegistryError::UnhandledEnumType { ident, ty }),
                 None if value.starts_with('-') => "EGLint",
                 None if ident == "TRUE" || ident == "FALSE" => "EGLBoolean",
                 None => "EGLenum",
@@ -212,15 +315,56 @@ fn make_egl_enum(ident: String, ty: Option<String>, value: String, alias: Option
         }
     };
 
-    Enum {
+    Ok(Enum {
         ident: ident,
         value: value,
         cast: cast,
         alias: alias,
         ty: ty,
+        introduced_in: None,
+        required_by_extensions: Vec::new(),
+        removed_in_profile: None,
+    })
+}
+
+/// Why an enum or command was included in the registry.
+#[derive(Clone, Debug, Default)]
+struct Provenance {
+    introduced_in: Option<(u8, u8)>,
+    required_by_extensions: Vec<String>,
+    removed_in_profile: Option<Profile>,
+}
+
+impl Provenance {
+    /// Records that the feature `number` requires the enum or command. The registry may list its
+    /// features in any order, so the earliest version that requires it is kept.
+    fn add_feature(&mut self, number: (u8, u8)) {
+        let version = self
+            .introduced_in
+            .map_or(number, |version| cmp::min(version, number));
+        self.introduced_in = Some(version);
+    }
+
+    fn add_extension(&mut self, name: &str) {
+        if !self.required_by_extensions.iter().any(|ext| ext == name) {
+            self.required_by_extensions.push(name.to_string());
+        }
     }
 }
 
+/// Looks up an enum or command by its unprefixed identifier, returning why it was included if it
+/// is desired.
+fn find_desired<'a>(
+    desired: &'a BTreeMap<String, Provenance>,
+    prefixes: &[&str],
+    ident: &str,
+) -> Option<&'a Provenance> {
+    prefixes
+        .iter()
+        .filter_map(|prefix| desired.get(&(prefix.to_string() + ident)))
+        .next()
+}
+
 fn trim_cmd_prefix(ident: &str, api: Api) -> &str {
     match api {
         Api::Gl | Api::GlCore | Api::Gles1 | Api::Gles2 | Api::Glsc2 => trim_str(ident, "gl"),
@@ -230,6 +374,9 @@ fn trim_cmd_prefix(ident: &str, api: Api) -> &str {
     }
 }
 
+/// Maps the name of a command to the commands that alias it.
+type Aliases = BTreeMap<String, Vec<String>>;
+
 fn merge_map(a: &mut BTreeMap<String, Vec<String>>, b: BTreeMap<String, Vec<String>>) {
     for (k, v) in b {
         match a.entry(k) {
@@ -247,13 +394,17 @@ fn merge_map(a: &mut BTreeMap<String, Vec<String>>, b: BTreeMap<String, Vec<Stri
 struct Feature {
     pub api: Api,
     pub name: String,
-    pub number: String,
+    pub number: (u8, u8),
     pub requires: Vec<Require>,
     pub removes: Vec<Remove>,
 }
 
 #[derive(Clone)]
 struct Require {
+    /// The API this requirement is restricted to, if any
+    pub api: Option<Api>,
+    /// The profile this requirement is restricted to, if any
+    pub profile: Option<String>,
     /// A reference to the earlier types, by name
     pub enums: Vec<String>,
     /// A reference to the earlier types, by name
@@ -262,14 +413,26 @@ struct Require {
 
 #[derive(Clone)]
 struct Remove {
-    // always Core, for now
-    pub profile: Profile,
+    /// The API this removal is restricted to, if any
+    pub api: Option<Api>,
+    /// The profile this removal is restricted to, if any
+    pub profile: Option<String>,
     /// A reference to the earlier types, by name
     pub enums: Vec<String>,
     /// A reference to the earlier types, by name
     pub commands: Vec<String>,
 }
 
+/// The contents of a `<type>` element.
+struct TypeDecl {
+    /// The API this type is restricted to, if any
+    api: Option<Api>,
+    /// The name from the `name` attribute or `<name>` element, if any
+    name: Option<String>,
+    category: Option<String>,
+    text: String,
+}
+
 #[derive(Clone)]
 struct Extension {
     pub name: String,
@@ -278,17 +441,160 @@ struct Extension {
     pub requires: Vec<Require>,
 }
 
+/// Selects which parts of an XML registry end up in a `Registry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Filter {
     pub api: Api,
     pub fallbacks: Fallbacks,
+    /// The names of the extensions to include, for example `"GL_ARB_debug_output"`.
+    ///
+    /// Names may contain `*` and `?` wildcards, for example `"GL_ARB_*"`. Extensions that are
+    /// selected by a wildcard but don't support `api` are skipped, whereas selecting such an
+    /// extension by its exact name is an error.
     pub extensions: BTreeSet<String>,
+    /// The names of the extensions to leave out, even if they are selected by `extensions`.
+    /// These may also contain wildcards.
+    pub excluded_extensions: BTreeSet<String>,
     pub profile: Profile,
-    pub version: String,
+    /// The newest version to include, for example `(4, 5)`.
+    pub version: (u8, u8),
+    /// The oldest version that the bindings will be used with, if it differs from `version`.
+    ///
+    /// Everything up to `version` is still included, but commands and enums introduced after
+    /// `min_version` may not be available at runtime.
+    pub min_version: Option<(u8, u8)>,
 }
 
-trait Parse: Sized + Iterator<Item = ParseEvent> {
-    fn parse(mut self, filter: &Filter, require_feature: bool) -> Registry {
-        self.consume_start_element("registry");
+/// How a `Filter` selects an extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Selection {
+    /// The extension was requested by its exact name.
+    Exact,
+    /// The extension was matched by a wildcard pattern.
+    Pattern,
+    /// The extension was not requested, or was excluded.
+    None,
+}
+
+impl Filter {
+    pub fn new<'a, Exts>(
+        api: Api,
+        version: (u8, u8),
+        profile: Profile,
+        fallbacks: Fallbacks,
+        extensions: Exts,
+    ) -> Filter
+    where
+        Exts: AsRef<[&'a str]>,
+    {
+        Filter {
+            api,
+            fallbacks,
+            extensions: extensions.as_ref().iter().map(<&str>::to_string).collect(),
+            excluded_extensions: BTreeSet::new(),
+            version,
+            min_version: None,
+            profile,
+        }
+    }
+
+    /// Whether a `<require>` or `<remove>` element restricted to `api` and `profile` applies to
+    /// this filter. Unrestricted elements always apply.
+    fn applies_to(&self, api: Option<Api>, profile: Option<&str>) -> bool {
+        let api_matches = match api {
+            Some(api) => self.selects_api(api),
+            None => true,
+        };
+        let profile_matches = match profile {
+            Some(profile) => self.selects_profile(profile),
+            None => true,
+        };
+        api_matches && profile_matches
+    }
+
+    /// Whether the features and requirements of `api` apply to this filter.
+    fn selects_api(&self, api: Api) -> bool {
+        match self.api {
+            // `glcore` is the core profile of `gl`, so it shares its versions and requirements
+            Api::GlCore => api == Api::Gl,
+            _ => api == self.api,
+        }
+    }
+
+    /// Whether this filter selects the profile named by a `profile` attribute. Profiles that a
+    /// filter can't select, including ones added by future registries, never match.
+    fn selects_profile(&self, profile: &str) -> bool {
+        match self.api {
+            // GLES 1 bindings are for its `common` profile, rather than `common-lite`
+            Api::Gles1 => profile == "common",
+            Api::GlCore => profile == "core",
+            _ => profile_from_str(profile) == Ok(self.profile),
+        }
+    }
+
+    fn selects_extension(&self, name: &str) -> Selection {
+        if self
+            .excluded_extensions
+            .iter()
+            .any(|pattern| matches_pattern(pattern, name))
+        {
+            Selection::None
+        } else if self.extensions.contains(name) {
+            Selection::Exact
+        } else if self
+            .extensions
+            .iter()
+            .any(|pattern| is_pattern(pattern) && matches_pattern(pattern, name))
+        {
+            Selection::Pattern
+        } else {
+            Selection::None
+        }
+    }
+}
+
+/// Returns `true` if `src` contains any wildcards.
+pub fn is_pattern(src: &str) -> bool {
+    src.contains(['*', '?'])
+}
+
+/// Matches `name` against a pattern where `*` matches any sequence of characters, and `?` matches
+/// any single character.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let name = name.as_bytes();
+
+    // The position of the last `*` in the pattern, and the position in the name that it has
+    // matched up to, so that we can backtrack if the rest of the pattern fails to match.
+    let mut backtrack = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+trait Parse: Sized + Iterator<Item = Result<ParseEvent, RegistryError>> {
+    fn parse(mut self, filter: &Filter, require_feature: bool) -> Result<Registry, RegistryError> {
+        self.consume_start_element("registry")?;
 
         let mut enums = Vec::new();
         let mut cmds = Vec::new();
@@ -296,24 +602,34 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
         let mut extensions = Vec::new();
         let mut aliases = BTreeMap::new();
         let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+        let mut bitmask_enums = BTreeSet::new();
+        let mut type_decls = Vec::new();
 
-        while let Some(event) = self.next() {
-            match event {
+        loop {
+            match self.next_event()? {
                 // ignores
                 ParseEvent::Text(_) => (),
-                ParseEvent::Start(ref name, _) if name == "comment" => self.skip_to_end("comment"),
-                ParseEvent::Start(ref name, _) if name == "types" => self.skip_to_end("types"),
+                ParseEvent::Start(ref name, _) if name == "comment" => {
+                    self.skip_to_end("comment")?
+                },
+                ParseEvent::Start(ref name, _) if name == "types" => {
+                    type_decls.extend(self.consume_types()?);
+                },
 
                 // add group namespace
                 ParseEvent::Start(ref name, _) if name == "groups" => {
-                    groups.extend(self.consume_groups(filter.api));
+                    groups.extend(self.consume_groups(filter.api)?);
                 },
 
                 // add enum namespace
                 ParseEvent::Start(ref name, ref attributes) if name == "enums" => {
-                    enums.extend(self.consume_enums(filter.api));
                     let enums_group = get_attribute(&attributes, "group");
                     let enums_type = get_attribute(&attributes, "type");
+                    let new_enums = self.consume_enums(filter.api, &mut groups, &enums_type)?;
+                    if enums_type.as_ref().map(String::as_ref) == Some("bitmask") {
+                        bitmask_enums.extend(new_enums.iter().map(|e| e.ident.clone()));
+                    }
+                    enums.extend(new_enums);
                     if let Some(group) = enums_group.and_then(|name| groups.get_mut(&name)) {
                         group.enums_type = enums_type;
                     }
@@ -321,23 +637,23 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
 
                 // add command namespace
                 ParseEvent::Start(ref name, _) if name == "commands" => {
-                    let (new_cmds, new_aliases) = self.consume_cmds(filter.api);
+                    let (new_cmds, new_aliases) = self.consume_cmds(filter.api)?;
                     cmds.extend(new_cmds);
                     merge_map(&mut aliases, new_aliases);
                 },
 
                 ParseEvent::Start(ref name, ref attributes) if name == "feature" => {
                     debug!("Parsing feature: {:?}", attributes);
-                    features.push(Feature::convert(&mut self, &attributes));
+                    features.push(Feature::convert(&mut self, &attributes)?);
                 },
 
                 ParseEvent::Start(ref name, _) if name == "extensions" => loop {
-                    match self.next().unwrap() {
+                    match self.next_event()? {
                         ParseEvent::Start(ref name, ref attributes) if name == "extension" => {
-                            extensions.push(Extension::convert(&mut self, &attributes));
+                            extensions.push(Extension::convert(&mut self, &attributes)?);
                         },
                         ParseEvent::End(ref name) if name == "extensions" => break,
-                        event => panic!("Unexpected message {:?}", event),
+                        event => return unexpected("<extension> or </extensions>", &event),
                     }
                 },
 
@@ -345,25 +661,56 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
                 ParseEvent::End(ref name) if name == "registry" => break,
 
                 // error handling
-                event => panic!("Expected </registry>, found: {:?}", event),
+                event => return unexpected("</registry>", &event),
+            }
+        }
+
+        // groups that are only listed in `<groups>` don't say whether they are bitmasks, but their
+        // enums are defined in bitmask namespaces
+        for group in groups.values_mut() {
+            if group.enums_type.is_none() && group.enums.iter().any(|e| bitmask_enums.contains(e)) {
+                group.enums_type = Some("bitmask".to_string());
             }
         }
 
-        let mut desired_enums = BTreeSet::new();
-        let mut desired_cmds = BTreeSet::new();
+        let types = parse_types(type_decls, filter, &enums)?;
+        classes::infer(&mut cmds);
+
+        let min_version = filter.min_version.unwrap_or(filter.version);
+        if min_version > filter.version {
+            return Err(RegistryError::InvalidVersionRange {
+                min: min_version,
+                max: filter.version,
+            });
+        }
+
+        // the names of the desired enums and commands, along with why they are desired
+        let mut desired_enums = BTreeMap::new();
+        let mut desired_cmds = BTreeMap::new();
 
         // find the features we want
-        let mut found_feature = false;
         for feature in &features {
-            // XXX: verify that the string comparison with <= actually works as desired
-            if feature.api == filter.api && feature.number <= filter.version {
+            if filter.selects_api(feature.api) && feature.number <= filter.version {
                 for require in &feature.requires {
-                    desired_enums.extend(require.enums.iter().map(|x| x.clone()));
-                    desired_cmds.extend(require.commands.iter().map(|x| x.clone()));
+                    if !filter.applies_to(require.api, require.profile.as_deref()) {
+                        continue;
+                    }
+                    for enm in &require.enums {
+                        desired_enums
+                            .entry(enm.clone())
+                            .or_insert_with(Provenance::default)
+                            .add_feature(feature.number);
+                    }
+                    for cmd in &require.commands {
+                        desired_cmds
+                            .entry(cmd.clone())
+                            .or_insert_with(Provenance::default)
+                            .add_feature(feature.number);
+                    }
                 }
 
                 for remove in &feature.removes {
-                    if remove.profile == filter.profile {
+                    if filter.applies_to(remove.api, remove.profile.as_deref()) {
                         for enm in &remove.enums {
                             debug!("Removing {}", enm);
                             desired_enums.remove(enm);
@@ -372,91 +719,153 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
                             debug!("Removing {}", cmd);
                             desired_cmds.remove(cmd);
                         }
+                    } else if filter.applies_to(remove.api, None) {
+                        // removed from another profile, so keep track of that
+                        let profile = remove
+                            .profile
+                            .as_ref()
+                            .and_then(|profile| profile_from_str(profile).ok());
+                        for enm in &remove.enums {
+                            if let Some(provenance) = desired_enums.get_mut(enm) {
+                                provenance.removed_in_profile = profile;
+                            }
+                        }
+                        for cmd in &remove.commands {
+                            if let Some(provenance) = desired_cmds.get_mut(cmd) {
+                                provenance.removed_in_profile = profile;
+                            }
+                        }
                     }
                 }
             }
-            if feature.number == filter.version {
-                found_feature = true;
-            }
         }
 
+        // versions that only other APIs have, such as 1.1 for GLES 1, use the newest feature of the
+        // API before them
+        let found_feature = features.iter().any(|f| f.number == filter.version)
+            && features
+                .iter()
+                .any(|f| filter.selects_api(f.api) && f.number <= filter.version);
         if !found_feature && require_feature {
-            panic!("Did not find version {} in the registry", filter.version);
+            return Err(RegistryError::VersionNotFound {
+                api: filter.api,
+                version: filter.version,
+            });
         }
 
+        let mut found_extensions = BTreeSet::new();
         for extension in &extensions {
-            if filter.extensions.contains(&extension.name) {
-                if !extension.supported.contains(&filter.api) {
-                    panic!(
-                        "Requested {}, which doesn't support the {} API",
-                        extension.name, filter.api
-                    );
+            let supported = extension.supported.contains(&filter.api);
+            match filter.selects_extension(&extension.name) {
+                Selection::Exact if !supported => {
+                    return Err(RegistryError::UnsupportedExtension {
+                        extension: extension.name.clone(),
+                        api: filter.api,
+                    });
+                },
+                Selection::Pattern if !supported => continue,
+                Selection::None => continue,
+                Selection::Exact | Selection::Pattern => {},
+            }
+            for require in &extension.requires {
+                if !filter.applies_to(require.api, require.profile.as_deref()) {
+                    continue;
+                }
+                for enm in &require.enums {
+                    desired_enums
+                        .entry(enm.clone())
+                        .or_insert_with(Provenance::default)
+                        .add_extension(&extension.name);
                 }
-                for require in &extension.requires {
-                    desired_enums.extend(require.enums.iter().map(|x| x.clone()));
-                    desired_cmds.extend(require.commands.iter().map(|x| x.clone()));
+                for cmd in &require.commands {
+                    desired_cmds
+                        .entry(cmd.clone())
+                        .or_insert_with(Provenance::default)
+                        .add_extension(&extension.name);
                 }
             }
+            found_extensions.insert(extension.name.clone());
         }
 
-        let is_desired_enum = |e: &Enum| {
-            desired_enums.contains(&("GL_".to_string() + &e.ident))
-                || desired_enums.contains(&("WGL_".to_string() + &e.ident))
-                || desired_enums.contains(&("GLX_".to_string() + &e.ident))
-                || desired_enums.contains(&("EGL_".to_string() + &e.ident))
+        let desired_enum = |mut e: Enum| {
+            let provenance =
+                find_desired(&desired_enums, &["GL_", "WGL_", "GLX_", "EGL_"], &e.ident)?;
+            e.introduced_in = provenance.introduced_in;
+            e.required_by_extensions = provenance.required_by_extensions.clone();
+            e.removed_in_profile = provenance.removed_in_profile;
+            Some(e)
         };
 
-        let is_desired_cmd = |c: &Cmd| {
-            desired_cmds.contains(&("gl".to_string() + &c.proto.ident))
-                || desired_cmds.contains(&("wgl".to_string() + &c.proto.ident))
-                || desired_cmds.contains(&("glX".to_string() + &c.proto.ident))
-                || desired_cmds.contains(&("egl".to_string() + &c.proto.ident))
+        let desired_cmd = |mut c: Cmd| {
+            let provenance =
+                find_desired(&desired_cmds, &["gl", "wgl", "glX", "egl"], &c.proto.ident)?;
+            c.introduced_in = provenance.introduced_in;
+            c.required_by_extensions = provenance.required_by_extensions.clone();
+            c.removed_in_profile = provenance.removed_in_profile;
+            Some(c)
         };
 
-        Registry {
+        Ok(Registry {
             api: filter.api,
-            enums: enums.into_iter().filter(is_desired_enum).collect(),
-            cmds: cmds.into_iter().filter(is_desired_cmd).collect(),
+            version: filter.version,
+            min_version,
+            enums: enums.into_iter().filter_map(desired_enum).collect(),
+            cmds: cmds.into_iter().filter_map(desired_cmd).collect(),
             aliases: if filter.fallbacks == Fallbacks::None {
                 BTreeMap::new()
             } else {
                 aliases
             },
             groups,
+            extensions: found_extensions,
+            types,
+        })
+    }
+
+    fn next_event(&mut self) -> Result<ParseEvent, RegistryError> {
+        match self.next() {
+            Some(event) => event,
+            None => Err(RegistryError::UnexpectedEof),
         }
     }
 
-    fn consume_characters(&mut self) -> String {
-        match self.next().unwrap() {
-            ParseEvent::Text(ch) => ch,
-            event => panic!("Expected characters, found: {:?}", event),
+    fn consume_characters(&mut self) -> Result<String, RegistryError> {
+        match self.next_event()? {
+            ParseEvent::Text(ch) => Ok(ch),
+            event => unexpected("characters", &event),
         }
     }
 
-    fn consume_start_element(&mut self, expected_name: &str) -> Vec<Attribute> {
-        match self.next().unwrap() {
+    fn consume_start_element(
+        &mut self,
+        expected_name: &str,
+    ) -> Result<Vec<Attribute>, RegistryError> {
+        match self.next_event()? {
             ParseEvent::Start(name, attributes) => {
                 if expected_name == name {
-                    attributes
+                    Ok(attributes)
                 } else {
-                    panic!("Expected <{}>, found: <{}>", expected_name, name)
+                    Err(RegistryError::UnexpectedElement {
+                        expected: format!("<{}>", expected_name),
+                        found: format!("<{}>", name),
+                    })
                 }
             },
-            event => panic!("Expected <{}>, found: {:?}", expected_name, event),
+            event => unexpected(format!("<{}>", expected_name), &event),
         }
     }
 
-    fn consume_end_element(&mut self, expected_name: &str) {
-        match self.next().unwrap() {
-            ParseEvent::End(ref name) if expected_name == name => (),
-            event => panic!("Expected </{}>, found: {:?}", expected_name, event),
+    fn consume_end_element(&mut self, expected_name: &str) -> Result<(), RegistryError> {
+        match self.next_event()? {
+            ParseEvent::End(ref name) if expected_name == name => Ok(()),
+            event => unexpected(format!("</{}>", expected_name), &event),
         }
     }
 
-    fn skip_to_end(&mut self, expected_name: &str) {
+    fn skip_to_end(&mut self, expected_name: &str) -> Result<(), RegistryError> {
         loop {
-            match self.next().unwrap() {
-                ParseEvent::End(ref name) if expected_name == name => break,
+            match self.next_event()? {
+                ParseEvent::End(ref name) if expected_name == name => return Ok(()),
                 _ => {},
             }
         }
@@ -467,14 +876,14 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
         one: &'a str,
         two: &'a str,
         end: &'a str,
-    ) -> (Vec<T>, Vec<U>) {
+    ) -> Result<(Vec<T>, Vec<U>), RegistryError> {
         debug!("consume_two: looking for {} and {} until {}", one, two, end);
 
         let mut ones = Vec::new();
         let mut twos = Vec::new();
 
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 ParseEvent::Start(ref name, ref attributes) => {
                     debug!("Found start element <{:?} {:?}>", name, attributes);
                     debug!("one and two are {} and {}", one, two);
@@ -482,7 +891,7 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
                     let n = name.clone();
 
                     if one == n {
-                        ones.push(FromXml::convert(self, &attributes));
+                        ones.push(FromXml::convert(self, &attributes)?);
                     } else if "type" == n {
                         // XXX: GL1.1 contains types, which we never care about anyway.
                         // Make sure consume_two doesn't get used for things which *do*
@@ -490,9 +899,12 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
                         warn!("Ignoring type!");
                         continue;
                     } else if two == n {
-                        twos.push(FromXml::convert(self, &attributes));
+                        twos.push(FromXml::convert(self, &attributes)?);
                     } else {
-                        panic!("Unexpected element: <{:?} {:?}>", n, &attributes);
+                        return Err(RegistryError::UnexpectedElement {
+                            expected: format!("<{}> or <{}> in <{}>", one, two, end),
+                            found: format!("<{}>", n),
+                        });
                     }
                 },
                 ParseEvent::End(ref name) => {
@@ -507,44 +919,110 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
                         warn!("Ignoring type!");
                         continue;
                     } else if end == name {
-                        return (ones, twos);
+                        return Ok((ones, twos));
                     } else {
-                        panic!("Unexpected end element {:?}", name);
+                        return Err(RegistryError::UnexpectedElement {
+                            expected: format!("</{}>", end),
+                            found: format!("</{}>", name),
+                        });
+                    }
+                },
+                event => return unexpected(format!("</{}>", end), &event),
+            }
+        }
+    }
+
+    fn consume_types(&mut self) -> Result<Vec<TypeDecl>, RegistryError> {
+        let mut decls = Vec::new();
+        loop {
+            match self.next_event()? {
+                ParseEvent::Start(ref name, ref attributes) if name == "type" => {
+                    let (api, _) = get_restrictions(attributes)?;
+                    let mut decl = TypeDecl {
+                        api,
+                        name: get_attribute(attributes, "name"),
+                        category: get_attribute(attributes, "category"),
+                        text: String::new(),
+                    };
+                    loop {
+                        match self.next_event()? {
+                            ParseEvent::Text(text) => decl.text.push_str(&text),
+                            ParseEvent::Start(ref name, _) if name == "name" => {
+                                let text = self.consume_characters()?;
+                                self.consume_end_element("name")?;
+                                decl.text.push_str(&format!(" {} ", text));
+                                if decl.name.is_none() {
+                                    decl.name = Some(text);
+                                }
+                            },
+                            // `<apientry/>` stands for the calling convention of the platform
+                            ParseEvent::Start(ref name, _) if name == "apientry" => {
+                                decl.text.push_str(" APIENTRY ");
+                            },
+                            ParseEvent::End(ref name) if name == "type" => break,
+                            // whitespace between elements is dropped, so keep the tokens apart
+                            ParseEvent::Start(_, _) | ParseEvent::End(_) => decl.text.push(' '),
+                        }
                     }
+                    decls.push(decl);
                 },
-                event => panic!("Unexpected message {:?}", event),
+                ParseEvent::End(ref name) if name == "types" => break,
+                event => return unexpected("<type> or </types>", &event),
             }
         }
+        Ok(decls)
     }
 
-    fn consume_enums(&mut self, api: Api) -> Vec<Enum> {
+    fn consume_enums(
+        &mut self,
+        api: Api,
+        groups: &mut BTreeMap<String, Group>,
+        enums_type: &Option<String>,
+    ) -> Result<Vec<Enum>, RegistryError> {
         let mut enums = Vec::new();
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 // ignores
                 ParseEvent::Text(_) => {},
-                ParseEvent::Start(ref name, _) if name == "unused" => self.skip_to_end("unused"),
+                ParseEvent::Start(ref name, _) if name == "unused" => self.skip_to_end("unused")?,
 
                 // add enum definition
                 ParseEvent::Start(ref name, ref attributes) if name == "enum" => {
-                    enums.push(self.consume_enum(api, attributes));
+                    let enm = self.consume_enum(api, attributes)?;
+                    // newer registries list the groups on each enum, instead of in `<groups>`
+                    if let Some(names) = get_attribute(attributes, "group") {
+                        for name in names.split(',') {
+                            let group = groups.entry(name.to_string()).or_insert_with(|| Group {
+                                ident: name.to_string(),
+                                enums_type: None,
+                                enums: Vec::new(),
+                            });
+                            if enums_type.is_some() {
+                                group.enums_type = enums_type.clone();
+                            }
+                            if !group.enums.contains(&enm.ident) {
+                                group.enums.push(enm.ident.clone());
+                            }
+                        }
+                    }
+                    enums.push(enm);
                 },
 
                 // finished building the namespace
                 ParseEvent::End(ref name) if name == "enums" => break,
                 // error handling
-                event => panic!("Expected </enums>, found: {:?}", event),
+                event => return unexpected("</enums>", &event),
             }
         }
-        enums
+        Ok(enums)
     }
 
-    fn consume_enum(&mut self, api: Api, attributes: &[Attribute]) -> Enum {
-        let ident = trim_enum_prefix(&get_attribute(&attributes, "name").unwrap(), api).to_string();
-        let value = get_attribute(&attributes, "value").unwrap();
+    fn consume_enum(&mut self, api: Api, attributes: &[Attribute]) -> Result<Enum, RegistryError> {
+        let ident = trim_enum_prefix(&require_attribute(&attributes, "enum", "name")?, api);
+        let value = require_attribute(&attributes, "enum", "value")?;
         let alias = get_attribute(&attributes, "alias");
         let ty = get_attribute(&attributes, "type");
-        self.consume_end_element("enum");
+        self.consume_end_element("enum")?;
 
         match api {
             Api::Egl => make_egl_enum(ident, ty, value, alias),
@@ -552,50 +1030,50 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
         }
     }
 
-    fn consume_groups(&mut self, api: Api) -> BTreeMap<String, Group> {
+    fn consume_groups(&mut self, api: Api) -> Result<BTreeMap<String, Group>, RegistryError> {
         let mut groups = BTreeMap::new();
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 ParseEvent::Start(ref name, ref attributes) if name == "group" => {
-                    let ident = get_attribute(&attributes, "name").unwrap();
+                    let ident = require_attribute(&attributes, "group", "name")?;
                     let group = Group {
                         ident: ident.clone(),
                         enums_type: None,
-                        enums: self.consume_group_enums(api),
+                        enums: self.consume_group_enums(api)?,
                     };
                     groups.insert(ident, group);
                 },
                 ParseEvent::End(ref name) if name == "groups" => break,
-                event => panic!("Expected </groups>, found: {:?}", event),
+                event => return unexpected("</groups>", &event),
             }
         }
-        groups
+        Ok(groups)
     }
 
-    fn consume_group_enums(&mut self, api: Api) -> Vec<String> {
+    fn consume_group_enums(&mut self, api: Api) -> Result<Vec<String>, RegistryError> {
         let mut enums = Vec::new();
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 ParseEvent::Start(ref name, ref attributes) if name == "enum" => {
-                    let enum_name = get_attribute(&attributes, "name");
-                    enums.push(trim_enum_prefix(&enum_name.unwrap(), api));
-                    self.consume_end_element("enum");
+                    let enum_name = require_attribute(&attributes, "enum", "name")?;
+                    enums.push(trim_enum_prefix(&enum_name, api));
+                    self.consume_end_element("enum")?;
                 },
                 ParseEvent::End(ref name) if name == "group" => break,
-                event => panic!("Expected </group>, found: {:?}", event),
+                event => return unexpected("</group>", &event),
             }
         }
-        enums
+        Ok(enums)
     }
 
-    fn consume_cmds(&mut self, api: Api) -> (Vec<Cmd>, BTreeMap<String, Vec<String>>) {
+    fn consume_cmds(&mut self, api: Api) -> Result<(Vec<Cmd>, Aliases), RegistryError> {
         let mut cmds = Vec::new();
-        let mut aliases: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut aliases: Aliases = BTreeMap::new();
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 // add command definition
                 ParseEvent::Start(ref name, _) if name == "command" => {
-                    let new = self.consume_cmd(api);
+                    let new = self.consume_cmd(api)?;
                     if let Some(ref v) = new.alias {
                         match aliases.entry(v.clone()) {
                             Entry::Occupied(mut ent) => {
@@ -611,16 +1089,17 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
                 // finished building the namespace
                 ParseEvent::End(ref name) if name == "commands" => break,
                 // error handling
-                event => panic!("Expected </commands>, found: {:?}", event),
+                event => return unexpected("</commands>", &event),
             }
         }
-        (cmds, aliases)
+        Ok((cmds, aliases))
     }
 
-    fn consume_cmd(&mut self, api: Api) -> Cmd {
+    fn consume_cmd(&mut self, api: Api) -> Result<Cmd, RegistryError> {
         // consume command prototype
-        self.consume_start_element("proto");
-        let mut proto = self.consume_binding("proto", &[]);
+        let attributes = self.consume_start_element("proto")?;
+        let mut proto = self.consume_binding("proto", &attributes, None)?;
+        let command = proto.ident.clone();
         proto.ident = trim_cmd_prefix(&proto.ident, api).to_string();
 
         let mut params = Vec::new();
@@ -628,75 +1107,181 @@ trait Parse: Sized + Iterator<Item = ParseEvent> {
         let mut vecequiv = None;
         let mut glx = None;
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 ParseEvent::Start(ref name, ref attributes) if name == "param" => {
-                    params.push(self.consume_binding("param", attributes));
+                    params.push(self.consume_binding("param", attributes, Some(&command))?);
                 },
                 ParseEvent::Start(ref name, ref attributes) if name == "alias" => {
                     alias = get_attribute(&attributes, "name");
                     alias = alias.map(|t| trim_cmd_prefix(&t, api).to_string());
-                    self.consume_end_element("alias");
+                    self.consume_end_element("alias")?;
                 },
                 ParseEvent::Start(ref name, ref attributes) if name == "vecequiv" => {
                     vecequiv = get_attribute(&attributes, "vecequiv");
-                    self.consume_end_element("vecequiv");
+                    self.consume_end_element("vecequiv")?;
                 },
                 ParseEvent::Start(ref name, ref attributes) if name == "glx" => {
                     glx = Some(GlxOpcode {
-                        opcode: get_attribute(&attributes, "opcode").unwrap(),
+                        opcode: require_attribute(&attributes, "glx", "opcode")?,
                         name: get_attribute(&attributes, "name"),
                     });
-                    self.consume_end_element("glx");
+                    self.consume_end_element("glx")?;
                 },
                 ParseEvent::End(ref name) if name == "command" => break,
-                event => panic!("Expected </command>, found: {:?}", event),
+                event => return unexpected(format!("</command> for {}", command), &event),
             }
         }
 
-        Cmd {
+        Ok(Cmd {
             proto: proto,
             params: params,
             alias: alias,
             vecequiv: vecequiv,
             glx: glx,
-        }
+            introduced_in: None,
+            required_by_extensions: Vec::new(),
+            removed_in_profile: None,
+        })
     }
 
-    fn consume_binding(&mut self, outside_tag: &str, attributes: &[Attribute]) -> Binding {
+    /// Consumes a `<proto>` or `<param>` element. `command` is the name of the command that the
+    /// parameter belongs to, or `None` when consuming the prototype itself.
+    fn consume_binding(
+        &mut self,
+        outside_tag: &str,
+        attributes: &[Attribute],
+        command: Option<&str>,
+    ) -> Result<Binding, RegistryError> {
         // consume type
         let mut ty = String::new();
+        let mut in_ptype = false;
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
+                ParseEvent::Text(ref text) if in_ptype => {
+                    let name = text.trim();
+                    match MISSPELLED_TYPES.iter().find(|&&(wrong, _)| wrong == name) {
+                        Some(&(_, right)) => ty.push_str(right),
+                        None => ty.push_str(name),
+                    }
+                },
                 ParseEvent::Text(text) => ty.push_str(&text),
-                ParseEvent::Start(ref name, _) if name == "ptype" => (),
-                ParseEvent::End(ref name) if name == "ptype" => (),
+                ParseEvent::Start(ref name, _) if name == "ptype" => {
+                    in_ptype = true;
+                    ty.push(' ');
+                },
+                ParseEvent::End(ref name) if name == "ptype" => {
+                    in_ptype = false;
+                    ty.push(' ');
+                },
                 ParseEvent::Start(ref name, _) if name == "name" => break,
-                event => panic!("Expected binding, found: {:?}", event),
+                event => return unexpected(format!("<name> in <{}>", outside_tag), &event),
             }
         }
 
         // consume identifier
-        let ident = underscore_keyword(self.consume_characters());
-        self.consume_end_element("name");
+        let ident = underscore_keyword(self.consume_characters()?);
+        self.consume_end_element("name")?;
 
-        // consume the type suffix
+        // consume the type suffix, such as the length of an array
+        ty.push(' ');
         loop {
-            match self.next().unwrap() {
+            match self.next_event()? {
                 ParseEvent::Text(text) => ty.push_str(&text),
                 ParseEvent::End(ref name) if name == outside_tag => break,
-                event => panic!("Expected binding, found: {:?}", event),
+                event => return unexpected(format!("</{}>", outside_tag), &event),
             }
         }
 
-        Binding {
+        let c_ty = match ctype::parse_declaration(&ty) {
+            Ok(c_ty) => c_ty,
+            Err(_) => {
+                return Err(RegistryError::UnknownType {
+                    command: command.unwrap_or(&ident).to_string(),
+                    param: command.map(|_| ident.clone()),
+                    ty: ty.split_whitespace().collect::<Vec<_>>().join(" "),
+                })
+            },
+        };
+
+        Ok(Binding {
             ident: ident,
-            ty: to_rust_ty(ty),
-            group: get_attribute(&attributes, "group"),
+            ty: Cow::Owned(c_ty.to_rust_binding(&|name: &str| {
+                ctype::name_to_rust(name, "types::", "__gl_imports::raw::")
+            })),
+            c_ty,
+            group: get_attribute(attributes, "group"),
+            len: get_attribute(attributes, "len").map(|len| len_from_str(&len)),
+            class: get_attribute(attributes, "class"),
+        })
+    }
+}
+
+/// Parses the `<type>` elements that apply to the API of `filter`. `enums` are used to look up
+/// array lengths.
+fn parse_types(
+    decls: Vec<TypeDecl>,
+    filter: &Filter,
+    enums: &[Enum],
+) -> Result<BTreeMap<String, TypeDef>, RegistryError> {
+    let len_of = |name: &str| {
+        let ident = trim_enum_prefix(name, filter.api);
+        let enm = enums.iter().find(|enm| enm.ident == ident)?;
+        if enm.value.starts_with("0x") {
+            usize::from_str_radix(&enm.value[2..], 16).ok()
+        } else {
+            enm.value.parse().ok()
+        }
+    };
+
+    let mut types = BTreeMap::new();
+    for decl in decls {
+        if decl.category.as_ref().map(String::as_ref) == Some("include")
+            || decl.text.contains("#include")
+            || !filter.applies_to(decl.api, None)
+        {
+            continue;
+        }
+
+        let text = decl.text.trim();
+        let (name, def) = if text.is_empty() || text.starts_with('#') {
+            // defined by a platform header, or by the preprocessor
+            match decl.name {
+                Some(name) => (name, TypeDef::External),
+                None => continue,
+            }
+        } else {
+            match ctype::parse_type(text, &len_of) {
+                Ok(result) => result,
+                Err(err) => match decl.name {
+                    Some(name) => {
+                        warn!("Could not parse the declaration of `{}`: {}", name, err);
+                        (name, TypeDef::External)
+                    },
+                    None => {
+                        return Err(RegistryError::InvalidType {
+                            decl: text.to_string(),
+                            message: err,
+                        })
+                    },
+                },
+            }
+        };
+
+        // builtin types, such as `int32_t` in glx.xml, are converted directly
+        if def == TypeDef::External && ctype::builtin(&name).is_some() {
+            continue;
+        }
+        // types for a specific API replace the general ones
+        if decl.api.is_some() {
+            types.insert(name, def);
+        } else {
+            types.entry(name).or_insert(def);
         }
     }
+    Ok(types)
 }
 
-impl<T> Parse for T where T: Sized + Iterator<Item = ParseEvent> {}
+impl<T> Parse for T where T: Sized + Iterator<Item = Result<ParseEvent, RegistryError>> {}
 
 fn get_attribute(attribs: &[Attribute], key: &str) -> Option<String> {
     attribs
@@ -705,398 +1290,137 @@ fn get_attribute(attribs: &[Attribute], key: &str) -> Option<String> {
         .map(|attrib| attrib.value.clone())
 }
 
-trait FromXml {
-    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Self;
+fn require_attribute(
+    attribs: &[Attribute],
+    element: &str,
+    key: &str,
+) -> Result<String, RegistryError> {
+    get_attribute(attribs, key).ok_or_else(|| RegistryError::MissingAttribute {
+        element: element.to_string(),
+        attribute: key.to_string(),
+    })
+}
+
+trait FromXml: Sized {
+    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Result<Self, RegistryError>;
+}
+
+/// Parses the optional `api` and `profile` attributes that restrict a `<require>` or `<remove>`
+/// element.
+fn get_restrictions(attribs: &[Attribute]) -> Result<(Option<Api>, Option<String>), RegistryError> {
+    let api = match get_attribute(attribs, "api") {
+        Some(api) => match api_from_str(&api) {
+            Ok(Some(api)) => Some(api),
+            Ok(None) | Err(()) => return Err(RegistryError::UnknownApi(api)),
+        },
+        None => None,
+    };
+    Ok((api, get_attribute(attribs, "profile")))
 }
 
 impl FromXml for Require {
-    fn convert<P: Parse>(parser: &mut P, _: &[Attribute]) -> Require {
+    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Result<Require, RegistryError> {
         debug!("Doing a FromXml on Require");
-        let (enums, commands) = parser.consume_two("enum", "command", "require");
-        Require {
-            enums: enums,
-            commands: commands,
-        }
+        let (api, profile) = get_restrictions(a)?;
+        let (enums, commands) = parser.consume_two("enum", "command", "require")?;
+        Ok(Require {
+            api,
+            profile,
+            enums,
+            commands,
+        })
     }
 }
 
 impl FromXml for Remove {
-    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Remove {
+    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Result<Remove, RegistryError> {
         debug!("Doing a FromXml on Remove");
-        let profile = get_attribute(a, "profile").unwrap();
-        let profile = profile_from_str(&profile).unwrap();
-        let (enums, commands) = parser.consume_two("enum", "command", "remove");
-
-        Remove {
-            profile: profile,
-            enums: enums,
-            commands: commands,
-        }
+        let (api, profile) = get_restrictions(a)?;
+        let (enums, commands) = parser.consume_two("enum", "command", "remove")?;
+
+        Ok(Remove {
+            api,
+            profile,
+            enums,
+            commands,
+        })
     }
 }
 
 impl FromXml for Feature {
-    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Feature {
+    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Result<Feature, RegistryError> {
         debug!("Doing a FromXml on Feature");
-        let api = get_attribute(a, "api").unwrap();
-        let api = api_from_str(&api).unwrap().unwrap();
-        let name = get_attribute(a, "name").unwrap();
-        let number = get_attribute(a, "number").unwrap();
+        let api = require_attribute(a, "feature", "api")?;
+        let api = match api_from_str(&api) {
+            Ok(Some(api)) => api,
+            Ok(None) | Err(()) => return Err(RegistryError::UnknownApi(api)),
+        };
+        let name = require_attribute(a, "feature", "name")?;
+        let number = require_attribute(a, "feature", "number")?;
+        let number =
+            version_from_str(&number).map_err(|()| RegistryError::InvalidVersion(number))?;
 
-        debug!("Found api = {}, name = {}, number = {}", api, name, number);
+        debug!(
+            "Found api = {}, name = {}, number = {:?}",
+            api, name, number
+        );
 
-        let (require, remove) = parser.consume_two("require", "remove", "feature");
+        let (require, remove) = parser.consume_two("require", "remove", "feature")?;
 
-        Feature {
+        Ok(Feature {
             api: api,
             name: name,
             number: number,
             requires: require,
             removes: remove,
-        }
+        })
     }
 }
 
 impl FromXml for Extension {
-    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Extension {
+    fn convert<P: Parse>(parser: &mut P, a: &[Attribute]) -> Result<Extension, RegistryError> {
         debug!("Doing a FromXml on Extension");
-        let name = get_attribute(a, "name").unwrap();
-        let supported = get_attribute(a, "supported")
-            .unwrap()
-            .split('|')
-            .filter_map(|api| {
-                api_from_str(api).unwrap_or_else(|()| panic!("unsupported API `{}`", api))
-            })
-            .collect::<Vec<_>>();
+        let name = require_attribute(a, "extension", "name")?;
+        let mut supported = Vec::new();
+        for api in require_attribute(a, "extension", "supported")?.split('|') {
+            match api_from_str(api) {
+                Ok(Some(api)) => supported.push(api),
+                Ok(None) => {},
+                Err(()) => return Err(RegistryError::UnknownApi(api.to_string())),
+            }
+        }
         let mut require = Vec::new();
         loop {
-            match parser.next().unwrap() {
+            match parser.next_event()? {
                 ParseEvent::Start(ref name, ref attributes) if name == "require" => {
-                    require.push(FromXml::convert(parser, &attributes));
+                    require.push(FromXml::convert(parser, &attributes)?);
                 },
                 ParseEvent::End(ref name) if name == "extension" => break,
-                event => panic!("Unexpected message {:?}", event),
+                event => {
+                    return unexpected(format!("<require> or </extension> in {}", name), &event)
+                },
             }
         }
 
-        Extension {
+        Ok(Extension {
             name: name,
             supported: supported,
             requires: require,
-        }
+        })
     }
 }
 
 impl FromXml for String {
-    fn convert<P: Parse>(_: &mut P, a: &[Attribute]) -> String {
-        get_attribute(a, "name").unwrap()
+    fn convert<P: Parse>(_: &mut P, a: &[Attribute]) -> Result<String, RegistryError> {
+        require_attribute(a, "enum or command", "name")
     }
 }
 
-/// Converts a C style type definition to the Rust equivalent
-pub fn to_rust_ty<T: AsRef<str>>(ty: T) -> Cow<'static, str> {
-    let ty = match ty.as_ref().trim() {
-        // gl.xml types
-        "GLDEBUGPROC" => "types::GLDEBUGPROC",
-        "GLDEBUGPROCAMD" => "types::GLDEBUGPROCAMD",
-        "GLDEBUGPROCARB" => "types::GLDEBUGPROCARB",
-        "GLDEBUGPROCKHR" => "types::GLDEBUGPROCKHR",
-        "GLbitfield" => "types::GLbitfield",
-        "GLboolean" => "types::GLboolean",
-        "GLbyte" => "types::GLbyte",
-        "GLclampd" => "types::GLclampd",
-        "GLclampf" => "types::GLclampf",
-        "GLclampx" => "types::GLclampx",
-        "GLdouble" => "types::GLdouble",
-        "GLeglImageOES" => "types::GLeglImageOES",
-        "GLenum" => "types::GLenum",
-        "GLfixed" => "types::GLfixed",
-        "GLfloat" => "types::GLfloat",
-        "GLhalfNV" => "types::GLhalfNV",
-        "GLhandleARB" => "types::GLhandleARB",
-        "GLint" => "types::GLint",
-        "GLint64" => "types::GLint64",
-        "GLint64EXT" => "types::GLint64EXT",
-        "GLintptr" => "types::GLintptr",
-        "GLintptrARB" => "types::GLintptrARB",
-        "GLshort" => "types::GLshort",
-        "GLsizei" => "types::GLsizei",
-        "GLsizeiptr" => "types::GLsizeiptr",
-        "GLsizeiptrARB" => "types::GLsizeiptrARB",
-        "GLsync" => "types::GLsync",
-        "GLubyte" => "types::GLubyte",
-        "GLuint" => "types::GLuint",
-        "GLuint64" => "types::GLuint64",
-        "GLuint64EXT" => "types::GLuint64EXT",
-        "GLushort" => "types::GLushort",
-        "GLvdpauSurfaceNV" => "types::GLvdpauSurfaceNV",
-        "void" => "()",
-        "GLboolean *" => "*mut types::GLboolean",
-        "GLchar *" => "*mut types::GLchar",
-        "const GLchar*" => "*const types::GLchar",
-        "GLcharARB *" => "*mut types::GLcharARB",
-        "GLdouble *" => "*mut types::GLdouble",
-        "GLenum *" => "*mut types::GLenum",
-        "GLfixed *" => "*mut types::GLfixed",
-        "GLfloat *" => "*mut types::GLfloat",
-        "GLhandleARB *" => "*mut types::GLhandleARB",
-        "GLint *" => "*mut types::GLint",
-        "GLint64 *" => "*mut types::GLint64",
-        "GLint64EXT *" => "*mut types::GLint64EXT",
-        "GLsizei *" => "*mut types::GLsizei",
-        "GLubyte *" => "*mut types::GLubyte",
-        "GLuint *" => "*mut types::GLuint",
-        "GLuint [2]" => "*mut [types::GLuint; 2]",
-        "GLuint64 *" => "*mut types::GLuint64",
-        "GLuint64EXT *" => "*mut types::GLuint64EXT",
-        "GLushort *" => "*mut types::GLushort",
-        "GLvoid *" => "*mut types::GLvoid",
-        "GLvoid **" => "*const *mut types::GLvoid",
-        "void *" => "*mut __gl_imports::raw::c_void",
-        "void **" => "*const *mut __gl_imports::raw::c_void",
-        "const GLboolean *" => "*const types::GLboolean",
-        "const GLbyte *" => "*const types::GLbyte",
-        "const GLchar *" => "*const types::GLchar",
-        "const GLcharARB *" => "*const types::GLcharARB",
-        "const GLclampf *" => "*const types::GLclampf",
-        "const GLdouble *" => "*const types::GLdouble",
-        "const GLenum *" => "*const types::GLenum",
-        "const GLfixed *" => "*const types::GLfixed",
-        "const GLfloat" => "types::GLfloat",
-        "const GLfloat *" => "*const types::GLfloat",
-        "const GLhalfNV *" => "*const types::GLhalfNV",
-        "const GLint *" => "*const types::GLint",
-        "const GLint*" => "*const types::GLint",
-        "const GLint64 *" => "*const types::GLint64",
-        "const GLint64EXT *" => "*const types::GLint64EXT",
-        "const GLintptr *" => "*const types::GLintptr",
-        "const GLshort *" => "*const types::GLshort",
-        "const GLsizei*" | "const GLsizei *" => "*const types::GLsizei",
-        "const GLsizeiptr *" => "*const types::GLsizeiptr",
-        "const GLubyte *" => "*const types::GLubyte",
-        "const GLuint *" => "*const types::GLuint",
-        "const GLuint64 *" => "*const types::GLuint64",
-        "const GLuint64EXT *" => "*const types::GLuint64EXT",
-        "const GLushort *" => "*const types::GLushort",
-        "const GLvdpauSurfaceNV *" => "*const types::GLvdpauSurfaceNV",
-        "const GLvoid *" => "*const types::GLvoid",
-        "const void*" | "const void *" => "*const __gl_imports::raw::c_void",
-        "const void **" => "*const *const __gl_imports::raw::c_void",
-        "const void *const*" => "*const *const __gl_imports::raw::c_void",
-        "const GLboolean **" => "*const *const types::GLboolean",
-        "const GLchar **" => "*const *const types::GLchar",
-        "const GLcharARB **" => "*const *const types::GLcharARB",
-        "const GLvoid **" => "*const *const types::GLvoid",
-        "const GLchar *const*" => "*const *const types::GLchar",
-        "const GLvoid *const*" => "*const *const types::GLvoid",
-        "struct _cl_context *" => "*const types::_cl_context",
-        "struct _cl_event *" => "*const types::_cl_event",
-        "GLuint[2]" => "[Gluint; 2]",
-
-        // glx.xml types
-        "Bool" => "types::Bool",
-        "Colormap" => "types::Colormap",
-        "DMbuffer" => "types::DMbuffer",
-        "Font" => "types::Font",
-        "GLXContext" => "types::GLXContext",
-        "GLXContextID" => "types::GLXContextID",
-        "GLXDrawable" => "types::GLXDrawable",
-        "GLXFBConfig" => "types::GLXFBConfig",
-        "GLXFBConfigSGIX" => "types::GLXFBConfigSGIX",
-        "GLXPbuffer" => "types::GLXPbuffer",
-        "GLXPbufferSGIX" => "types::GLXPbufferSGIX",
-        "GLXPixmap" => "types::GLXPixmap",
-        "GLXVideoCaptureDeviceNV" => "types::GLXVideoCaptureDeviceNV",
-        "GLXVideoDeviceNV" => "types::GLXVideoDeviceNV",
-        "GLXVideoSourceSGIX" => "types::GLXVideoSourceSGIX",
-        "GLXWindow" => "types::GLXWindow",
-        // "GLboolean"                 => "types::GLboolean",
-        // "GLenum"                    => "types::GLenum",
-        // "GLint"                     => "types::GLint",
-        // "GLsizei"                   => "types::GLsizei",
-        // "GLuint"                    => "types::GLuint",
-        "Pixmap" => "types::Pixmap",
-        "Status" => "types::Status",
-        "VLNode" => "types::VLNode",
-        "VLPath" => "types::VLPath",
-        "VLServer" => "types::VLServer",
-        "Window" => "types::Window",
-        "__GLXextFuncPtr" => "types::__GLXextFuncPtr",
-        "const GLXContext" => "const types::GLXContext",
-        "float" => "__gl_imports::raw::c_float",
-        "int" => "__gl_imports::raw::c_int",
-        "int64_t" => "i64",
-        "unsigned int" => "__gl_imports::raw::c_uint",
-        "unsigned long" => "__gl_imports::raw::c_ulong",
-        // "void "                     => "()",
-        "DMparams *" => "*mut types::DMparams",
-        "Display *" => "*mut types::Display",
-        "GLXFBConfig *" => "*mut types::GLXFBConfig",
-        "GLXFBConfigSGIX *" => "*mut types::GLXFBConfigSGIX",
-        "GLXHyperpipeConfigSGIX *" => "*mut types::GLXHyperpipeConfigSGIX",
-        "GLXHyperpipeNetworkSGIX *" => "*mut types::GLXHyperpipeNetworkSGIX",
-        "GLXVideoCaptureDeviceNV *" => "*mut types::GLXVideoCaptureDeviceNV",
-        "GLXVideoDeviceNV *" => "*mut types::GLXVideoDeviceNV",
-        // "GLuint *"                  => "*mut types::GLuint",
-        "XVisualInfo *" => "*mut types::XVisualInfo",
-        // "const GLubyte *"           => "*GLubyte",
-        "const char *" => "*const __gl_imports::raw::c_char",
-        "const int *" => "*const __gl_imports::raw::c_int",
-        // "const void *"              => "*const __gl_imports::raw::c_void",
-        "int *" => "*mut __gl_imports::raw::c_int",
-        "int32_t *" => "*mut i32",
-        "int64_t *" => "*mut i64",
-        "long *" => "*mut __gl_imports::raw::c_long",
-        "unsigned int *" => "*mut __gl_imports::raw::c_uint",
-        "unsigned long *" => "*mut __gl_imports::raw::c_ulong",
-        // "void *"                    => "*mut __gl_imports::raw::c_void",
-
-        // wgl.xml types
-        "BOOL" => "types::BOOL",
-        "DWORD" => "types::DWORD",
-        "FLOAT" => "types::FLOAT",
-        // "GLbitfield"                => "types::GLbitfield",
-        // "GLboolean"                 => "types::GLboolean",
-        // "GLenum"                    => "types::GLenum",
-        // "GLfloat"                   => "types::GLfloat",
-        // "GLint"                     => "types::GLint",
-        // "GLsizei"                   => "types::GLsizei",
-        // "GLuint"                    => "types::GLuint",
-        // "GLushort"                  => "types::GLushort",
-        "HANDLE" => "types::HANDLE",
-        "HDC" => "types::HDC",
-        "HENHMETAFILE" => "types::HENHMETAFILE",
-        "HGLRC" => "types::HGLRC",
-        "HGPUNV" => "types::HGPUNV",
-        "HPBUFFERARB" => "types::HPBUFFERARB",
-        "HPBUFFEREXT" => "types::HPBUFFEREXT",
-        "HPVIDEODEV" => "types::HPVIDEODEV",
-        "HVIDEOINPUTDEVICENV" => "types::HVIDEOINPUTDEVICENV",
-        "HVIDEOOUTPUTDEVICENV" => "types::HVIDEOOUTPUTDEVICENV",
-        "INT" => "types::INT",
-        "INT64" => "types::INT64",
-        "LPCSTR" => "types::LPCSTR",
-        "LPGLYPHMETRICSFLOAT" => "types::LPGLYPHMETRICSFLOAT",
-        "LPVOID" => "types::LPVOID",
-        "PGPU_DEVICE" => "types::PGPU_DEVICE",
-        "PROC" => "types::PROC",
-        "UINT" => "types::UINT",
-        "VOID" => "types::VOID",
-        // "int "                      => "__gl_imports::raw::c_int",
-        // "unsigned int "             => "__gl_imports::raw::c_uint",
-        // "void "                     => "()",
-        "BOOL *" => "*mut types::BOOL",
-        "DWORD *" => "*mut types::DWORD",
-        "FLOAT *" => "*mut types::FLOAT",
-        // "GLuint *"                  => "*mut types::GLuint",
-        "HANDLE *" => "*mut types::HANDLE",
-        "HGPUNV *" => "*mut types::HGPUNV",
-        "HPVIDEODEV *" => "*mut types::HPVIDEODEV",
-        "HVIDEOINPUTDEVICENV *" => "*mut types::HVIDEOINPUTDEVICENV",
-        "HVIDEOOUTPUTDEVICENV *" => "*mut types::HVIDEOOUTPUTDEVICENV",
-        "INT32 *" => "*mut types::INT32",
-        "INT64 *" => "*mut types::INT64",
-        "UINT *" => "*mut types::UINT",
-        "USHORT *" => "*mut types::USHORT",
-        "const COLORREF *" => "*const types::COLORREF",
-        "const DWORD *" => "*const types::DWORD",
-        "const FLOAT *" => "*const types::FLOAT",
-        // "const GLushort *"          => "*const types::GLushort",
-        "const HANDLE *" => "*const types::HANDLE",
-        "const HGPUNV *" => "*const types::HGPUNV",
-        "const LAYERPLANEDESCRIPTOR *" => "*const types::LAYERPLANEDESCRIPTOR",
-        "const LPVOID *" => "*const types::LPVOID",
-        "const PIXELFORMATDESCRIPTOR *" => "*const types::IXELFORMATDESCRIPTOR",
-        "const USHORT *" => "*const types::USHORT",
-        // "const char *"              => "*const __gl_imports::raw::c_char",
-        // "const int *"               => "*const __gl_imports::raw::c_int",
-        "float *" => "*mut __gl_imports::raw::c_float",
-        // "int *"                     => "*mut __gl_imports::raw::c_int",
-        // "unsigned long *"           => "*mut __gl_imports::raw::c_ulong",
-        // "void *"                    => "*mut __gl_imports::raw::c_void",
-
-        // elx.xml types
-        "khronos_utime_nanoseconds_t" => "types::khronos_utime_nanoseconds_t",
-        "khronos_uint64_t" => "types::khronos_uint64_t",
-        "khronos_ssize_t" => "types::khronos_ssize_t",
-        "EGLNativeDisplayType" => "types::EGLNativeDisplayType",
-        "EGLNativePixmapType" => "types::EGLNativePixmapType",
-        "EGLNativeWindowType" => "types::EGLNativeWindowType",
-        "EGLint" => "types::EGLint",
-        "EGLint *" => "*mut types::EGLint",
-        "const EGLint *" => "*const types::EGLint",
-        "NativeDisplayType" => "types::NativeDisplayType",
-        "NativePixmapType" => "types::NativePixmapType",
-        "NativeWindowType" => "types::NativeWindowType",
-        //"Bool"                      => "types::Bool",
-        "EGLBoolean" => "types::EGLBoolean",
-        "EGLenum" => "types::EGLenum",
-        "EGLAttribKHR" => "types::EGLAttribKHR",
-        "EGLAttrib" => "types::EGLAttrib",
-        "EGLAttrib *" => "*mut types::EGLAttrib",
-        "const EGLAttrib *" => "*const types::EGLAttrib",
-        "const EGLattrib *" => "*const types::EGLAttrib", // Due to a typo in khronos_api/api_angle/scripts/egl_angle_ext.xml - see brendanzab/gl-rs#491
-        "EGLConfig" => "types::EGLConfig",
-        "EGLConfig *" => "*mut types::EGLConfig",
-        "EGLContext" => "types::EGLContext",
-        "EGLDeviceEXT" => "types::EGLDeviceEXT",
-        "EGLDisplay" => "types::EGLDisplay",
-        "EGLSurface" => "types::EGLSurface",
-        "EGLClientBuffer" => "types::EGLClientBuffer",
-        "__eglMustCastToProperFunctionPointerType" => {
-            "types::__eglMustCastToProperFunctionPointerType"
-        },
-        "EGLImageKHR" => "types::EGLImageKHR",
-        "EGLImage" => "types::EGLImage",
-        "EGLOutputLayerEXT" => "types::EGLOutputLayerEXT",
-        "EGLOutputPortEXT" => "types::EGLOutputPortEXT",
-        "EGLSyncKHR" => "types::EGLSyncKHR",
-        "EGLSync" => "types::EGLSync",
-        "EGLTimeKHR" => "types::EGLTimeKHR",
-        "EGLTime" => "types::EGLTime",
-        "EGLSyncNV" => "types::EGLSyncNV",
-        "EGLTimeNV" => "types::EGLTimeNV",
-        "EGLuint64NV" => "types::EGLuint64NV",
-        "EGLStreamKHR" => "types::EGLStreamKHR",
-        "EGLuint64KHR" => "types::EGLuint64KHR",
-        "EGLNativeFileDescriptorKHR" => "types::EGLNativeFileDescriptorKHR",
-        "EGLsizeiANDROID" => "types::EGLsizeiANDROID",
-        "EGLSetBlobFuncANDROID" => "types::EGLSetBlobFuncANDROID",
-        "EGLGetBlobFuncANDROID" => "types::EGLGetBlobFuncANDROID",
-        "EGLClientPixmapHI" => "types::EGLClientPixmapHI",
-        "struct EGLClientPixmapHI *" => "*const types::EGLClientPixmapHI",
-        "const EGLAttribKHR *" => "*const types::EGLAttribKHR",
-        "const EGLuint64KHR *" => "*const types::EGLuint64KHR",
-        "EGLAttribKHR *" => "*mut types::EGLAttribKHR",
-        "EGLDeviceEXT *" => "*mut types::EGLDeviceEXT",
-        "EGLNativeDisplayType *" => "*mut types::EGLNativeDisplayType",
-        "EGLNativePixmapType *" => "*mut types::EGLNativePixmapType",
-        "EGLNativeWindowType *" => "*mut types::EGLNativeWindowType",
-        "EGLOutputLayerEXT *" => "*mut types::EGLOutputLayerEXT",
-        "EGLTimeKHR *" => "*mut types::EGLTimeKHR",
-        "EGLOutputPortEXT *" => "*mut types::EGLOutputPortEXT",
-        "EGLuint64KHR *" => "*mut types::EGLuint64KHR",
-        "const struct AHardwareBuffer *" => "*const __gl_imports::raw::c_void", // humm
-        "char *" => "*const __gl_imports::raw::c_char",
-        "struct wl_buffer *" => "*const __gl_imports::raw::c_void",
-        "struct wl_display *" => "*const __gl_imports::raw::c_void",
-        "struct wl_resource *" => "*const __gl_imports::raw::c_void",
-
-        "GLeglClientBufferEXT" => "types::GLeglClientBufferEXT",
-        "GLVULKANPROCNV" => "types::GLVULKANPROCNV",
-        "EGLDEBUGPROCKHR" => "types::EGLDEBUGPROCKHR",
-        "EGLObjectKHR" => "types::EGLObjectKHR",
-        "EGLLabelKHR" => "types::EGLLabelKHR",
-        "EGLnsecsANDROID" => "types::EGLnsecsANDROID",
-        "EGLnsecsANDROID *" => "*mut types::EGLnsecsANDROID",
-        "EGLBoolean *" => "*mut types::EGLBoolean",
-
-        // failure
-        _ => panic!("Type conversion not implemented for `{}`", ty.as_ref()),
-    };
-
-    Cow::Borrowed(ty)
-}
+/// Type names that are misspelled in the registries, along with their correct spelling.
+const MISSPELLED_TYPES: &[(&str, &str)] = &[
+    // khronos_api/api_angle/scripts/egl_angle_ext.xml - see brendanzab/gl-rs#491
+    ("EGLattrib", "EGLAttrib"),
+];
 
 #[cfg(test)]
 mod tests {
@@ -1125,6 +1449,8 @@ mod tests {
             assert_eq!(parse::underscore_keyword("in".to_string()), "in_");
             assert_eq!(parse::underscore_keyword("ref".to_string()), "ref_");
             assert_eq!(parse::underscore_keyword("type".to_string()), "type_");
+            assert_eq!(parse::underscore_keyword("box".to_string()), "box_");
+            assert_eq!(parse::underscore_keyword("async".to_string()), "async_");
         }
 
         #[test]
@@ -1133,8 +1459,33 @@ mod tests {
             assert_eq!(parse::underscore_keyword("bar".to_string()), "bar");
         }
     }
+    mod matches_pattern {
+        use super::super::matches_pattern;
+
+        #[test]
+        fn test_literal() {
+            assert!(matches_pattern("GL_ARB_sync", "GL_ARB_sync"));
+            assert!(!matches_pattern("GL_ARB_sync", "GL_ARB_syncs"));
+        }
+
+        #[test]
+        fn test_star() {
+            assert!(matches_pattern("GL_ARB_*", "GL_ARB_sync"));
+            assert!(matches_pattern("GL_*_sync", "GL_ARB_sync"));
+            assert!(matches_pattern("*", ""));
+            assert!(!matches_pattern("GL_ARB_*", "GL_EXT_sync"));
+        }
+
+        #[test]
+        fn test_question_mark() {
+            assert!(matches_pattern("GL_AR?_sync", "GL_ARB_sync"));
+            assert!(!matches_pattern("GL_A?_sync", "GL_ARB_sync"));
+        }
+    }
+
     mod make_enum {
         use registry::parse;
+        use registry::RegistryError;
 
         #[test]
         fn test_cast_0() {
@@ -1143,7 +1494,8 @@ mod tests {
                 None,
                 "((EGLint)-1)".to_string(),
                 Some("BAR".to_string()),
-            );
+            )
+            .unwrap();
             assert_eq!(e.ident, "FOO");
             assert_eq!((&*e.ty, &*e.value), ("EGLint", "-1"));
             assert_eq!(e.alias, Some("BAR".to_string()));
@@ -1156,7 +1508,8 @@ mod tests {
                 None,
                 "((EGLint)(-1))".to_string(),
                 Some("BAR".to_string()),
-            );
+            )
+            .unwrap();
             assert_eq!(e.ident, "FOO");
             assert_eq!((&*e.ty, &*e.value), ("EGLint", "(-1)"));
             assert_eq!(e.alias, Some("BAR".to_string()));
@@ -1169,7 +1522,8 @@ mod tests {
                 None,
                 "value".to_string(),
                 Some("BAR".to_string()),
-            );
+            )
+            .unwrap();
             assert_eq!(e.ident, "FOO");
             assert_eq!(e.value, "value");
             assert_eq!(e.alias, Some("BAR".to_string()));
@@ -1177,6 +1531,12 @@ mod tests {
             assert_eq!(e.cast, false);
         }
 
+        #[test]
+        fn test_negative() {
+            let e = parse::make_enum("FOO".to_string(), None, "-2".to_string(), None).unwrap();
+            assert_eq!((&*e.ty, &*e.value), ("GLint", "-2"));
+        }
+
         #[test]
         fn test_u() {
             let e = parse::make_enum(
@@ -1184,7 +1544,8 @@ mod tests {
                 Some("u".to_string()),
                 String::new(),
                 None,
-            );
+            )
+            .unwrap();
             assert_eq!(e.ty, "GLuint");
         }
 
@@ -1195,42 +1556,50 @@ mod tests {
                 Some("ull".to_string()),
                 String::new(),
                 None,
-            );
+            )
+            .unwrap();
             assert_eq!(e.ty, "GLuint64");
         }
 
         #[test]
-        #[should_panic]
         fn test_unknown_type() {
-            parse::make_enum(
+            let e = parse::make_enum(
                 "FOO".to_string(),
                 Some("blargh".to_string()),
                 String::new(),
                 None,
             );
+            assert_eq!(
+                e,
+                Err(RegistryError::UnhandledEnumType {
+                    ident: "FOO".to_string(),
+                    ty: "blargh".to_string(),
+                })
+            );
         }
 
         #[test]
         fn test_value_str() {
-            let e = parse::make_enum("FOO".to_string(), None, "\"hi\"".to_string(), None);
+            let e = parse::make_enum("FOO".to_string(), None, "\"hi\"".to_string(), None).unwrap();
             assert_eq!(e.ty, "&'static str");
         }
 
         #[test]
         fn test_ident_true() {
-            let e = parse::make_enum("TRUE".to_string(), None, String::new(), None);
+            let e = parse::make_enum("TRUE".to_string(), None, String::new(), None).unwrap();
             assert_eq!(e.ty, "GLboolean");
         }
 
         #[test]
         fn test_ident_false() {
-            let e = parse::make_enum("FALSE".to_string(), None, String::new(), None);
+            let e = parse::make_enum("FALSE".to_string(), None, String::new(), None).unwrap();
             assert_eq!(e.ty, "GLboolean");
         }
     }
 
     mod make_egl_enum {
         use registry::parse;
+        use registry::RegistryError;
 
         #[test]
         fn test_cast_egl() {
@@ -1239,7 +1608,8 @@ mod tests {
                 None,
                 "EGL_CAST(EGLint,-1)".to_string(),
                 Some("BAR".to_string()),
-            );
+            )
+            .unwrap();
             assert_eq!(e.ident, "FOO");
             assert_eq!((&*e.ty, &*e.value), ("EGLint", "-1"));
             assert_eq!(e.alias, Some("BAR".to_string()));
@@ -1247,13 +1617,15 @@ mod tests {
 
         #[test]
         fn test_ident_true() {
-            let e = parse::make_egl_enum("TRUE".to_string(), None, "1234".to_string(), None);
+            let e =
+                parse::make_egl_enum("TRUE".to_string(), None, "1234".to_string(), None).unwrap();
             assert_eq!(e.ty, "EGLBoolean");
         }
 
         #[test]
         fn test_ident_false() {
-            let e = parse::make_egl_enum("FALSE".to_string(), None, "1234".to_string(), None);
+            let e =
+                parse::make_egl_enum("FALSE".to_string(), None, "1234".to_string(), None).unwrap();
             assert_eq!(e.ty, "EGLBoolean");
         }
 
@@ -1264,37 +1636,774 @@ mod tests {
                 Some("ull".to_string()),
                 "1234".to_string(),
                 None,
-            );
+            )
+            .unwrap();
             assert_eq!(e.ty, "EGLuint64KHR");
         }
 
         #[test]
         fn test_negative_value() {
-            let e = parse::make_egl_enum("FOO".to_string(), None, "-1".to_string(), None);
+            let e = parse::make_egl_enum("FOO".to_string(), None, "-1".to_string(), None).unwrap();
             assert_eq!(e.ty, "EGLint");
         }
 
         #[test]
-        #[should_panic]
         fn test_unknown_type() {
-            parse::make_egl_enum(
+            let e = parse::make_egl_enum(
                 "FOO".to_string(),
                 Some("blargh".to_string()),
-                String::new(),
+                "1234".to_string(),
                 None,
             );
+            assert_eq!(
+                e,
+                Err(RegistryError::UnhandledEnumType {
+                    ident: "FOO".to_string(),
+                    ty: "blargh".to_string(),
+                })
+            );
         }
 
         #[test]
-        #[should_panic]
         fn test_unknown_value() {
-            parse::make_egl_enum("FOO".to_string(), None, "a".to_string(), None);
+            let e = parse::make_egl_enum("FOO".to_string(), None, "a".to_string(), None);
+            assert_eq!(
+                e,
+                Err(RegistryError::InvalidEnumValue {
+                    ident: "FOO".to_string(),
+                    value: "a".to_string(),
+                })
+            );
         }
 
         #[test]
-        #[should_panic]
         fn test_empty_value() {
-            parse::make_egl_enum("FOO".to_string(), None, String::new(), None);
+            let e = parse::make_egl_enum("FOO".to_string(), None, String::new(), None);
+            assert_eq!(
+                e,
+                Err(RegistryError::InvalidEnumValue {
+                    ident: "FOO".to_string(),
+                    value: String::new(),
+                })
+            );
+        }
+    }
+
+    mod from_xml {
+        use registry::parse::{self, Filter};
+        use registry::RegistryError;
+        use std::collections::BTreeSet;
+        use {Api, Fallbacks, Profile};
+
+        const REGISTRY: &str = r#"
+            <registry>
+                <enums namespace="GL">
+                    <enum value="0x0001" name="GL_FOO"/>
+                    <enum value="0x0002" name="GL_BAR"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto>void <name>glFoo</name></proto>
+                        <param><ptype>GLenum</ptype> <name>foo</name></param>
+                    </command>
+                    <command>
+                        <proto>void <name>glBar</name></proto>
+                    </command>
+                </commands>
+                <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                    <require>
+                        <enum name="GL_FOO"/>
+                        <command name="glFoo"/>
+                    </require>
+                </feature>
+                <extensions>
+                    <extension name="GL_EXT_bar" supported="gles2">
+                        <require>
+                            <enum name="GL_BAR"/>
+                            <command name="glBar"/>
+                        </require>
+                    </extension>
+                </extensions>
+            </registry>
+        "#;
+
+        fn filter(version: (u8, u8), extensions: &[&str]) -> Filter {
+            Filter {
+                api: Api::Gl,
+                fallbacks: Fallbacks::All,
+                extensions: extensions.iter().map(|e| e.to_string()).collect(),
+                excluded_extensions: BTreeSet::new(),
+                profile: Profile::Core,
+                version,
+                min_version: None,
+            }
+        }
+
+        #[test]
+        fn test_version() {
+            let registry =
+                parse::from_xml(REGISTRY.as_bytes(), &filter((1, 0), &[]), true).unwrap();
+            assert_eq!(registry.enums.len(), 1);
+            assert_eq!(registry.cmds.len(), 1);
+            assert_eq!(registry.extensions, BTreeSet::new());
+        }
+
+        #[test]
+        fn test_version_not_found() {
+            let result = parse::from_xml(REGISTRY.as_bytes(), &filter((2, 0), &[]), true);
+            assert_eq!(
+                result,
+                Err(RegistryError::VersionNotFound {
+                    api: Api::Gl,
+                    version: (2, 0),
+                })
+            );
+        }
+
+        #[test]
+        fn test_version_of_other_api() {
+            let mut filter = filter((1, 0), &[]);
+            filter.api = Api::Gles2;
+            let result = parse::from_xml(REGISTRY.as_bytes(), &filter, true);
+            assert_eq!(
+                result,
+                Err(RegistryError::VersionNotFound {
+                    api: Api::Gles2,
+                    version: (1, 0),
+                })
+            );
+        }
+
+        #[test]
+        fn test_unsupported_extension() {
+            let filter = filter((1, 0), &["GL_EXT_bar"]);
+            let result = parse::from_xml(REGISTRY.as_bytes(), &filter, true);
+            assert_eq!(
+                result,
+                Err(RegistryError::UnsupportedExtension {
+                    extension: "GL_EXT_bar".to_string(),
+                    api: Api::Gl,
+                })
+            );
+        }
+
+        #[test]
+        fn test_new_type() {
+            let src = REGISTRY.replace("<ptype>GLenum</ptype>", "<ptype>GLwhatever</ptype>");
+            let registry = parse::from_xml(src.as_bytes(), &filter((1, 0), &[]), true).unwrap();
+            assert_eq!(
+                registry.cmds.iter().next().unwrap().params[0].ty,
+                "types::GLwhatever"
+            );
+        }
+
+        #[test]
+        fn test_unknown_type() {
+            let src = REGISTRY.replace("<ptype>GLenum</ptype>", "<ptype>GLenum</ptype>)");
+            let result = parse::from_xml(src.as_bytes(), &filter((1, 0), &[]), true);
+            assert_eq!(
+                result,
+                Err(RegistryError::UnknownType {
+                    command: "glFoo".to_string(),
+                    param: Some("foo".to_string()),
+                    ty: "GLenum )".to_string(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_missing_attribute() {
+            let src = REGISTRY.replace(r#"number="1.0""#, "");
+            let result = parse::from_xml(src.as_bytes(), &filter((1, 0), &[]), true);
+            assert_eq!(
+                result,
+                Err(RegistryError::MissingAttribute {
+                    element: "feature".to_string(),
+                    attribute: "number".to_string(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_malformed_xml() {
+            let src = REGISTRY.replace("</enums>", "</enumz>");
+            match parse::from_xml(src.as_bytes(), &filter((1, 0), &[]), true) {
+                Err(RegistryError::Xml(_)) => {},
+                result => panic!("expected an XML error, found {:?}", result),
+            }
+        }
+    }
+
+    mod versions {
+        use registry::parse::{self, version_from_str, Filter};
+        use registry::{Registry, RegistryError};
+        use {Api, Fallbacks, Profile};
+
+        const REGISTRY: &str = r#"
+            <registry>
+                <enums namespace="GL">
+                    <enum value="0x0001" name="GL_ONE"/>
+                    <enum value="0x0002" name="GL_NINE"/>
+                    <enum value="0x0003" name="GL_TEN"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto>void <name>glOne</name></proto>
+                    </command>
+                    <command>
+                        <proto>void <name>glNine</name></proto>
+                    </command>
+                    <command>
+                        <proto>void <name>glTen</name></proto>
+                    </command>
+                </commands>
+                <feature api="gl" name="GL_VERSION_4_1" number="4.1">
+                    <require>
+                        <enum name="GL_ONE"/>
+                        <command name="glOne"/>
+                    </require>
+                </feature>
+                <feature api="gl" name="GL_VERSION_4_9" number="4.9">
+                    <require>
+                        <enum name="GL_NINE"/>
+                        <command name="glNine"/>
+                        <command name="glOne"/>
+                    </require>
+                </feature>
+                <feature api="gl" name="GL_VERSION_4_10" number="4.10">
+                    <require>
+                        <enum name="GL_TEN"/>
+                        <command name="glTen"/>
+                    </require>
+                </feature>
+            </registry>
+        "#;
+
+        fn parse(
+            version: (u8, u8),
+            min_version: Option<(u8, u8)>,
+        ) -> Result<Registry, RegistryError> {
+            let mut filter = Filter::new(Api::Gl, version, Profile::Core, Fallbacks::All, []);
+            filter.min_version = min_version;
+            parse::from_xml(REGISTRY.as_bytes(), &filter, true)
+        }
+
+        fn introduced_in(registry: &Registry) -> Vec<(&str, Option<(u8, u8)>)> {
+            registry
+                .cmds
+                .iter()
+                .map(|c| (&c.proto.ident[..], c.introduced_in))
+                .collect()
+        }
+
+        #[test]
+        fn test_version_from_str() {
+            assert_eq!(version_from_str("1.0"), Ok((1, 0)));
+            assert_eq!(version_from_str("4.10"), Ok((4, 10)));
+            assert_eq!(version_from_str("10.2"), Ok((10, 2)));
+            assert_eq!(version_from_str("1"), Err(()));
+            assert_eq!(version_from_str("1.0.0"), Err(()));
+            assert_eq!(version_from_str("1.x"), Err(()));
+        }
+
+        #[test]
+        fn test_numeric_comparison() {
+            let registry = parse((4, 9), None).unwrap();
+            assert_eq!(registry.cmds.len(), 2);
+            assert!(!registry.cmds.iter().any(|c| c.proto.ident == "Ten"));
+
+            let registry = parse((4, 10), None).unwrap();
+            assert_eq!(registry.cmds.len(), 3);
+        }
+
+        #[test]
+        fn test_introduced_in() {
+            let registry = parse((4, 10), None).unwrap();
+            assert_eq!(
+                introduced_in(&registry),
+                [
+                    ("Nine", Some((4, 9))),
+                    ("One", Some((4, 1))),
+                    ("Ten", Some((4, 10))),
+                ]
+            );
+            assert!(registry
+                .enums
+                .iter()
+                .any(|e| e.ident == "TEN" && e.introduced_in == Some((4, 10))));
+        }
+
+        #[test]
+        fn test_introduced_in_unordered() {
+            // move GL_VERSION_4_1 after the features that also require glOne
+            let start = REGISTRY
+                .find(r#"<feature api="gl" name="GL_VERSION_4_1""#)
+                .unwrap();
+            let end = REGISTRY
+                .find(r#"<feature api="gl" name="GL_VERSION_4_9""#)
+                .unwrap();
+            let src = REGISTRY[..start].to_string()
+                + &REGISTRY[end..].replace("</registry>", &REGISTRY[start..end])
+                + "</registry>";
+            let filter = Filter::new(Api::Gl, (4, 10), Profile::Core, Fallbacks::All, []);
+            let registry = parse::from_xml(src.as_bytes(), &filter, true).unwrap();
+            assert_eq!(
+                introduced_in(&registry),
+                [
+                    ("Nine", Some((4, 9))),
+                    ("One", Some((4, 1))),
+                    ("Ten", Some((4, 10))),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_version_range() {
+            let registry = parse((4, 10), Some((4, 1))).unwrap();
+            assert_eq!(registry.version, (4, 10));
+            assert_eq!(registry.min_version, (4, 1));
+            assert_eq!(registry.cmds.len(), 3);
+
+            let registry = parse((4, 10), None).unwrap();
+            assert_eq!(registry.min_version, (4, 10));
+        }
+
+        #[test]
+        fn test_invalid_version_range() {
+            assert_eq!(
+                parse((4, 1), Some((4, 9))),
+                Err(RegistryError::InvalidVersionRange {
+                    min: (4, 9),
+                    max: (4, 1),
+                })
+            );
+        }
+
+        #[test]
+        fn test_invalid_version() {
+            let src = REGISTRY.replace(r#"number="4.10""#, r#"number="4.x""#);
+            let filter = Filter::new(Api::Gl, (4, 1), Profile::Core, Fallbacks::All, []);
+            assert_eq!(
+                parse::from_xml(src.as_bytes(), &filter, true),
+                Err(RegistryError::InvalidVersion("4.x".to_string()))
+            );
+        }
+    }
+
+    mod len_from_str {
+        use registry::parse::len_from_str;
+        use registry::Len;
+
+        fn param(ident: &str, factor: usize) -> Len {
+            Len::Param {
+                ident: ident.to_string(),
+                factor,
+            }
+        }
+
+        #[test]
+        fn test_fixed() {
+            assert_eq!(len_from_str("16"), Len::Fixed(16));
+            assert_eq!(len_from_str("4*2"), Len::Fixed(8));
+        }
+
+        #[test]
+        fn test_param() {
+            assert_eq!(len_from_str("count"), param("count", 1));
+            assert_eq!(len_from_str("count*16"), param("count", 16));
+            assert_eq!(len_from_str("type"), param("type_", 1));
+        }
+
+        #[test]
+        fn test_other() {
+            let len = "COMPSIZE(format,type)";
+            assert_eq!(len_from_str(len), Len::Other(len.to_string()));
+            assert_eq!(len_from_str("count*n"), Len::Other("count*n".to_string()));
+        }
+    }
+
+    mod groups {
+        use registry::parse;
+        use registry::Filter;
+        use {Api, Fallbacks, Profile};
+
+        const REGISTRY: &str = r#"
+            <registry>
+                <groups>
+                    <group name="TextureTarget">
+                        <enum name="GL_TEXTURE_2D"/>
+                    </group>
+                    <group name="ClearBufferMask">
+                        <enum name="GL_COLOR_BUFFER_BIT"/>
+                    </group>
+                </groups>
+                <enums namespace="GL" group="AttribMask" type="bitmask">
+                    <enum value="0x4000" name="GL_COLOR_BUFFER_BIT"/>
+                </enums>
+                <enums namespace="GL" type="bitmask">
+                    <enum value="0x0001" name="GL_MAP_READ_BIT" group="MapBufferAccessMask"/>
+                    <enum value="0x0002" name="GL_MAP_WRITE_BIT" group="MapBufferAccessMask,BufferAccessMask"/>
+                </enums>
+                <enums namespace="GL">
+                    <enum value="0x0DE1" name="GL_TEXTURE_2D"/>
+                    <enum value="0x806F" name="GL_TEXTURE_3D" group="TextureTarget"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto group="ErrorCode"><ptype>GLenum</ptype> <name>glGetError</name></proto>
+                    </command>
+                    <command>
+                        <proto class="program"><ptype>GLuint</ptype> <name>glCreateProgram</name></proto>
+                    </command>
+                    <command>
+                        <proto>void <name>glUseProgram</name></proto>
+                        <param class="program"><ptype>GLuint</ptype> <name>program</name></param>
+                    </command>
+                </commands>
+                <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                    <require>
+                        <command name="glGetError"/>
+                        <command name="glCreateProgram"/>
+                        <command name="glUseProgram"/>
+                    </require>
+                </feature>
+            </registry>
+        "#;
+
+        #[test]
+        fn test_groups() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = parse::from_xml(REGISTRY.as_bytes(), &filter, true).unwrap();
+
+            let group = &registry.groups["TextureTarget"];
+            assert_eq!(group.enums, ["TEXTURE_2D", "TEXTURE_3D"]);
+            assert_eq!(group.enums_type, None);
+
+            let group = &registry.groups["MapBufferAccessMask"];
+            assert_eq!(group.enums, ["MAP_READ_BIT", "MAP_WRITE_BIT"]);
+            assert_eq!(group.enums_type, Some("bitmask".to_string()));
+
+            let group = &registry.groups["BufferAccessMask"];
+            assert_eq!(group.enums, ["MAP_WRITE_BIT"]);
+
+            let group = &registry.groups["ClearBufferMask"];
+            assert_eq!(group.enums_type, Some("bitmask".to_string()));
+        }
+
+        #[test]
+        fn test_proto_group() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = parse::from_xml(REGISTRY.as_bytes(), &filter, true).unwrap();
+            let cmd = registry.cmds.iter().find(|c| c.proto.ident == "GetError");
+            assert_eq!(cmd.unwrap().proto.group, Some("ErrorCode".to_string()));
+        }
+
+        #[test]
+        fn test_class() {
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = parse::from_xml(REGISTRY.as_bytes(), &filter, true).unwrap();
+            let cmd = registry
+                .cmds
+                .iter()
+                .find(|c| c.proto.ident == "CreateProgram");
+            assert_eq!(cmd.unwrap().proto.class, Some("program".to_string()));
+            let cmd = registry.cmds.iter().find(|c| c.proto.ident == "UseProgram");
+            assert_eq!(cmd.unwrap().params[0].class, Some("program".to_string()));
+        }
+    }
+
+    mod types {
+        use registry::parse;
+        use registry::{CType, Filter, TypeDef};
+        use {Api, Fallbacks, Profile};
+
+        const REGISTRY: &str = r#"
+            <registry>
+                <types>
+                    <type name="khrplatform">#include &lt;KHR/khrplatform.h&gt;</type>
+                    <type>#include &lt;X11/Xlib.h&gt;</type>
+                    <type>typedef unsigned int <name>GLenum</name>;</type>
+                    <type api="gles2">typedef int <name>GLenum</name>;</type>
+                    <type requires="khrplatform">typedef khronos_int8_t <name>GLbyte</name>;</type>
+                    <type name="GLhandleARB">#ifdef __APPLE__
+typedef void *GLhandleARB;
+#else
+typedef unsigned int GLhandleARB;
+#endif</type>
+                    <type>typedef struct __GLsync *<name>GLsync</name>;</type>
+                    <type><name>struct _cl_context</name>;</type>
+                    <type>typedef void (<apientry/> *<name>GLDEBUGPROC</name>)(GLenum source,const void *userParam);</type>
+                    <type name="Display"/>
+                    <type>typedef struct {
+    int type;
+    char pipeName[<enum>GLX_HYPERPIPE_PIPE_NAME_LENGTH_SGIX</enum>];
+} <name>GLXHyperpipeNetworkSGIX</name>;</type>
+                </types>
+                <enums namespace="GLX">
+                    <enum value="80" name="GLX_HYPERPIPE_PIPE_NAME_LENGTH_SGIX"/>
+                </enums>
+                <feature api="gl" name="GL_VERSION_1_0" number="1.0"/>
+                <feature api="gles2" name="GL_ES_VERSION_2_0" number="2.0"/>
+                <feature api="glx" name="GLX_VERSION_1_0" number="1.0"/>
+            </registry>
+        "#;
+
+        fn parse(api: Api) -> ::std::collections::BTreeMap<String, TypeDef> {
+            let version = if api == Api::Gles2 { (2, 0) } else { (1, 0) };
+            let filter = Filter::new(api, version, Profile::Core, Fallbacks::All, []);
+            parse::from_xml(REGISTRY.as_bytes(), &filter, true)
+                .unwrap()
+                .types
+        }
+
+        #[test]
+        fn test_alias() {
+            let types = parse(Api::Gl);
+            let named = |name: &str| CType::Named(name.to_string());
+            assert_eq!(types["GLenum"], TypeDef::Alias(named("unsigned int")));
+            assert_eq!(types["GLbyte"], TypeDef::Alias(named("khronos_int8_t")));
+            assert_eq!(
+                types["GLsync"],
+                TypeDef::Alias(CType::Pointer(Box::new(CType::Struct(
+                    "__GLsync".to_string()
+                ))))
+            );
+        }
+
+        #[test]
+        fn test_api_specific() {
+            let types = parse(Api::Gles2);
+            assert_eq!(
+                types["GLenum"],
+                TypeDef::Alias(CType::Named("int".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_external() {
+            let types = parse(Api::Gl);
+            assert_eq!(types["GLhandleARB"], TypeDef::External);
+            assert_eq!(types["Display"], TypeDef::External);
+            assert_eq!(types["_cl_context"], TypeDef::Opaque);
+            assert!(!types.contains_key("khrplatform"));
+        }
+
+        #[test]
+        fn test_function() {
+            let types = parse(Api::Gl);
+            match types["GLDEBUGPROC"] {
+                TypeDef::Function {
+                    ref params,
+                    apientry,
+                    ..
+                } => {
+                    assert!(apientry);
+                    assert_eq!(params[0].0, Some("source".to_string()));
+                    assert_eq!(params.len(), 2);
+                },
+                ref def => panic!("expected a function, found {:?}", def),
+            }
+        }
+
+        #[test]
+        fn test_array_length() {
+            let types = parse(Api::Glx);
+            match types["GLXHyperpipeNetworkSGIX"] {
+                TypeDef::Struct(ref fields) => assert_eq!(
+                    fields[1],
+                    (
+                        "pipeName".to_string(),
+                        CType::Array(Box::new(CType::Named("char".to_string())), 80)
+                    )
+                ),
+                ref def => panic!("expected a struct, found {:?}", def),
+            }
+        }
+    }
+
+    mod restrictions {
+        use registry::parse::{self, Filter};
+        use registry::{Registry, RegistryError};
+        use std::collections::BTreeSet;
+        use {Api, Fallbacks, Profile};
+
+        // Modelled on GL_KHR_debug, which has a separate set of suffixed commands for GLES
+        const REGISTRY: &str = r#"
+            <registry>
+                <enums namespace="GL">
+                    <enum value="0x0001" name="GL_COMMON"/>
+                    <enum value="0x0002" name="GL_DEBUG_OUTPUT"/>
+                    <enum value="0x0002" name="GL_DEBUG_OUTPUT_KHR"/>
+                    <enum value="0x0003" name="GL_LEGACY"/>
+                </enums>
+                <commands namespace="GL">
+                    <command>
+                        <proto>void <name>glDebugMessageControl</name></proto>
+                    </command>
+                    <command>
+                        <proto>void <name>glDebugMessageControlKHR</name></proto>
+                    </command>
+                </commands>
+                <feature api="gl" name="GL_VERSION_1_0" number="1.0">
+                    <require>
+                        <enum name="GL_COMMON"/>
+                        <enum name="GL_LEGACY"/>
+                    </require>
+                    <remove profile="core">
+                        <enum name="GL_LEGACY"/>
+                    </remove>
+                </feature>
+                <feature api="gles2" name="GL_ES_VERSION_2_0" number="2.0">
+                    <require>
+                        <enum name="GL_COMMON"/>
+                    </require>
+                    <require profile="compatibility">
+                        <enum name="GL_LEGACY"/>
+                    </require>
+                </feature>
+                <extensions>
+                    <extension name="GL_KHR_debug" supported="gl|glcore|gles2">
+                        <require api="gl">
+                            <enum name="GL_DEBUG_OUTPUT"/>
+                            <command name="glDebugMessageControl"/>
+                        </require>
+                        <require api="gles2">
+                            <enum name="GL_DEBUG_OUTPUT_KHR"/>
+                            <command name="glDebugMessageControlKHR"/>
+                        </require>
+                    </extension>
+                </extensions>
+            </registry>
+        "#;
+
+        fn parse(api: Api, version: (u8, u8), profile: Profile) -> Result<Registry, RegistryError> {
+            let filter = Filter {
+                api,
+                fallbacks: Fallbacks::All,
+                extensions: Some("GL_KHR_debug".to_string()).into_iter().collect(),
+                excluded_extensions: BTreeSet::new(),
+                profile,
+                version,
+                min_version: None,
+            };
+            parse::from_xml(REGISTRY.as_bytes(), &filter, false)
+        }
+
+        fn enums(registry: &Registry) -> Vec<&str> {
+            registry.enums.iter().map(|e| &e.ident[..]).collect()
+        }
+
+        fn cmds(registry: &Registry) -> Vec<&str> {
+            registry.cmds.iter().map(|c| &c.proto.ident[..]).collect()
+        }
+
+        #[test]
+        fn test_extension_gl() {
+            let registry = parse(Api::Gl, (1, 0), Profile::Core).unwrap();
+            assert_eq!(enums(&registry), ["COMMON", "DEBUG_OUTPUT"]);
+            assert_eq!(cmds(&registry), ["DebugMessageControl"]);
+        }
+
+        #[test]
+        fn test_extension_glcore() {
+            let registry = parse(Api::GlCore, (1, 0), Profile::Core).unwrap();
+            assert_eq!(enums(&registry), ["COMMON", "DEBUG_OUTPUT"]);
+            assert_eq!(cmds(&registry), ["DebugMessageControl"]);
+        }
+
+        #[test]
+        fn test_extension_gles2() {
+            let registry = parse(Api::Gles2, (2, 0), Profile::Core).unwrap();
+            assert_eq!(enums(&registry), ["COMMON", "DEBUG_OUTPUT_KHR"]);
+            assert_eq!(cmds(&registry), ["DebugMessageControlKHR"]);
+        }
+
+        #[test]
+        fn test_profile_require() {
+            let registry = parse(Api::Gles2, (2, 0), Profile::Compatibility).unwrap();
+            assert!(enums(&registry).contains(&"LEGACY"));
+        }
+
+        #[test]
+        fn test_profile_remove() {
+            let registry = parse(Api::Gl, (1, 0), Profile::Compatibility).unwrap();
+            assert!(enums(&registry).contains(&"LEGACY"));
+            let registry = parse(Api::Gl, (1, 0), Profile::Core).unwrap();
+            assert!(!enums(&registry).contains(&"LEGACY"));
+        }
+
+        #[test]
+        fn test_provenance() {
+            let registry = parse(Api::Gl, (1, 0), Profile::Compatibility).unwrap();
+
+            let common = registry.enums.iter().find(|e| e.ident == "COMMON").unwrap();
+            assert_eq!(common.introduced_in, Some((1, 0)));
+            assert!(common.required_by_extensions.is_empty());
+            assert_eq!(common.removed_in_profile, None);
+
+            let legacy = registry.enums.iter().find(|e| e.ident == "LEGACY").unwrap();
+            assert_eq!(legacy.removed_in_profile, Some(Profile::Core));
+
+            let cmd = registry.cmds.iter().next().unwrap();
+            assert_eq!(cmd.proto.ident, "DebugMessageControl");
+            assert_eq!(cmd.introduced_in, None);
+            assert_eq!(cmd.required_by_extensions, ["GL_KHR_debug"]);
+        }
+
+        #[test]
+        fn test_unrestricted_remove() {
+            let src = REGISTRY.replace(r#"<remove profile="core">"#, "<remove>");
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Compatibility, Fallbacks::All, []);
+            let registry = parse::from_xml(src.as_bytes(), &filter, true).unwrap();
+            assert_eq!(enums(&registry), ["COMMON"]);
+        }
+
+        #[test]
+        fn test_unknown_api() {
+            let src = REGISTRY.replace(r#"<require api="gles2">"#, r#"<require api="gles9">"#);
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            assert_eq!(
+                parse::from_xml(src.as_bytes(), &filter, true),
+                Err(RegistryError::UnknownApi("gles9".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_gles1_profile() {
+            let src = REGISTRY
+                .replace(
+                    r#"api="gles2" name="GL_ES_VERSION_2_0" number="2.0""#,
+                    r#"api="gles1" name="GL_VERSION_ES_CM_1_0" number="1.0""#,
+                )
+                .replace(
+                    r#"<require profile="compatibility">"#,
+                    r#"<require profile="common">"#,
+                );
+            let filter = Filter::new(Api::Gles1, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = parse::from_xml(src.as_bytes(), &filter, true).unwrap();
+            assert_eq!(enums(&registry), ["COMMON", "LEGACY"]);
+        }
+
+        #[test]
+        fn test_unknown_profile() {
+            let src = REGISTRY.replace(
+                r#"<require profile="compatibility">"#,
+                r#"<require profile="es">"#,
+            );
+            let filter = Filter::new(
+                Api::Gles2,
+                (2, 0),
+                Profile::Compatibility,
+                Fallbacks::All,
+                [],
+            );
+            let registry = parse::from_xml(src.as_bytes(), &filter, true).unwrap();
+            assert_eq!(enums(&registry), ["COMMON"]);
+
+            let src = REGISTRY.replace(r#"<remove profile="core">"#, r#"<remove profile="es">"#);
+            let filter = Filter::new(Api::Gl, (1, 0), Profile::Core, Fallbacks::All, []);
+            let registry = parse::from_xml(src.as_bytes(), &filter, true).unwrap();
+            assert_eq!(enums(&registry), ["COMMON", "LEGACY"]);
         }
     }
 
@@ -0,0 +1,169 @@
+// Copyright 2015-2016 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate khronos_api;
+
+use std::borrow::Cow;
+use std::fmt;
+
+/// Decides whether a supplemental registry is needed, from the names of the requested
+/// extensions.
+#[derive(Clone)]
+enum Trigger {
+    Prefix(String),
+    Predicate(fn(&str) -> bool),
+}
+
+/// An extra XML registry that follows the Khronos schema, but only defines extensions, such as
+/// ANGLE's `gl_angle_ext.xml`.
+///
+/// A supplemental registry is only parsed when one of the requested extensions needs it, as
+/// decided by its prefix or predicate. `Supplement::defaults` are the ones bundled with
+/// `khronos_api`, and `RegistryBuilder::supplement` adds others:
+///
+/// ```no_run
+/// # use gl_generator::{Api, RegistryBuilder, Supplement};
+/// let registry = RegistryBuilder::new(Api::Gl, (4, 5))
+///     .extensions(&["GL_MESA_framebuffer_flip_y"])
+///     .supplement(Supplement::with_prefix(
+///         std::fs::read("xml/gl_mesa_ext.xml").unwrap(),
+///         "GL_MESA_",
+///     ))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Supplement {
+    src: Cow<'static, [u8]>,
+    trigger: Trigger,
+}
+
+impl Supplement {
+    /// A supplemental registry that is parsed when the name of any requested extension starts
+    /// with `prefix`, such as `"GL_ANGLE_"`.
+    pub fn with_prefix<S>(src: S, prefix: &str) -> Supplement
+    where
+        S: Into<Cow<'static, [u8]>>,
+    {
+        Supplement {
+            src: src.into(),
+            trigger: Trigger::Prefix(prefix.to_string()),
+        }
+    }
+
+    /// A supplemental registry that is parsed when `predicate` returns `true` for the name of any
+    /// requested extension.
+    pub fn with_predicate<S>(src: S, predicate: fn(&str) -> bool) -> Supplement
+    where
+        S: Into<Cow<'static, [u8]>>,
+    {
+        Supplement {
+            src: src.into(),
+            trigger: Trigger::Predicate(predicate),
+        }
+    }
+
+    /// The supplemental registries bundled with `khronos_api`, which define the ANGLE extensions
+    /// for GL and EGL.
+    pub fn defaults() -> Vec<Supplement> {
+        vec![
+            Supplement::with_prefix(khronos_api::GL_ANGLE_EXT_XML, "GL_ANGLE_"),
+            Supplement::with_prefix(khronos_api::EGL_ANGLE_EXT_XML, "EGL_ANGLE_"),
+        ]
+    }
+
+    /// Returns `true` if the registry should be parsed for the requested extension, which may be
+    /// a pattern. A pattern needs a registry with a prefix if any name that it matches could
+    /// start with the prefix, so `"*"` and `"GL_*"` need `"GL_ANGLE_"`, but `"GL_ARB_*"` doesn't.
+    pub fn is_needed_by(&self, extension: &str) -> bool {
+        match self.trigger {
+            Trigger::Prefix(ref prefix) => match extension.find(['*', '?']) {
+                Some(wildcard) => {
+                    let literal = &extension[..wildcard];
+                    literal.starts_with(prefix.as_str()) || prefix.starts_with(literal)
+                },
+                None => extension.starts_with(prefix.as_str()),
+            },
+            Trigger::Predicate(predicate) => predicate(extension),
+        }
+    }
+
+    /// The XML source of the registry.
+    pub fn src(&self) -> &[u8] {
+        &self.src
+    }
+}
+
+impl fmt::Debug for Supplement {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let trigger = match self.trigger {
+            Trigger::Prefix(ref prefix) => format!("{:?}", prefix),
+            Trigger::Predicate(_) => "<predicate>".to_string(),
+        };
+        fmt.debug_struct("Supplement")
+            .field("src", &format_args!("<{} bytes>", self.src.len()))
+            .field("trigger", &format_args!("{}", trigger))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Supplement;
+
+    #[test]
+    fn test_prefix() {
+        let supplement = Supplement::with_prefix(&b"<registry/>"[..], "GL_MESA_");
+        assert!(supplement.is_needed_by("GL_MESA_framebuffer_flip_y"));
+        assert!(supplement.is_needed_by("GL_MESA_*"));
+        assert!(!supplement.is_needed_by("GL_ARB_sync"));
+        assert!(!supplement.is_needed_by("GL_ARB_*"));
+        assert!(!supplement.is_needed_by("GL_MES"));
+    }
+
+    #[test]
+    fn test_prefix_pattern() {
+        let supplement = Supplement::with_prefix(&b"<registry/>"[..], "GL_MESA_");
+        assert!(supplement.is_needed_by("*"));
+        assert!(supplement.is_needed_by("GL_*"));
+        assert!(supplement.is_needed_by("GL_ME?A_*"));
+        assert!(supplement.is_needed_by("GL_MESA_framebuffer_*"));
+        assert!(!supplement.is_needed_by("GL_EXT_*"));
+        assert!(!supplement.is_needed_by("EGL_*"));
+        assert_eq!(supplement.src(), b"<registry/>");
+    }
+
+    #[test]
+    fn test_predicate() {
+        fn is_in_house(extension: &str) -> bool {
+            extension.ends_with("_ACME")
+        }
+        let supplement = Supplement::with_predicate(Vec::new(), is_in_house);
+        assert!(supplement.is_needed_by("GL_EXT_foo_ACME"));
+        assert!(!supplement.is_needed_by("GL_EXT_foo"));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let defaults = Supplement::defaults();
+        assert!(defaults
+            .iter()
+            .any(|s| s.is_needed_by("GL_ANGLE_instanced_arrays")));
+        assert!(defaults
+            .iter()
+            .any(|s| s.is_needed_by("EGL_ANGLE_device_d3d")));
+        assert!(!defaults.iter().any(|s| s.is_needed_by("GL_ARB_sync")));
+        assert!(defaults.iter().all(|s| s.is_needed_by("*")));
+    }
+}
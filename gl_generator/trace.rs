@@ -0,0 +1,173 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading the traces written by the bindings from `TracingGenerator`.
+//!
+//! A trace starts with a header, and is followed by a record for each call. All numbers are
+//! little-endian.
+//!
+//! The header is the magic bytes `GLTRACE\0`, the version of the format as a `u16`, the name of
+//! the API, the number of commands as a `u16`, and then, for each command, its name, the number
+//! of parameters that it takes as a `u8` and whether it returns a value as a `u8`. Names are a
+//! `u8` length followed by that many bytes of UTF-8.
+//!
+//! Each call is the index of its command in the header as a `u16`, followed by a value for each
+//! of its arguments, and then its return value if it has one. Each value is a tag from the
+//! `tag` module followed by the bytes of the value.
+//!
+//! ```no_run
+//! # use gl_generator::TraceReader;
+//! # use std::fs::File;
+//! let mut trace = TraceReader::new(File::open("frame.gltrace").unwrap()).unwrap();
+//! while let Some(call) = trace.read_call().unwrap() {
+//!     println!("{}{:?}", trace.commands()[call.command].name, call.args);
+//! }
+//! ```
+
+use std::error;
+use std::fmt;
+use std::io::{self, Read};
+
+include!("trace_reader.rs");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A trace with `glBindTexture(GLenum, GLuint)` and `glIsTexture(GLuint) -> GLboolean`.
+    fn header() -> Vec<u8> {
+        let mut trace = MAGIC.to_vec();
+        trace.extend_from_slice(&[1, 0, 2, b'g', b'l', 2, 0]);
+        trace.push(13);
+        trace.extend_from_slice(b"glBindTexture");
+        trace.extend_from_slice(&[2, 0]);
+        trace.push(11);
+        trace.extend_from_slice(b"glIsTexture");
+        trace.extend_from_slice(&[1, 1]);
+        trace
+    }
+
+    #[test]
+    fn test_read_header() {
+        let bytes = header();
+        let trace = TraceReader::new(&bytes[..]).unwrap();
+        assert_eq!(trace.api(), "gl");
+        assert_eq!(
+            trace.commands(),
+            &[
+                TraceCommand {
+                    name: "glBindTexture".to_string(),
+                    params: 2,
+                    returns: false,
+                },
+                TraceCommand {
+                    name: "glIsTexture".to_string(),
+                    params: 1,
+                    returns: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_calls() {
+        let mut bytes = header();
+        bytes.extend_from_slice(&[0, 0, tag::U32, 0xE1, 0x0D, 0, 0, tag::U32, 7, 0, 0, 0]);
+        bytes.extend_from_slice(&[1, 0, tag::U32, 7, 0, 0, 0, tag::U8, 1]);
+
+        let mut trace = TraceReader::new(&bytes[..]).unwrap();
+        assert_eq!(
+            trace.read_call(),
+            Ok(Some(TraceCall {
+                command: 0,
+                args: vec![TraceValue::U32(0x0DE1), TraceValue::U32(7)],
+                ret: None,
+            }))
+        );
+        assert_eq!(
+            trace.read_call(),
+            Ok(Some(TraceCall {
+                command: 1,
+                args: vec![TraceValue::U32(7)],
+                ret: Some(TraceValue::U8(1)),
+            }))
+        );
+        assert_eq!(trace.read_call(), Ok(None));
+    }
+
+    #[test]
+    fn test_read_values() {
+        let mut bytes = vec![tag::I32, 0xFF, 0xFF, 0xFF, 0xFF, tag::F32];
+        bytes.extend_from_slice(&1.5f32.to_bits().to_le_bytes());
+        bytes.push(tag::BUFFER);
+        bytes.extend_from_slice(&0x1000u64.to_le_bytes());
+        bytes.extend_from_slice(&[3, 0, 0, 0, 1, 2, 3]);
+        bytes.push(tag::STRING);
+        bytes.extend_from_slice(&0x2000u64.to_le_bytes());
+        bytes.extend_from_slice(&[2, 0, 0, 0, b'h', b'i']);
+
+        let input = &mut &bytes[..];
+        assert_eq!(read_value(input), Ok(TraceValue::I32(-1)));
+        assert_eq!(read_value(input), Ok(TraceValue::F32(1.5)));
+        let buffer = read_value(input).unwrap();
+        assert_eq!(buffer.as_u64(), Some(0x1000));
+        assert_eq!(buffer.data(), Some(&[1, 2, 3][..]));
+        assert_eq!(
+            read_value(input),
+            Ok(TraceValue::String {
+                address: 0x2000,
+                data: b"hi".to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert_eq!(
+            TraceReader::new(&b"GLTRACE"[..]).err(),
+            Some(TraceError::Truncated)
+        );
+        assert_eq!(
+            TraceReader::new(&b"NOTATRACE\0"[..]).err(),
+            Some(TraceError::NotATrace)
+        );
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[9, 0]);
+        assert_eq!(
+            TraceReader::new(&bytes[..]).err(),
+            Some(TraceError::UnsupportedVersion(9))
+        );
+
+        let mut bytes = header();
+        bytes.extend_from_slice(&[5, 0]);
+        let mut trace = TraceReader::new(&bytes[..]).unwrap();
+        assert_eq!(trace.read_call(), Err(TraceError::UnknownCommand(5)));
+
+        let mut bytes = header();
+        bytes.extend_from_slice(&[0, 0, 0x7F]);
+        let mut trace = TraceReader::new(&bytes[..]).unwrap();
+        assert_eq!(trace.read_call(), Err(TraceError::UnknownTag(0x7F)));
+
+        let mut bytes = header();
+        bytes.extend_from_slice(&[0, 0, tag::U32, 1]);
+        let mut trace = TraceReader::new(&bytes[..]).unwrap();
+        assert_eq!(trace.read_call(), Err(TraceError::Truncated));
+
+        // a buffer that claims to be 4 GiB long, but ends after 3 bytes
+        let mut bytes = vec![tag::BUFFER];
+        bytes.extend_from_slice(&0x1000u64.to_le_bytes());
+        bytes.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 1, 2, 3]);
+        assert_eq!(read_value(&mut &bytes[..]), Err(TraceError::Truncated));
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The reader of the trace format, which `trace.rs` includes and `GeneratorOptions::replay` writes
+// into the `replay` module of the bindings, so that the format only has one reader. It has no
+// `use` items, because both places import `std::error`, `std::fmt` and `std::io::{self, Read}`.
+
+/// The first bytes of every trace.
+pub(crate) const MAGIC: &[u8; 8] = b"GLTRACE\0";
+
+/// The version of the format that is written and read.
+pub(crate) const VERSION: u16 = 1;
+
+/// The tags that start each value in a trace.
+pub(crate) mod tag {
+    pub const U8: u8 = 0x01;
+    pub const I8: u8 = 0x02;
+    pub const U16: u8 = 0x03;
+    pub const I16: u8 = 0x04;
+    pub const U32: u8 = 0x05;
+    pub const I32: u8 = 0x06;
+    pub const U64: u8 = 0x07;
+    pub const I64: u8 = 0x08;
+    pub const F32: u8 = 0x09;
+    pub const F64: u8 = 0x0A;
+    /// An address as a `u64`.
+    pub const POINTER: u8 = 0x0B;
+    /// An address as a `u64`, followed by the number of bytes it points to as a `u32`, and the
+    /// bytes themselves.
+    pub const BUFFER: u8 = 0x0C;
+    /// Like `BUFFER`, for a nul-terminated string. The nul is not included.
+    pub const STRING: u8 = 0x0D;
+}
+
+/// A command in the header of a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceCommand {
+    /// The name of the command, for example `"glBindTexture"`.
+    pub name: String,
+    /// The number of parameters that the command takes.
+    pub params: usize,
+    /// Whether the command returns a value.
+    pub returns: bool,
+}
+
+/// A recorded call of a command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceCall {
+    /// The index of the command in `TraceReader::commands`.
+    pub command: usize,
+    /// The arguments of the call.
+    pub args: Vec<TraceValue>,
+    /// The value that the call returned, if the command returns one.
+    pub ret: Option<TraceValue>,
+}
+
+/// An argument or return value in a trace.
+///
+/// Pointers are recorded as `Buffer`s when the registry gives the number of elements that they
+/// point to, and as `String`s when they are strings of unknown length. The contents are recorded
+/// after the call returns, so they include anything that the command wrote.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Pointer(u64),
+    Buffer { address: u64, data: Vec<u8> },
+    String { address: u64, data: Vec<u8> },
+}
+
+impl TraceValue {
+    /// The value as a `u64`, if it is an integer or a pointer. Signed integers are sign-extended.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            TraceValue::U8(v) => Some(v as u64),
+            TraceValue::I8(v) => Some(v as u64),
+            TraceValue::U16(v) => Some(v as u64),
+            TraceValue::I16(v) => Some(v as u64),
+            TraceValue::U32(v) => Some(v as u64),
+            TraceValue::I32(v) => Some(v as u64),
+            TraceValue::U64(v) => Some(v),
+            TraceValue::I64(v) => Some(v as u64),
+            TraceValue::Pointer(address)
+            | TraceValue::Buffer { address, .. }
+            | TraceValue::String { address, .. } => Some(address),
+            TraceValue::F32(_) | TraceValue::F64(_) => None,
+        }
+    }
+
+    /// The bytes that a pointer pointed to, if they were recorded.
+    pub fn data(&self) -> Option<&[u8]> {
+        match *self {
+            TraceValue::Buffer { ref data, .. } | TraceValue::String { ref data, .. } => Some(data),
+            _ => None,
+        }
+    }
+}
+
+/// An error from reading a trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceError {
+    /// The input could not be read.
+    Io(String),
+    /// The input does not start with the magic bytes of a trace.
+    NotATrace,
+    /// The trace was written in a version of the format that this reader doesn't know.
+    UnsupportedVersion(u16),
+    /// The input ended in the middle of the header or of a call.
+    Truncated,
+    /// A name in the header is not valid UTF-8.
+    InvalidName,
+    /// A call refers to a command that is not in the header.
+    UnknownCommand(u16),
+    /// A value starts with a tag that is not in the format.
+    UnknownTag(u8),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TraceError::Io(ref message) => write!(fmt, "failed to read trace: {}", message),
+            TraceError::NotATrace => write!(fmt, "not a GL trace"),
+            TraceError::UnsupportedVersion(version) => {
+                write!(fmt, "unsupported trace format version {}", version)
+            },
+            TraceError::Truncated => write!(fmt, "trace ended unexpectedly"),
+            TraceError::InvalidName => write!(fmt, "command name is not valid UTF-8"),
+            TraceError::UnknownCommand(id) => write!(fmt, "call of unknown command {}", id),
+            TraceError::UnknownTag(tag) => write!(fmt, "unknown value tag 0x{:02X}", tag),
+        }
+    }
+}
+
+impl error::Error for TraceError {}
+
+impl From<io::Error> for TraceError {
+    fn from(err: io::Error) -> TraceError {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => TraceError::Truncated,
+            _ => TraceError::Io(err.to_string()),
+        }
+    }
+}
+
+/// Reads the calls from a trace written by the bindings from `TracingGenerator`.
+pub struct TraceReader<R> {
+    input: R,
+    api: String,
+    commands: Vec<TraceCommand>,
+}
+
+impl<R: io::Read> TraceReader<R> {
+    /// Reads the header of a trace.
+    pub fn new(mut input: R) -> Result<TraceReader<R>, TraceError> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if magic != *MAGIC {
+            return Err(TraceError::NotATrace);
+        }
+        let version = read_u16(&mut input)?;
+        if version != VERSION {
+            return Err(TraceError::UnsupportedVersion(version));
+        }
+
+        let api = read_name(&mut input)?;
+        let count = read_u16(&mut input)?;
+        let mut commands = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let name = read_name(&mut input)?;
+            let params = read_u8(&mut input)? as usize;
+            let returns = read_u8(&mut input)? != 0;
+            commands.push(TraceCommand {
+                name,
+                params,
+                returns,
+            });
+        }
+
+        Ok(TraceReader {
+            input,
+            api,
+            commands,
+        })
+    }
+
+    /// The API of the bindings that wrote the trace, for example `"gl"`.
+    pub fn api(&self) -> &str {
+        &self.api
+    }
+
+    /// The commands of the bindings that wrote the trace. `TraceCall::command` is an index into
+    /// this list.
+    pub fn commands(&self) -> &[TraceCommand] {
+        &self.commands
+    }
+
+    /// Reads the next call, or returns `None` at the end of the trace.
+    pub fn read_call(&mut self) -> Result<Option<TraceCall>, TraceError> {
+        let mut id = [0; 2];
+        match self.input.read(&mut id[..1])? {
+            0 => return Ok(None),
+            _ => self.input.read_exact(&mut id[1..])?,
+        }
+        let id = u16::from_le_bytes(id);
+        let (params, returns) = match self.commands.get(id as usize) {
+            Some(command) => (command.params, command.returns),
+            None => return Err(TraceError::UnknownCommand(id)),
+        };
+
+        let mut args = Vec::with_capacity(params);
+        for _ in 0..params {
+            args.push(read_value(&mut self.input)?);
+        }
+        let ret = match returns {
+            true => Some(read_value(&mut self.input)?),
+            false => None,
+        };
+
+        Ok(Some(TraceCall {
+            command: id as usize,
+            args,
+            ret,
+        }))
+    }
+}
+
+impl<R: io::Read> Iterator for TraceReader<R> {
+    type Item = Result<TraceCall, TraceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_call() {
+            Ok(Some(call)) => Some(Ok(call)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Reads `len` bytes. The buffer grows as the bytes are read, rather than being allocated up
+/// front, so that a corrupt length in a truncated trace doesn't allocate gigabytes.
+fn read_array<R: io::Read>(input: &mut R, len: usize) -> Result<Vec<u8>, TraceError> {
+    let mut bytes = Vec::new();
+    input.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(TraceError::Truncated);
+    }
+    Ok(bytes)
+}
+
+fn read_u8<R: io::Read>(input: &mut R) -> Result<u8, TraceError> {
+    let mut bytes = [0; 1];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u16<R: io::Read>(input: &mut R) -> Result<u16, TraceError> {
+    let mut bytes = [0; 2];
+    input.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32<R: io::Read>(input: &mut R) -> Result<u32, TraceError> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: io::Read>(input: &mut R) -> Result<u64, TraceError> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_name<R: io::Read>(input: &mut R) -> Result<String, TraceError> {
+    let len = read_u8(input)? as usize;
+    String::from_utf8(read_array(input, len)?).map_err(|_| TraceError::InvalidName)
+}
+
+fn read_value<R: io::Read>(input: &mut R) -> Result<TraceValue, TraceError> {
+    Ok(match read_u8(input)? {
+        tag::U8 => TraceValue::U8(read_u8(input)?),
+        tag::I8 => TraceValue::I8(read_u8(input)? as i8),
+        tag::U16 => TraceValue::U16(read_u16(input)?),
+        tag::I16 => TraceValue::I16(read_u16(input)? as i16),
+        tag::U32 => TraceValue::U32(read_u32(input)?),
+        tag::I32 => TraceValue::I32(read_u32(input)? as i32),
+        tag::U64 => TraceValue::U64(read_u64(input)?),
+        tag::I64 => TraceValue::I64(read_u64(input)? as i64),
+        tag::F32 => TraceValue::F32(f32::from_bits(read_u32(input)?)),
+        tag::F64 => TraceValue::F64(f64::from_bits(read_u64(input)?)),
+        tag::POINTER => TraceValue::Pointer(read_u64(input)?),
+        tag::BUFFER => {
+            let address = read_u64(input)?;
+            let len = read_u32(input)? as usize;
+            TraceValue::Buffer {
+                address,
+                data: read_array(input, len)?,
+            }
+        },
+        tag::STRING => {
+            let address = read_u64(input)?;
+            let len = read_u32(input)? as usize;
+            TraceValue::String {
+                address,
+                data: read_array(input, len)?,
+            }
+        },
+        other => return Err(TraceError::UnknownTag(other)),
+    })
+}
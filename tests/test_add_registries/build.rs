@@ -29,4 +29,11 @@ fn main() {
     (registry0 + registry1)
         .write_bindings(GlobalGenerator, &mut file)
         .unwrap();
+
+    let mut registry = Registry::new(Api::Gl, (3, 2), Profile::Core, Fallbacks::All, []);
+    let newer = Registry::new(Api::Gl, (4, 5), Profile::Core, Fallbacks::All, []);
+    registry.merge(newer, MergePolicy::Error).unwrap();
+
+    let mut file = File::create(Path::new(&dest).join("test_merge_registries.rs")).unwrap();
+    registry.write_bindings(GlobalGenerator, &mut file).unwrap();
 }
@@ -15,3 +15,15 @@
 pub mod gl {
     include!(concat!(env!("OUT_DIR"), "/test_add_registries.rs"));
 }
+
+pub mod gl_merged {
+    include!(concat!(env!("OUT_DIR"), "/test_merge_registries.rs"));
+}
+
+#[test]
+fn test_merged_commands() {
+    let _: unsafe fn(gl_merged::types::GLenum) -> gl_merged::types::GLuint =
+        gl_merged::CreateShader;
+    let _: unsafe fn(gl_merged::types::GLuint, gl_merged::types::GLuint) =
+        gl_merged::BindTextureUnit;
+}
@@ -0,0 +1,154 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running the bindings written by `DebugStructGenerator` against fake commands, with a logger
+//! that records what it is given.
+#![deny(warnings)]
+
+extern crate log;
+
+include!(concat!(env!("OUT_DIR"), "/test_call_logger.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::fmt::Debug;
+    use std::os::raw::c_void;
+    use std::ptr;
+
+    thread_local! {
+        /// The error flags that the fake `GetError` returns, in order.
+        static ERRORS: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+        /// The calls and errors passed to `RecordingLogger`.
+        static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct RecordingLogger;
+
+    impl gl::CallLogger for RecordingLogger {
+        fn log_call(&self, command: &'static str, args: &[&dyn Debug]) {
+            let entry = format!("{}{:?}", command, args);
+            LOG.with(|log| log.borrow_mut().push(entry));
+        }
+
+        fn log_error(&self, command: &'static str, error: u32) {
+            let entry = format!("0x{:04X} after {}", error, command);
+            LOG.with(|log| log.borrow_mut().push(entry));
+        }
+    }
+
+    impl gl_typed::CallLogger for RecordingLogger {
+        fn log_call(&self, command: &'static str, args: &[&dyn Debug]) {
+            gl::CallLogger::log_call(self, command, args)
+        }
+
+        fn log_error(&self, command: &'static str, error: u32) {
+            gl::CallLogger::log_error(self, command, error)
+        }
+    }
+
+    fn take_log() -> Vec<String> {
+        LOG.with(|log| log.borrow_mut().drain(..).collect())
+    }
+
+    extern "system" fn get_error() -> u32 {
+        ERRORS.with(|flags| {
+            let mut flags = flags.borrow_mut();
+            match flags.len() {
+                0 => 0,
+                _ => flags.remove(0),
+            }
+        })
+    }
+
+    extern "system" fn bind_texture(_: u32, _: u32) {}
+
+    extern "system" fn clear(_: u32) {}
+
+    extern "system" fn buffer_data(_: u32, _: isize, _: *const c_void, _: u32) {}
+
+    fn load(symbol: &str) -> *const c_void {
+        match symbol {
+            "glGetError" => get_error as *const c_void,
+            "glBindTexture" => bind_texture as *const c_void,
+            "glClear" => clear as *const c_void,
+            "glBufferData" => buffer_data as *const c_void,
+            _ => ptr::null(),
+        }
+    }
+
+    #[test]
+    fn test_enum_args_are_named() {
+        let mut gl = gl::Gl::load_with(load);
+        gl.set_logger(RecordingLogger);
+        unsafe {
+            gl.BindTexture(gl::TEXTURE_2D, 7);
+            gl.BufferData(gl::ARRAY_BUFFER, 0, ptr::null(), gl::STATIC_DRAW);
+            gl.Clear(gl::COLOR_BUFFER_BIT);
+        }
+        assert_eq!(
+            take_log(),
+            [
+                "glBindTexture[TEXTURE_2D, 7]",
+                "glBufferData[ARRAY_BUFFER, 0, 0x0, STATIC_DRAW]",
+                "glClear[16384]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_enum_values_are_hex() {
+        let mut gl = gl::Gl::load_with(load);
+        gl.set_logger(RecordingLogger);
+        unsafe { gl.BindTexture(0x1234, 0) };
+        assert_eq!(take_log(), ["glBindTexture[0x1234, 0]"]);
+    }
+
+    #[test]
+    fn test_errors_are_logged() {
+        let mut gl = gl::Gl::load_with(load);
+        gl.set_logger(RecordingLogger);
+        ERRORS.with(|flags| flags.borrow_mut().push(gl::INVALID_ENUM));
+        unsafe { gl.BindTexture(0x1234, 0) };
+        assert_eq!(
+            take_log(),
+            ["glBindTexture[0x1234, 0]", "0x0500 after glBindTexture"]
+        );
+    }
+
+    #[test]
+    fn test_typed_groups_are_named() {
+        let mut gl = gl_typed::Gl::load_with(load);
+        gl.set_logger(RecordingLogger);
+        unsafe {
+            gl.BindTexture(gl_typed::groups::TextureTarget::TEXTURE_2D, 7);
+            gl.Clear(gl_typed::groups::ClearBufferMask::COLOR_BUFFER_BIT);
+        }
+        assert_eq!(
+            take_log(),
+            [
+                "glBindTexture[TEXTURE_2D, 7]",
+                "glClear[ClearBufferMask(COLOR_BUFFER_BIT)]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_logger() {
+        let gl = gl::Gl::load_with(load);
+        unsafe { gl.BindTexture(gl::TEXTURE_2D, 0) };
+        assert!(take_log().is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate gl_generator;
+
+use gl_generator::*;
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::*;
+
+fn main() {
+    let dest = env::var("OUT_DIR").unwrap();
+    let mut file = File::create(Path::new(&dest).join("test_error_checking.rs")).unwrap();
+
+    let registry = Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, []);
+    let options = GeneratorOptions {
+        typed_groups: true,
+        slices: true,
+        ..GeneratorOptions::default()
+    };
+
+    let bindings = [
+        (
+            "gl_result_struct",
+            CommandStyle::Methods,
+            ErrorReport::Result,
+        ),
+        (
+            "gl_result_global",
+            CommandStyle::Functions,
+            ErrorReport::Result,
+        ),
+        ("gl_hook_struct", CommandStyle::Methods, ErrorReport::Hook),
+        ("gl_hook_global", CommandStyle::Functions, ErrorReport::Hook),
+    ];
+    for &(name, style, report) in &bindings {
+        writeln!(&mut file, "pub mod {} {{", name).unwrap();
+        registry
+            .write_bindings_with_options(
+                ErrorCheckingGenerator { style, report },
+                &options,
+                &mut file,
+            )
+            .unwrap();
+        writeln!(&mut file, "}}").unwrap();
+    }
+
+    let generator = ErrorCheckingGenerator {
+        style: CommandStyle::Methods,
+        report: ErrorReport::Result,
+    };
+
+    writeln!(&mut file, "pub mod gl_compatibility {{").unwrap();
+    Registry::new(Api::Gl, (2, 1), Profile::Compatibility, Fallbacks::All, [])
+        .write_bindings(generator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
+    writeln!(&mut file, "pub mod glx {{").unwrap();
+    Registry::new(Api::Glx, (1, 4), Profile::Core, Fallbacks::All, [])
+        .write_bindings(generator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+}
@@ -0,0 +1,202 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Running the bindings written by `ErrorCheckingGenerator` against fake commands.
+#![deny(warnings)]
+
+extern crate log;
+
+use std::ptr;
+
+include!(concat!(env!("OUT_DIR"), "/test_error_checking.rs"));
+
+#[allow(dead_code)]
+unsafe fn compile_test_unchecked(gl: &gl_compatibility::Gl, glx: &glx::Glx) {
+    // Commands that may be called between `Begin` and `End` are not checked, and `End` reports
+    // their errors
+    let () = gl.Begin(gl_compatibility::TRIANGLES);
+    let () = gl.Vertex3f(0.0, 0.0, 0.0);
+    let () = gl.Materialfv(
+        gl_compatibility::FRONT,
+        gl_compatibility::AMBIENT,
+        ptr::null(),
+    );
+    let _: Result<(), _> = gl.End();
+    let _: Result<(), _> = gl.VertexPointer(3, gl_compatibility::FLOAT, 0, ptr::null());
+    let _: Result<(), _> = gl.ColorMask(0, 0, 0, 0);
+
+    // GLX has no `GetError`
+    let _: glx::types::GLXContext = glx.GetCurrentContext();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::os::raw::c_void;
+
+    const INVALID_ENUM: u32 = 0x0500;
+    const INVALID_VALUE: u32 = 0x0501;
+    const OUT_OF_MEMORY: u32 = 0x0505;
+
+    thread_local! {
+        /// The error flags that the fake `GetError` returns, in order.
+        static ERRORS: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+        /// The errors passed to `record_hook`.
+        static REPORTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Makes the next command fail with `errors`.
+    fn fail_with(errors: &[u32]) {
+        ERRORS.with(|flags| flags.borrow_mut().extend_from_slice(errors));
+    }
+
+    extern "system" fn get_error() -> u32 {
+        ERRORS.with(|flags| {
+            let mut flags = flags.borrow_mut();
+            match flags.len() {
+                0 => 0,
+                _ => flags.remove(0),
+            }
+        })
+    }
+
+    extern "system" fn clear(_: u32) {}
+
+    extern "system" fn gen_buffers(n: i32, buffers: *mut u32) {
+        for i in 0..n as usize {
+            unsafe { *buffers.add(i) = i as u32 + 1 };
+        }
+    }
+
+    fn load(symbol: &str) -> *const c_void {
+        match symbol {
+            "glGetError" => get_error as *const c_void,
+            "glClear" => clear as *const c_void,
+            "glGenBuffers" => gen_buffers as *const c_void,
+            _ => ptr::null(),
+        }
+    }
+
+    fn record_hook(
+        error: gl_hook_global::errors::GlError,
+        command: &'static str,
+        args: &[&dyn std::fmt::Debug],
+    ) {
+        let report = format!("{} after {}{:?}", error, command, args);
+        REPORTS.with(|reports| reports.borrow_mut().push(report));
+    }
+
+    #[test]
+    fn test_result_struct() {
+        use gl_result_struct::errors::GlError;
+        use gl_result_struct::groups::ClearBufferMask;
+
+        let gl = gl_result_struct::Gl::load_with(load);
+        unsafe {
+            assert_eq!(gl.Clear(ClearBufferMask::COLOR_BUFFER_BIT), Ok(()));
+
+            // Only the first error is returned, and the rest are cleared
+            fail_with(&[INVALID_VALUE, OUT_OF_MEMORY]);
+            assert_eq!(
+                gl.Clear(ClearBufferMask::COLOR_BUFFER_BIT),
+                Err(GlError::InvalidValue)
+            );
+            assert_eq!(gl.Clear(ClearBufferMask::COLOR_BUFFER_BIT), Ok(()));
+
+            let mut buffers = [0; 2];
+            assert_eq!(gl.slices().GenBuffers(&mut buffers), Ok(()));
+            assert_eq!(buffers, [1, 2]);
+
+            let _: gl_result_struct::groups::ErrorCode = gl.GetError();
+        }
+    }
+
+    #[test]
+    fn test_result_global() {
+        use gl_result_global::errors::GlError;
+        use gl_result_global::groups::ClearBufferMask;
+
+        gl_result_global::load_with(load);
+        unsafe {
+            fail_with(&[OUT_OF_MEMORY]);
+            assert_eq!(
+                gl_result_global::Clear(ClearBufferMask::DEPTH_BUFFER_BIT),
+                Err(GlError::OutOfMemory)
+            );
+
+            fail_with(&[0x9999]);
+            let mut buffers = [0; 2];
+            assert_eq!(
+                gl_result_global::slices::GenBuffers(&mut buffers),
+                Err(GlError::Other(0x9999))
+            );
+        }
+    }
+
+    #[test]
+    fn test_hook_global() {
+        use gl_hook_global::groups::ClearBufferMask;
+
+        gl_hook_global::load_with(load);
+        gl_hook_global::errors::set_hook(record_hook);
+        unsafe {
+            gl_hook_global::Clear(ClearBufferMask::COLOR_BUFFER_BIT);
+            fail_with(&[INVALID_VALUE]);
+            gl_hook_global::Clear(
+                ClearBufferMask::COLOR_BUFFER_BIT | ClearBufferMask::from_bits(0x1),
+            );
+        }
+        REPORTS.with(|reports| {
+            assert_eq!(
+                *reports.borrow(),
+                ["INVALID_VALUE after glClear[ClearBufferMask(COLOR_BUFFER_BIT | 0x1)]"]
+            )
+        });
+    }
+
+    #[test]
+    fn test_default_hook() {
+        use gl_hook_struct::errors::{default_hook, GlError};
+
+        // Logs the error instead of panicking
+        default_hook(GlError::InvalidEnum, "glClear", &[&0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "INVALID_ENUM after glClear(ClearBufferMask(empty))")]
+    fn test_panic_hook() {
+        let gl = gl_hook_struct::Gl::load_with(load);
+        gl_hook_struct::errors::set_hook(gl_hook_struct::errors::panic_hook);
+        fail_with(&[INVALID_ENUM]);
+        unsafe { gl.Clear(gl_hook_struct::groups::ClearBufferMask::empty()) };
+    }
+
+    #[test]
+    fn test_gl_error() {
+        use gl_result_global::errors::GlError;
+
+        assert_eq!(GlError::from_code(0), None);
+        assert_eq!(
+            GlError::from_code(gl_result_global::INVALID_OPERATION),
+            Some(GlError::InvalidOperation)
+        );
+        assert_eq!(
+            GlError::InvalidFramebufferOperation.code(),
+            gl_result_global::INVALID_FRAMEBUFFER_OPERATION
+        );
+        assert_eq!(GlError::ContextLost.to_string(), "CONTEXT_LOST");
+        assert_eq!(GlError::Other(0x9999).to_string(), "error 0x9999");
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate gl_generator;
+
+use gl_generator::*;
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::*;
+
+fn main() {
+    let dest = env::var("OUT_DIR").unwrap();
+    let mut file = File::create(Path::new(&dest).join("test_generator_options.rs")).unwrap();
+
+    let registry = Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, []);
+    let options = GeneratorOptions {
+        typed_groups: true,
+        slices: true,
+        enum_names: true,
+        ..GeneratorOptions::default()
+    };
+
+    writeln!(&mut file, "pub mod gl_global {{").unwrap();
+    registry
+        .write_bindings_with_options(GlobalGenerator, &options, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
+    writeln!(&mut file, "pub mod gl_struct {{").unwrap();
+    registry
+        .write_bindings_with_options(StructGenerator, &options, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
+    let options = GeneratorOptions {
+        typed_handles: true,
+        slices: true,
+        ..GeneratorOptions::default()
+    };
+
+    writeln!(&mut file, "pub mod gl_handles {{").unwrap();
+    registry
+        .write_bindings_with_options(GlobalGenerator, &options, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
+    let options = GeneratorOptions {
+        type_map: TypeMap::new()
+            .alias("GLsizeiptr", "usize")
+            .define(
+                "GLboolean",
+                "#[repr(transparent)] #[derive(Copy, Clone, Debug, PartialEq, Eq)] \
+                 pub struct GLboolean(pub bool);",
+            )
+            .constants("GLboolean", "types::GLboolean({} != 0)"),
+        ..GeneratorOptions::default()
+    };
+
+    writeln!(&mut file, "pub mod gl_overrides {{").unwrap();
+    registry
+        .write_bindings_with_options(GlobalGenerator, &options, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+}
@@ -0,0 +1,176 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Making sure that the extras enabled by `GeneratorOptions` compile without warnings.
+#![deny(warnings)]
+
+include!(concat!(env!("OUT_DIR"), "/test_generator_options.rs"));
+
+#[allow(dead_code)]
+unsafe fn compile_test_typed_groups(gl: &gl_struct::Gl) {
+    use gl_global::groups::TextureTarget;
+
+    gl_global::BindTexture(TextureTarget::TEXTURE_2D, 0);
+    gl_global::BindTexture(TextureTarget(gl_global::TEXTURE_3D), 0);
+    gl.BindTexture(gl_struct::groups::TextureTarget::TEXTURE_2D, 0);
+}
+
+#[allow(dead_code)]
+unsafe fn compile_test_bitmask_groups() {
+    use gl_global::groups::ClearBufferMask;
+
+    gl_global::Clear(ClearBufferMask::COLOR_BUFFER_BIT | ClearBufferMask::DEPTH_BUFFER_BIT);
+    gl_global::Clear(ClearBufferMask::empty());
+}
+
+#[allow(dead_code)]
+unsafe fn compile_test_slices(gl: &gl_struct::Gl) {
+    let mut buffers = [0; 4];
+    gl_global::slices::GenBuffers(&mut buffers);
+    gl_global::slices::DeleteBuffers(&buffers);
+    gl_global::slices::UniformMatrix4fv(0, &[[0.0; 16]], gl_global::FALSE);
+
+    gl.slices().GenBuffers(&mut buffers);
+    gl.slices().DeleteBuffers(&buffers);
+}
+
+#[allow(dead_code)]
+unsafe fn compile_test_typed_handles() {
+    use gl_handles::handles::{Buffer, Program, Texture};
+
+    let mut buffers = [Buffer::default(); 4];
+    gl_handles::GenBuffers(buffers.len() as _, buffers.as_mut_ptr());
+    gl_handles::slices::GenBuffers(&mut buffers);
+    gl_handles::BindBuffer(gl_handles::ARRAY_BUFFER, buffers[0]);
+    gl_handles::slices::DeleteBuffers(&buffers);
+
+    gl_handles::BindTexture(gl_handles::TEXTURE_2D, Texture(0));
+
+    let program: Program = gl_handles::CreateProgram();
+    gl_handles::UseProgram(program);
+    gl_handles::DeleteProgram(program);
+}
+
+#[allow(dead_code)]
+unsafe fn compile_test_type_map() {
+    let size: usize = 16;
+    gl_overrides::BufferData(
+        gl_overrides::ARRAY_BUFFER,
+        size,
+        std::ptr::null(),
+        gl_overrides::STATIC_DRAW,
+    );
+
+    let enabled: gl_overrides::types::GLboolean = gl_overrides::IsEnabled(gl_overrides::BLEND);
+    gl_overrides::DepthMask(enabled);
+}
+
+#[test]
+fn test_typed_group_values() {
+    use gl_global::groups::TextureTarget;
+
+    assert_eq!(TextureTarget::TEXTURE_2D.0, gl_global::TEXTURE_2D);
+    assert_eq!(
+        TextureTarget::from(gl_global::TEXTURE_2D),
+        TextureTarget::TEXTURE_2D
+    );
+    assert_eq!(u32::from(TextureTarget::TEXTURE_2D), 0x0DE1);
+}
+
+#[test]
+fn test_bitmask_group_values() {
+    use gl_global::groups::ClearBufferMask;
+
+    let mut mask = ClearBufferMask::COLOR_BUFFER_BIT | ClearBufferMask::DEPTH_BUFFER_BIT;
+    assert!(mask.contains(ClearBufferMask::COLOR_BUFFER_BIT));
+    assert_eq!(
+        mask.bits(),
+        gl_global::COLOR_BUFFER_BIT | gl_global::DEPTH_BUFFER_BIT
+    );
+
+    mask.remove(ClearBufferMask::COLOR_BUFFER_BIT);
+    assert_eq!(
+        mask & ClearBufferMask::COLOR_BUFFER_BIT,
+        ClearBufferMask::empty()
+    );
+    assert_eq!(ClearBufferMask::from_bits(mask.bits()), mask);
+    assert!(ClearBufferMask::empty().is_empty());
+}
+
+#[test]
+fn test_bitmask_group_debug() {
+    use gl_global::groups::ClearBufferMask;
+
+    let mask = ClearBufferMask::DEPTH_BUFFER_BIT | ClearBufferMask::from_bits(0x1);
+    assert_eq!(
+        format!("{:?}", mask),
+        "ClearBufferMask(DEPTH_BUFFER_BIT | 0x1)"
+    );
+    assert_eq!(
+        format!("{:?}", ClearBufferMask::empty()),
+        "ClearBufferMask(empty)"
+    );
+}
+
+#[test]
+fn test_enum_names() {
+    assert_eq!(
+        gl_global::enum_name(gl_global::TEXTURE_2D),
+        Some("TEXTURE_2D")
+    );
+    assert_eq!(
+        gl_global::enum_name(gl_global::STATIC_DRAW),
+        Some("STATIC_DRAW")
+    );
+    assert_eq!(gl_global::enum_name(0x1234_5678), None);
+    assert_eq!(gl_struct::enum_name(0x0DE1), Some("TEXTURE_2D"));
+}
+
+#[test]
+fn test_enum_names_in_groups() {
+    use gl_global::enum_name_in;
+    use gl_global::groups::TextureTarget;
+
+    assert_eq!(enum_name_in("PrimitiveType", 0x0001), Some("LINES"));
+    assert_eq!(enum_name_in("BlendingFactor", 0x0001), Some("ONE"));
+    assert_eq!(enum_name_in("ErrorCode", 0), Some("NO_ERROR"));
+    assert_eq!(enum_name_in("StencilOp", 0), Some("ZERO"));
+    assert_eq!(
+        enum_name_in("TextureTarget", TextureTarget::TEXTURE_3D.0),
+        Some("TEXTURE_3D")
+    );
+    assert_eq!(enum_name_in("TextureTarget", gl_global::LINES), None);
+    assert_eq!(enum_name_in("NoSuchGroup", gl_global::TEXTURE_2D), None);
+    assert_eq!(
+        enum_name_in("ClearBufferMask", gl_global::COLOR_BUFFER_BIT),
+        None
+    );
+}
+
+#[test]
+fn test_type_map_constants() {
+    use gl_overrides::types::GLboolean;
+
+    assert_eq!(gl_overrides::TRUE, GLboolean(true));
+    assert_eq!(gl_overrides::FALSE, GLboolean(false));
+}
+
+#[test]
+fn test_typed_handle_values() {
+    use gl_handles::handles::Buffer;
+
+    assert_eq!(Buffer::default(), Buffer(0));
+    assert_eq!(Buffer::from(7).0, 7);
+    assert_eq!(u32::from(Buffer(7)), 7);
+}
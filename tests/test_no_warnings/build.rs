@@ -58,6 +58,12 @@ fn main() {
         .unwrap();
     writeln!(&mut file, "}}").unwrap();
 
+    writeln!(&mut file, "mod gl_tracing_struct {{").unwrap();
+    gl_registry
+        .write_bindings(TracingGenerator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
     // Glx
 
     let glx_registry = Registry::new(Api::Glx, (1, 4), Profile::Core, Fallbacks::All, []);
@@ -92,6 +98,12 @@ fn main() {
         .unwrap();
     writeln!(&mut file, "}}").unwrap();
 
+    writeln!(&mut file, "mod glx_tracing_struct {{").unwrap();
+    glx_registry
+        .write_bindings(TracingGenerator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
     // Wgl
 
     let wgl_registry = Registry::new(Api::Wgl, (1, 0), Profile::Core, Fallbacks::All, []);
@@ -126,6 +138,12 @@ fn main() {
         .unwrap();
     writeln!(&mut file, "}}").unwrap();
 
+    writeln!(&mut file, "mod wgl_tracing_struct {{").unwrap();
+    wgl_registry
+        .write_bindings(TracingGenerator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
     // Gles1
 
     let gles1_registry = Registry::new(Api::Gles1, (1, 1), Profile::Core, Fallbacks::All, []);
@@ -160,6 +178,12 @@ fn main() {
         .unwrap();
     writeln!(&mut file, "}}").unwrap();
 
+    writeln!(&mut file, "mod gles1_tracing_struct {{").unwrap();
+    gles1_registry
+        .write_bindings(TracingGenerator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
     // Gles2
 
     let gles2_registry = Registry::new(Api::Gles2, (3, 1), Profile::Core, Fallbacks::All, []);
@@ -194,6 +218,12 @@ fn main() {
         .unwrap();
     writeln!(&mut file, "}}").unwrap();
 
+    writeln!(&mut file, "mod gles2_tracing_struct {{").unwrap();
+    gles2_registry
+        .write_bindings(TracingGenerator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
+
     // Egl
 
     let egl_registry = Registry::new(Api::Egl, (1, 5), Profile::Core, Fallbacks::All, []);
@@ -232,6 +262,17 @@ fn main() {
         .write_bindings(DebugStructGenerator, &mut file)
         .unwrap();
     writeln!(&mut file, "}}").unwrap();
+
+    writeln!(
+        &mut file,
+        "mod egl_tracing_struct {{ {}",
+        build_egl_symbols()
+    )
+    .unwrap();
+    egl_registry
+        .write_bindings(TracingGenerator, &mut file)
+        .unwrap();
+    writeln!(&mut file, "}}").unwrap();
 }
 
 fn build_egl_symbols() -> &'static str {
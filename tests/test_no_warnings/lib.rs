@@ -15,4 +15,6 @@
 //! Making sure that no warning is generated by code from generate_gl_bindings!
 #![deny(warnings)]
 
+extern crate log;
+
 include!(concat!(env!("OUT_DIR"), "/test_no_warnings.rs"));
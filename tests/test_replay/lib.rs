@@ -0,0 +1,489 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording calls to fake commands with the bindings from `TracingGenerator`, and replaying
+//! them through the `replay` modules to fake commands that create objects with other names.
+#![deny(warnings)]
+
+include!(concat!(env!("OUT_DIR"), "/test_replay.rs"));
+
+#[cfg(test)]
+mod tests {
+    extern crate gl_generator;
+
+    use super::gl::replay::{NameMap, ReplayError, TraceCall, TraceError, TraceReader, TraceValue};
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::ffi::CStr;
+    use std::io;
+    use std::os::raw::{c_char, c_void};
+    use std::ptr;
+    use std::slice;
+    use std::sync::{Arc, Mutex};
+
+    /// A writer whose bytes can be read after it has been given to `start_trace`.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    thread_local! {
+        /// The name of the next object that the fake commands create.
+        static NEXT_NAME: Cell<u32> = const { Cell::new(1) };
+        /// The calls of the fake commands.
+        static CALLS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn log(call: String) {
+        CALLS.with(|calls| calls.borrow_mut().push(call));
+    }
+
+    fn next_name() -> u32 {
+        NEXT_NAME.with(|next| {
+            let name = next.get();
+            next.set(name + 1);
+            name
+        })
+    }
+
+    /// Starts recording the calls of the fake commands, which will name objects from `first`.
+    fn reset(first: u32) {
+        NEXT_NAME.with(|next| next.set(first));
+        CALLS.with(|calls| calls.borrow_mut().clear());
+    }
+
+    fn calls() -> Vec<String> {
+        CALLS.with(|calls| calls.borrow().clone())
+    }
+
+    extern "system" fn gen_buffers(n: i32, buffers: *mut u32) {
+        for i in 0..n as usize {
+            unsafe { *buffers.add(i) = next_name() };
+        }
+        log(format!("GenBuffers({})", n));
+    }
+
+    extern "system" fn bind_buffer(target: u32, buffer: u32) {
+        log(format!("BindBuffer(0x{:04X}, {})", target, buffer));
+    }
+
+    extern "system" fn buffer_data(target: u32, size: isize, data: *const c_void, usage: u32) {
+        let data = unsafe { slice::from_raw_parts(data as *const u8, size as usize) };
+        log(format!(
+            "BufferData(0x{:04X}, {:?}, 0x{:04X})",
+            target, data, usage
+        ));
+    }
+
+    extern "system" fn delete_buffers(n: i32, buffers: *const u32) {
+        let buffers = unsafe { slice::from_raw_parts(buffers, n as usize) };
+        log(format!("DeleteBuffers({:?})", buffers));
+    }
+
+    extern "system" fn create_program() -> u32 {
+        log("CreateProgram()".to_string());
+        next_name()
+    }
+
+    extern "system" fn use_program(program: u32) {
+        log(format!("UseProgram({})", program));
+    }
+
+    extern "system" fn get_uniform_location(program: u32, name: *const c_char) -> i32 {
+        let name = unsafe { CStr::from_ptr(name) };
+        log(format!("GetUniformLocation({}, {:?})", program, name));
+        4
+    }
+
+    extern "system" fn get_integerv(pname: u32, data: *mut i32) {
+        unsafe { *data = 0 };
+        log(format!("GetIntegerv(0x{:04X})", pname));
+    }
+
+    extern "system" fn clear_color(red: f32, green: f32, blue: f32, alpha: f32) {
+        log(format!(
+            "ClearColor({}, {}, {}, {})",
+            red, green, blue, alpha
+        ));
+    }
+
+    extern "system" fn vertex_attrib_pointer(
+        index: u32,
+        size: i32,
+        ty: u32,
+        normalized: u8,
+        stride: i32,
+        pointer: *const c_void,
+    ) {
+        log(format!(
+            "VertexAttribPointer({}, {}, 0x{:04X}, {}, {}, {:?})",
+            index, size, ty, normalized, stride, pointer
+        ));
+    }
+
+    fn load(symbol: &str) -> *const c_void {
+        match symbol {
+            "glGenBuffers" => gen_buffers as *const c_void,
+            "glBindBuffer" => bind_buffer as *const c_void,
+            "glBufferData" => buffer_data as *const c_void,
+            "glDeleteBuffers" => delete_buffers as *const c_void,
+            "glCreateProgram" => create_program as *const c_void,
+            "glUseProgram" => use_program as *const c_void,
+            "glGetUniformLocation" => get_uniform_location as *const c_void,
+            "glGetIntegerv" => get_integerv as *const c_void,
+            "glClearColor" => clear_color as *const c_void,
+            "glVertexAttribPointer" => vertex_attrib_pointer as *const c_void,
+            _ => ptr::null(),
+        }
+    }
+
+    /// Records a trace of some calls to the fake commands, which name objects from 1.
+    fn record() -> Vec<u8> {
+        reset(1);
+        let trace = SharedBuf::default();
+        let mut gl = gl_trace::Gl::load_with(load);
+        gl.start_trace(trace.clone()).unwrap();
+
+        let mut buffers = [0; 2];
+        let mut viewport = [0; 4];
+        unsafe {
+            gl.GenBuffers(2, buffers.as_mut_ptr());
+            gl.BindBuffer(gl_trace::ARRAY_BUFFER, buffers[1]);
+            gl.BufferData(
+                gl_trace::ARRAY_BUFFER,
+                3,
+                [1u8, 2, 3].as_ptr() as *const _,
+                gl_trace::STATIC_DRAW,
+            );
+            let program = gl.CreateProgram();
+            gl.UseProgram(program);
+            gl.GetUniformLocation(program, b"color\0".as_ptr() as *const _);
+            gl.GetIntegerv(gl_trace::VIEWPORT, viewport.as_mut_ptr());
+            gl.ClearColor(0.0, 0.5, 1.0, 1.0);
+            gl.DeleteBuffers(2, buffers.as_ptr());
+        }
+        gl.finish_trace().unwrap();
+
+        let trace = trace.0.lock().unwrap().clone();
+        assert_eq!(calls().len(), 9);
+        trace
+    }
+
+    /// The calls that the replay of the trace from `record` makes, when objects are named from
+    /// 100. `GetIntegerv` is skipped, since the trace doesn't have the memory that it writes.
+    fn replayed_calls() -> Vec<&'static str> {
+        vec![
+            "GenBuffers(2)",
+            "BindBuffer(0x8892, 101)",
+            "BufferData(0x8892, [1, 2, 3], 0x88E4)",
+            "CreateProgram()",
+            "UseProgram(102)",
+            "GetUniformLocation(102, \"color\")",
+            "ClearColor(0, 0.5, 1, 1)",
+            "DeleteBuffers([100, 101])",
+        ]
+    }
+
+    #[test]
+    fn test_readers_agree() {
+        let trace = record();
+        let mut reader = TraceReader::new(&trace[..]).unwrap();
+        let mut generator_reader = gl_generator::TraceReader::new(&trace[..]).unwrap();
+        assert_eq!(reader.api(), generator_reader.api());
+        assert_eq!(
+            format!("{:?}", reader.commands()),
+            format!("{:?}", generator_reader.commands())
+        );
+        loop {
+            let call = reader.read_call();
+            let generator_call = generator_reader.read_call();
+            assert_eq!(format!("{:?}", call), format!("{:?}", generator_call));
+            if let Ok(None) = call {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_replay_struct() {
+        let trace = record();
+        let gl = gl::Gl::load_with(load);
+        let mut names = NameMap::new();
+
+        reset(100);
+        let count =
+            unsafe { gl::replay::replay(&gl, TraceReader::new(&trace[..]).unwrap(), &mut names) };
+        assert_eq!(count, Ok(8));
+        assert_eq!(calls(), replayed_calls());
+
+        assert_eq!(names.get("buffer", 1), Some(100));
+        assert_eq!(names.get("buffer", 2), Some(101));
+        assert_eq!(names.get("program", 3), Some(102));
+    }
+
+    #[test]
+    fn test_replay_typed_handles() {
+        let trace = record();
+        let gl = gl_typed::Gl::load_with(load);
+        let mut names = gl_typed::replay::NameMap::new();
+
+        reset(100);
+        let trace = gl_typed::replay::TraceReader::new(&trace[..]).unwrap();
+        let count = unsafe { gl_typed::replay::replay(&gl, trace, &mut names) };
+        assert_eq!(count, Ok(8));
+        assert_eq!(calls(), replayed_calls());
+    }
+
+    #[test]
+    fn test_replay_global() {
+        let trace = record();
+        gl_global::load_with(load);
+        let mut names = gl_global::replay::NameMap::new();
+
+        reset(100);
+        let trace = gl_global::replay::TraceReader::new(&trace[..]).unwrap();
+        let count = unsafe { gl_global::replay::replay(trace, &mut names) };
+        assert_eq!(count, Ok(8));
+        assert_eq!(calls(), replayed_calls());
+    }
+
+    #[test]
+    fn test_replay_addresses() {
+        let gl = gl::Gl::load_with(load);
+        let call = || TraceCall {
+            command: 0,
+            args: vec![
+                TraceValue::U32(0),
+                TraceValue::I32(3),
+                TraceValue::U32(gl::FLOAT),
+                TraceValue::U8(0),
+                TraceValue::I32(0),
+                TraceValue::Pointer(16),
+            ],
+            ret: None,
+        };
+
+        reset(100);
+        let mut names = NameMap::new();
+        let result =
+            unsafe { gl::replay::replay_call(&gl, "glVertexAttribPointer", call(), &mut names) };
+        assert_eq!(
+            result,
+            Err(ReplayError::InvalidArgument {
+                command: "glVertexAttribPointer".to_string(),
+                index: 5,
+            })
+        );
+        assert!(calls().is_empty());
+
+        let mut names = NameMap::new().pass_addresses(true);
+        let result =
+            unsafe { gl::replay::replay_call(&gl, "glVertexAttribPointer", call(), &mut names) };
+        assert_eq!(result, Ok(true));
+        assert_eq!(calls(), ["VertexAttribPointer(0, 3, 0x1406, 0, 0, 0x10)"]);
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let gl = gl::Gl::load_with(load);
+        let mut names = NameMap::new();
+        let call = |args| TraceCall {
+            command: 0,
+            args,
+            ret: None,
+        };
+
+        let result =
+            unsafe { gl::replay::replay_call(&gl, "glNotACommand", call(vec![]), &mut names) };
+        assert_eq!(
+            result,
+            Err(ReplayError::UnknownCommand("glNotACommand".to_string()))
+        );
+
+        let args = vec![TraceValue::F32(1.0), TraceValue::U32(1)];
+        let result =
+            unsafe { gl::replay::replay_call(&gl, "glBindBuffer", call(args), &mut names) };
+        assert_eq!(
+            result,
+            Err(ReplayError::InvalidArgument {
+                command: "glBindBuffer".to_string(),
+                index: 0,
+            })
+        );
+
+        let trace = record();
+        let trace = TraceReader::new(&trace[..trace.len() - 1]).unwrap();
+        let result = unsafe { gl::replay::replay(&gl, trace, &mut names) };
+        assert_eq!(result, Err(ReplayError::Trace(TraceError::Truncated)));
+    }
+}
+
+/// Reading the arguments of single calls, without the fake commands.
+#[cfg(test)]
+mod replay_args {
+    use super::gl::replay::{NameMap, ReplayArgs, ReplayError, TraceCall, TraceValue};
+    use std::slice;
+
+    fn call(args: Vec<TraceValue>, ret: Option<TraceValue>) -> TraceCall {
+        TraceCall {
+            command: 0,
+            args,
+            ret,
+        }
+    }
+
+    /// A buffer of object names.
+    fn buffer(names: &[u32]) -> TraceValue {
+        TraceValue::Buffer {
+            address: 0x1000,
+            data: names.iter().flat_map(|name| name.to_ne_bytes()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_name_map() {
+        let mut names = NameMap::new();
+        names.insert("buffer", 1, 7);
+        names.insert("texture", 1, 9);
+        assert_eq!(names.get("buffer", 1), Some(7));
+        assert_eq!(names.get("texture", 1), Some(9));
+        assert_eq!(names.get("buffer", 2), None);
+        assert_eq!(names.get("program", 1), None);
+    }
+
+    #[test]
+    fn test_scalars() {
+        let mut names = NameMap::new();
+        let call = call(
+            vec![TraceValue::I32(-1), TraceValue::F32(0.5), TraceValue::U8(1)],
+            None,
+        );
+        let mut args = ReplayArgs::new("glTest", call, &mut names);
+        assert_eq!(args.arg::<i32>(0), Ok(-1));
+        assert_eq!(args.arg::<f32>(1), Ok(0.5));
+        assert_eq!(args.arg::<u8>(2), Ok(1));
+        let invalid = |index| ReplayError::InvalidArgument {
+            command: "glTest".to_string(),
+            index,
+        };
+        assert_eq!(args.arg::<u32>(1), Err(invalid(1)));
+        assert_eq!(args.arg::<f32>(0), Err(invalid(0)));
+        assert_eq!(args.arg::<u32>(3), Err(invalid(3)));
+    }
+
+    #[test]
+    fn test_pointers() {
+        let mut names = NameMap::new();
+        let call = call(
+            vec![
+                TraceValue::Pointer(0),
+                TraceValue::Pointer(16),
+                TraceValue::Buffer {
+                    address: 0x1000,
+                    data: vec![1, 2, 3],
+                },
+                TraceValue::String {
+                    address: 0x2000,
+                    data: b"color".to_vec(),
+                },
+            ],
+            None,
+        );
+        let mut args = ReplayArgs::new("glTest", call, &mut names);
+        assert!(args.arg::<*const u8>(0).unwrap().is_null());
+        assert!(!args.points_to_unknown(0));
+        assert_eq!(
+            args.arg::<*const u8>(1),
+            Err(ReplayError::InvalidArgument {
+                command: "glTest".to_string(),
+                index: 1,
+            })
+        );
+        assert!(args.points_to_unknown(1));
+
+        let buffer: *const u8 = args.arg(2).unwrap();
+        assert_eq!(unsafe { slice::from_raw_parts(buffer, 3) }, [1, 2, 3]);
+        assert!(!args.points_to_unknown(2));
+
+        let string: *const u8 = args.arg(3).unwrap();
+        assert_eq!(unsafe { slice::from_raw_parts(string, 6) }, b"color\0");
+    }
+
+    #[test]
+    fn test_addresses() {
+        let mut names = NameMap::new().pass_addresses(true);
+        names.insert("sync", 16, 32);
+        let values = vec![TraceValue::Pointer(16), TraceValue::Pointer(16)];
+        let mut args = ReplayArgs::new("glTest", call(values.clone(), None), &mut names);
+        assert_eq!(args.arg::<*const u8>(0), Ok(16 as *const u8));
+        args.map_name(1, "sync").unwrap();
+        assert_eq!(args.arg::<*const u8>(1), Ok(32 as *const u8));
+
+        let mut names = NameMap::new();
+        let mut args = ReplayArgs::new("glTest", call(values, None), &mut names);
+        assert!(args.arg::<*const u8>(0).is_err());
+        args.map_name(1, "sync").unwrap();
+        assert_eq!(args.arg::<*const u8>(1), Ok(16 as *const u8));
+    }
+
+    #[test]
+    fn test_map_names() {
+        let mut names = NameMap::new();
+        names.insert("buffer", 1, 7);
+        names.insert("buffer", 2, 8);
+        let call = call(
+            vec![TraceValue::U32(2), buffer(&[1, 2, 3]), TraceValue::U32(0)],
+            None,
+        );
+        let mut args = ReplayArgs::new("glTest", call, &mut names);
+        args.map_name(0, "buffer").unwrap();
+        args.map_names(1, "buffer", 4).unwrap();
+        args.map_name(2, "buffer").unwrap();
+        assert_eq!(args.arg::<u32>(0), Ok(8));
+        let buffer: *const u32 = args.arg(1).unwrap();
+        assert_eq!(unsafe { slice::from_raw_parts(buffer, 3) }, [7, 8, 3]);
+        assert_eq!(args.arg::<u32>(2), Ok(0));
+    }
+
+    #[test]
+    fn test_insert_names() {
+        let mut names = NameMap::new();
+        {
+            let call = call(vec![TraceValue::I32(2), buffer(&[1, 2])], None);
+            let mut args = ReplayArgs::new("glGenBuffers", call, &mut names);
+            let buffer: *mut u32 = args.arg(1).unwrap();
+            unsafe {
+                *buffer = 5;
+                *buffer.add(1) = 6;
+            }
+            args.insert_names(1, "buffer", 4).unwrap();
+        }
+        {
+            let call = call(vec![], Some(TraceValue::U32(3)));
+            let mut args = ReplayArgs::new("glCreateProgram", call, &mut names);
+            args.insert_return("program", 10);
+        }
+        assert_eq!(names.get("buffer", 1), Some(5));
+        assert_eq!(names.get("buffer", 2), Some(6));
+        assert_eq!(names.get("program", 3), Some(10));
+    }
+}
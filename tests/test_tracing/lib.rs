@@ -0,0 +1,261 @@
+// Copyright 2015 Brendan Zabarauskas and the gl-rs developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracing calls through the bindings written by `TracingGenerator` to fake commands, and
+//! reading the traces back with `TraceReader`.
+#![deny(warnings)]
+
+include!(concat!(env!("OUT_DIR"), "/test_tracing.rs"));
+
+#[cfg(test)]
+mod tests {
+    extern crate gl_generator;
+
+    use self::gl_generator::{TraceCall, TraceError, TraceReader, TraceValue};
+    use super::*;
+    use std::ffi::CStr;
+    use std::io;
+    use std::os::raw::{c_char, c_void};
+    use std::ptr;
+    use std::sync::{Arc, Mutex};
+
+    /// A writer whose bytes can be read after it has been given to `start_trace`.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A writer that fails after its first write.
+    struct FailingWriter(bool);
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            match self.0 {
+                false => {
+                    self.0 = true;
+                    Ok(buf.len())
+                },
+                true => Err(io::Error::other("disk full")),
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    extern "system" fn bind_texture(_: u32, _: u32) {}
+
+    extern "system" fn gen_buffers(n: i32, buffers: *mut u32) {
+        for i in 0..n as usize {
+            unsafe { *buffers.add(i) = i as u32 + 1 };
+        }
+    }
+
+    extern "system" fn buffer_data(_: u32, _: isize, _: *const c_void, _: u32) {}
+
+    extern "system" fn get_uniform_location(_: u32, name: *const c_char) -> i32 {
+        unsafe { CStr::from_ptr(name).to_bytes().len() as i32 }
+    }
+
+    extern "system" fn clear_color(_: f32, _: f32, _: f32, _: f32) {}
+
+    extern "system" fn object_label(_: u32, _: u32, _: i32, _: *const c_char) {}
+
+    fn load(symbol: &str) -> *const c_void {
+        match symbol {
+            "glBindTexture" => bind_texture as *const c_void,
+            "glGenBuffers" => gen_buffers as *const c_void,
+            "glBufferData" => buffer_data as *const c_void,
+            "glGetUniformLocation" => get_uniform_location as *const c_void,
+            "glClearColor" => clear_color as *const c_void,
+            "glObjectLabel" => object_label as *const c_void,
+            _ => ptr::null(),
+        }
+    }
+
+    /// Reads a trace, with the name of the command of each call.
+    fn read(trace: &[u8]) -> Vec<(String, TraceCall)> {
+        let mut reader = TraceReader::new(trace).unwrap();
+        assert_eq!(reader.api(), "gl");
+        let mut calls = Vec::new();
+        while let Some(call) = reader.read_call().unwrap() {
+            calls.push((reader.commands()[call.command].name.clone(), call));
+        }
+        calls
+    }
+
+    #[test]
+    fn test_trace_calls() {
+        let trace = SharedBuf::default();
+        let mut gl = gl::Gl::load_with(load);
+        gl.start_trace(trace.clone()).unwrap();
+
+        let mut buffers = [0; 2];
+        let data = [1u8, 2, 3];
+        let location = unsafe {
+            gl.BindTexture(gl::TEXTURE_2D, 7);
+            gl.GenBuffers(2, buffers.as_mut_ptr());
+            gl.BufferData(
+                gl::ARRAY_BUFFER,
+                3,
+                data.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            );
+            gl.BufferData(gl::ARRAY_BUFFER, 3, ptr::null(), gl::STATIC_DRAW);
+            gl.ClearColor(0.0, 0.5, 1.0, 1.0);
+            gl.GetUniformLocation(3, b"color\0".as_ptr() as *const _)
+        };
+        gl.finish_trace().unwrap();
+        assert_eq!(location, 5);
+
+        let calls = read(&trace.0.lock().unwrap());
+        let names: Vec<_> = calls.iter().map(|(name, _)| &name[..]).collect();
+        assert_eq!(
+            names,
+            [
+                "glBindTexture",
+                "glGenBuffers",
+                "glBufferData",
+                "glBufferData",
+                "glClearColor",
+                "glGetUniformLocation",
+            ]
+        );
+
+        assert_eq!(
+            calls[0].1.args,
+            [TraceValue::U32(gl::TEXTURE_2D), TraceValue::U32(7)]
+        );
+        assert_eq!(calls[0].1.ret, None);
+
+        // the names written by `GenBuffers` are recorded
+        assert_eq!(calls[1].1.args[0], TraceValue::I32(2));
+        assert_eq!(
+            calls[1].1.args[1],
+            TraceValue::Buffer {
+                address: buffers.as_ptr() as u64,
+                data: vec![1, 0, 0, 0, 2, 0, 0, 0],
+            }
+        );
+
+        assert_eq!(calls[2].1.args[2].data(), Some(&data[..]));
+        assert_eq!(calls[3].1.args[2], TraceValue::Pointer(0));
+        assert_eq!(calls[4].1.args[1], TraceValue::F32(0.5));
+
+        let name = &calls[5].1.args[1];
+        assert_eq!(name.data(), Some(&b"color"[..]));
+        assert_eq!(calls[5].1.ret, Some(TraceValue::I32(5)));
+    }
+
+    #[test]
+    fn test_trace_labels() {
+        let trace = SharedBuf::default();
+        let mut gl = gl::Gl::load_with(load);
+        gl.start_trace(trace.clone()).unwrap();
+
+        // only the first `length` bytes are read when it isn't negative, so the label doesn't
+        // need a nul
+        let label = b"vertices";
+        unsafe {
+            gl.ObjectLabel(gl::BUFFER, 1, 5, label.as_ptr() as *const _);
+            gl.ObjectLabel(gl::BUFFER, 2, -1, b"indices\0".as_ptr() as *const _);
+            gl.ObjectLabel(gl::BUFFER, 3, 0, ptr::null());
+        }
+        gl.finish_trace().unwrap();
+
+        let calls = read(&trace.0.lock().unwrap());
+        assert_eq!(
+            calls[0].1.args[3],
+            TraceValue::String {
+                address: label.as_ptr() as u64,
+                data: b"verti".to_vec(),
+            }
+        );
+        assert_eq!(calls[1].1.args[3].data(), Some(&b"indices"[..]));
+        assert_eq!(calls[2].1.args[3], TraceValue::Pointer(0));
+    }
+
+    #[test]
+    fn test_trace_typed_groups_and_slices() {
+        use gl_typed::groups::TextureTarget;
+
+        let trace = SharedBuf::default();
+        let mut gl = gl_typed::Gl::load_with(load);
+        gl.start_trace(trace.clone()).unwrap();
+
+        let mut buffers = [0; 3];
+        unsafe {
+            gl.BindTexture(TextureTarget::TEXTURE_2D, 7);
+            gl.slices().GenBuffers(&mut buffers);
+        }
+        gl.finish_trace().unwrap();
+
+        let calls = read(&trace.0.lock().unwrap());
+        assert_eq!(calls[0].1.args[0], TraceValue::U32(0x0DE1));
+        assert_eq!(calls[1].1.args[0], TraceValue::I32(3));
+        assert_eq!(calls[1].1.args[1].data().map(|data| data.len()), Some(12));
+    }
+
+    #[test]
+    fn test_untraced_calls() {
+        let trace = SharedBuf::default();
+        let mut gl = gl::Gl::load_with(load);
+        unsafe { gl.BindTexture(gl::TEXTURE_2D, 1) };
+
+        gl.start_trace(trace.clone()).unwrap();
+        let clone = gl.clone();
+        unsafe { clone.BindTexture(gl::TEXTURE_2D, 2) };
+        gl.finish_trace().unwrap();
+        unsafe { gl.BindTexture(gl::TEXTURE_2D, 3) };
+        assert!(gl.finish_trace().is_ok());
+
+        let calls = read(&trace.0.lock().unwrap());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].1.args[1], TraceValue::U32(2));
+    }
+
+    #[test]
+    fn test_trace_write_error() {
+        let mut gl = gl::Gl::load_with(load);
+        gl.start_trace(FailingWriter(false)).unwrap();
+        unsafe {
+            gl.BindTexture(gl::TEXTURE_2D, 1);
+            gl.BindTexture(gl::TEXTURE_2D, 2);
+        }
+        let err = gl.finish_trace().unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_truncated_trace() {
+        let trace = SharedBuf::default();
+        let mut gl = gl::Gl::load_with(load);
+        gl.start_trace(trace.clone()).unwrap();
+        unsafe { gl.BindTexture(gl::TEXTURE_2D, 1) };
+        gl.finish_trace().unwrap();
+
+        let trace = trace.0.lock().unwrap();
+        let mut reader = TraceReader::new(&trace[..trace.len() - 1]).unwrap();
+        assert_eq!(reader.read_call(), Err(TraceError::Truncated));
+    }
+}
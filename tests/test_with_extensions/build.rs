@@ -17,6 +17,7 @@ extern crate gl_generator;
 use gl_generator::*;
 use std::env;
 use std::fs::File;
+use std::io::prelude::*;
 use std::path::*;
 
 fn main() {
@@ -27,4 +28,45 @@ fn main() {
     Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, extensions)
         .write_bindings(GlobalGenerator, &mut file)
         .unwrap();
+
+    let mut file = File::create(Path::new(&dest).join("all_extensions.rs")).unwrap();
+    let apis = [
+        ("gl", Api::Gl, (4, 6)),
+        ("gles2", Api::Gles2, (3, 2)),
+        ("glx", Api::Glx, (1, 4)),
+        ("wgl", Api::Wgl, (1, 0)),
+        ("egl", Api::Egl, (1, 5)),
+    ];
+    for &(name, api, version) in &apis {
+        let registry = RegistryBuilder::new(api, version)
+            .profile(Profile::Compatibility)
+            .all_extensions()
+            .build()
+            .unwrap();
+        writeln!(&mut file, "pub mod {} {{", name).unwrap();
+        if api == Api::Egl {
+            writeln!(&mut file, "{}", build_egl_symbols()).unwrap();
+        }
+        registry.write_bindings(GlobalGenerator, &mut file).unwrap();
+        writeln!(&mut file, "}}").unwrap();
+    }
+}
+
+fn build_egl_symbols() -> &'static str {
+    "
+        #![allow(non_camel_case_types)]
+
+        use std::os::raw;
+
+        pub type khronos_utime_nanoseconds_t = raw::c_int;
+        pub type khronos_uint64_t = u64;
+        pub type khronos_ssize_t = isize;
+        pub type EGLNativeDisplayType = *const raw::c_void;
+        pub type EGLNativePixmapType = *const raw::c_void;
+        pub type EGLNativeWindowType = *const raw::c_void;
+        pub type EGLint = raw::c_int;
+        pub type NativeDisplayType = *const raw::c_void;
+        pub type NativePixmapType = *const raw::c_void;
+        pub type NativeWindowType = *const raw::c_void;
+    "
 }
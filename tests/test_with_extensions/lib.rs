@@ -16,6 +16,10 @@ pub mod gl {
     include!(concat!(env!("OUT_DIR"), "/test_symbols.rs"));
 }
 
+pub mod all_extensions {
+    include!(concat!(env!("OUT_DIR"), "/all_extensions.rs"));
+}
+
 pub fn compile_test_symbols_exist() {
     let _ = gl::DebugMessageControlARB;
     let _ = gl::DebugMessageInsertARB;
@@ -45,3 +49,16 @@ pub fn compile_test_symbols_exist() {
     assert_eq!(gl::DEBUG_SEVERITY_MEDIUM_ARB, 0x9147);
     assert_eq!(gl::DEBUG_SEVERITY_LOW_ARB, 0x9148);
 }
+
+pub fn compile_test_all_extensions() {
+    use all_extensions::{egl, gl, gles2, glx, wgl};
+
+    let _ = gl::WindowRectanglesEXT;
+    let _ = gles2::WindowRectanglesEXT;
+    let _: gl::types::GLint = gl::NEXT_BUFFER_NV;
+    let _: gl::types::GLint = gl::SKIP_COMPONENTS1_NV;
+    let _ = glx::AssociateDMPbufferSGIX;
+    let _ = glx::CreateGLXVideoSourceSGIX;
+    let _ = wgl::CreateContext;
+    let _ = egl::CreateContext;
+}